@@ -18,6 +18,8 @@ pub struct Anim {
     frame_time: TimeTick,
     /// [`TimeTick`] elapsed since the previous frame.
     elapsed_time: TimeTick,
+    /// Mirror frames horizontally and vertically.
+    flip: (bool, bool),
 }
 
 impl Anim {
@@ -40,7 +42,15 @@ impl Anim {
             frame_time != 0 || frames.len() == 1,
             builder::Error::msg("Frame time specified as 0, but frame count > 0")
         );
-        Ok(Self { frames, current_frame: 0, frame_time, elapsed_time: 0 })
+        let flip_x = cfg
+            .take_opt("flip_x")
+            .change_context(builder::Error::msg("Failed to init anim horizontal flip flag"))?
+            .unwrap_or(false);
+        let flip_y = cfg
+            .take_opt("flip_y")
+            .change_context(builder::Error::msg("Failed to init anim vertical flip flag"))?
+            .unwrap_or(false);
+        Ok(Self { frames, current_frame: 0, frame_time, elapsed_time: 0, flip: (flip_x, flip_y) })
     }
 
     /// Update animation.
@@ -73,6 +83,12 @@ impl Anim {
         }
         &self.frames[self.current_frame]
     }
+
+    /// Get frames mirroring: (horizontal, vertical).
+    #[must_use]
+    pub const fn get_flip(&self) -> (bool, bool) {
+        self.flip
+    }
 }
 
 impl ParseFormValue for Anim {
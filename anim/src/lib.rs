@@ -4,11 +4,11 @@
 use anim::Anim;
 use builder::config::Config;
 use error_stack::{bail, report, Result, ResultExt};
-use renderer::Drawable;
-use resources::TextureId;
+use renderer::{BlendMode, Drawable};
+use resources::{LoadOptions, ShaderId, TextureId};
 use scene::TimeTick;
-use std::{collections::HashMap, fmt::Debug, hash::Hash, path::PathBuf};
-use utils::{rect::Rectf, vec2::Vec2f};
+use std::{collections::HashMap, fmt::Debug, hash::Hash, path::PathBuf, str::FromStr};
+use utils::{color::Color, rect::Rectf, vec2::Vec2f};
 
 pub mod anim;
 
@@ -112,6 +112,14 @@ pub struct Animator<S: Eq + Hash + Copy + Debug, E: Eq + Hash + Copy + Debug> {
     rect: Rectf,
     /// Current texture rect.
     texture_rect: Rectf,
+    /// Current frame mirroring: (horizontal, vertical).
+    flip: (bool, bool),
+    /// Color, that multiplies sprite colors.
+    tint: Color,
+    /// Sprite blend mode.
+    blend_mode: BlendMode,
+    /// Sprite shader. `None` if sprite is drawn with default shader.
+    shader: Option<ShaderId>,
 }
 
 impl<S: Eq + Hash + Copy + Debug, E: Eq + Hash + Copy + Debug> Animator<S, E> {
@@ -128,7 +136,11 @@ impl<S: Eq + Hash + Copy + Debug, E: Eq + Hash + Copy + Debug> Animator<S, E> {
             .take::<PathBuf>("texture")
             .change_context(builder::Error::msg("Failed to init texture name"))?;
         let texture_name = texture_path.display().to_string();
-        res.load("texture", &texture_name, &texture_path)
+        let texture_opts = cfg
+            .take_opt::<LoadOptions>("texture_options")
+            .change_context(builder::Error::msg("Failed to init texture load options"))?
+            .unwrap_or_default();
+        res.load("texture", &texture_name, &texture_path, &texture_opts)
             .change_context(builder::Error::msg("Failed to load texture"))?;
         let texture = res
             .get_texture(&texture_name)
@@ -138,6 +150,29 @@ impl<S: Eq + Hash + Copy + Debug, E: Eq + Hash + Copy + Debug> Animator<S, E> {
             .change_context(builder::Error::msg("Failed to load animations"))?;
         let rect =
             cfg.take("rect").change_context(builder::Error::msg("Failed to init animator rect"))?;
+        let tint = cfg
+            .take_opt("tint")
+            .change_context(builder::Error::msg("Failed to init animator tint"))?
+            .unwrap_or(Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 });
+        let blend_mode = cfg
+            .take_opt::<String>("blend_mode")
+            .change_context(builder::Error::msg("Failed to init animator blend mode"))?
+            .map(|s| BlendMode::from_str(&s))
+            .transpose()
+            .change_context(builder::Error::msg("Failed to parse animator blend mode"))?
+            .unwrap_or_default();
+        let shader = cfg
+            .take_opt::<PathBuf>("shader")
+            .change_context(builder::Error::msg("Failed to init animator shader"))?
+            .map(|path| {
+                let name = path.display().to_string();
+                if res.get_shader(&name).is_err() {
+                    res.load("shader", &name, &path, &LoadOptions::default())?;
+                }
+                res.get_shader(&name)
+            })
+            .transpose()
+            .change_context(builder::Error::msg("Failed to load animator shader"))?;
 
         let mut anims = HashMap::new();
         for (k, v) in animator_cfg.anim_names {
@@ -149,21 +184,24 @@ impl<S: Eq + Hash + Copy + Debug, E: Eq + Hash + Copy + Debug> Animator<S, E> {
             }
         }
 
-        let texture_rect = *anims
-            .get(&animator_cfg.state)
-            .ok_or_else(|| {
-                report!(builder::Error::msg(format!(
-                    "Failed to get animation for state: {:?}",
-                    animator_cfg.state
-                )))
-            })?
-            .get_rect();
+        let init_anim = anims.get(&animator_cfg.state).ok_or_else(|| {
+            report!(builder::Error::msg(format!(
+                "Failed to get animation for state: {:?}",
+                animator_cfg.state
+            )))
+        })?;
+        let texture_rect = *init_anim.get_rect();
+        let flip = init_anim.get_flip();
 
         Ok(Self {
             rect,
             anims,
             texture,
             texture_rect,
+            flip,
+            tint,
+            blend_mode,
+            shader,
             state: animator_cfg.state,
             timeout_event: animator_cfg.timeout_event,
             transient_map: animator_cfg.transient_map,
@@ -203,12 +241,28 @@ impl<S: Eq + Hash + Copy + Debug, E: Eq + Hash + Copy + Debug> Animator<S, E> {
         })?;
         let new_cycle = anim.update(dt);
         self.texture_rect = *anim.get_rect();
+        self.flip = anim.get_flip();
         if new_cycle {
             self.handle_event(self.timeout_event)?;
         }
         Ok(())
     }
 
+    /// Set color, that multiplies sprite colors. Used for hit flashes and fading.
+    pub const fn set_tint(&mut self, tint: Color) {
+        self.tint = tint;
+    }
+
+    /// Set sprite blend mode.
+    pub const fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// Set sprite shader. `None` restores default shader.
+    pub const fn set_shader(&mut self, shader: Option<ShaderId>) {
+        self.shader = shader;
+    }
+
     /// Set animator position.
     pub fn set_pos(&mut self, pos: Vec2f) {
         self.rect.x = pos.x;
@@ -218,7 +272,25 @@ impl<S: Eq + Hash + Copy + Debug, E: Eq + Hash + Copy + Debug> Animator<S, E> {
 
 impl<S: Eq + Hash + Copy + Debug, E: Eq + Hash + Copy + Debug> Drawable for Animator<S, E> {
     fn draw(&self, renderer: &mut dyn renderer::Renderer) {
-        renderer.draw_img(&self.rect, self.texture, &self.texture_rect);
+        let (flip_x, flip_y) = self.flip;
+        let (w, h) = (self.rect.w, self.rect.h);
+        renderer.push_state();
+        renderer.set_blend_mode(self.blend_mode);
+        renderer.set_shader(self.shader);
+        if flip_x || flip_y {
+            renderer.translate(self.rect.x + w / 2.0, self.rect.y + h / 2.0);
+            renderer.scale(if flip_x { -1.0 } else { 1.0 }, if flip_y { -1.0 } else { 1.0 });
+            renderer.translate(-w / 2.0, -h / 2.0);
+        } else {
+            renderer.translate(self.rect.x, self.rect.y);
+        }
+        renderer.draw_img_tinted(
+            &[0.0, 0.0, w, h].into(),
+            self.texture,
+            &self.texture_rect,
+            &self.tint,
+        );
+        renderer.pop_state();
     }
 }
 
@@ -110,6 +110,29 @@ impl Config {
         Ok(Some(val))
     }
 
+    /// Get option value without taking it.
+    /// if the required field is in the config, it is returned as `Some(T)`, otherwise `None`.
+    ///
+    /// # Errors
+    /// Return error if required field exist, but has unexpected type.
+    pub fn get_opt<T: ParseFormValue>(&self, key: &str) -> Result<Option<T>, Error> {
+        let Some(val) = self.cfg.get(key) else {
+            return Ok(None);
+        };
+        let val = Value { val: val.clone(), path: self.file.clone() };
+        let val = T::parse_val(val).change_context(Error::msg(format!(
+            "Failed to parse field \"{}\" as {}",
+            key,
+            std::any::type_name::<T>()
+        )))?;
+        Ok(Some(val))
+    }
+
+    /// Set field value. Previous value of the field is replaced.
+    pub fn set<T: Into<config::ValueKind>>(&mut self, key: &str, value: T) {
+        self.cfg.insert(key.to_owned(), config::Value::new(None, value));
+    }
+
     /// Take value.
     /// if the required field is in the config, it is retrieved and returned.
     ///
@@ -2,10 +2,12 @@
 use std::{
     collections::HashMap,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
 use super::Config;
 use error_stack::{bail, ensure, report, Result, ResultExt};
+use resources::{Filter, FontStyle, LoadOptions, Wrap};
 
 /// Parse error.
 #[derive(Debug, thiserror::Error)]
@@ -139,6 +141,31 @@ impl ParseFormValue for Config {
     }
 }
 
+impl ParseFormValue for LoadOptions {
+    fn parse_val(value: Value) -> Result<Self, Error> {
+        let mut cfg = Config::parse_val(value)?;
+        let mut opts = Self::default();
+        let err = || Error::msg("Failed to parse resource load options");
+        if let Some(filter) = cfg.take_opt::<String>("filter").change_context_lazy(err)? {
+            opts.filter = Filter::from_str(&filter).change_context_lazy(err)?;
+        }
+        if let Some(wrap) = cfg.take_opt::<String>("wrap").change_context_lazy(err)? {
+            opts.wrap = Wrap::from_str(&wrap).change_context_lazy(err)?;
+        }
+        let mut take_flag = |name| -> Result<bool, Error> {
+            Ok(cfg.take_opt(name).change_context_lazy(err)?.unwrap_or(false))
+        };
+        opts.mipmaps = take_flag("mipmaps")?;
+        opts.srgb = take_flag("srgb")?;
+        opts.premultiplied_alpha = take_flag("premultiplied_alpha")?;
+        if let Some(style) = cfg.take_opt::<String>("style").change_context_lazy(err)? {
+            opts.style = FontStyle::from_str(&style).change_context_lazy(err)?;
+        }
+        opts.fallback = cfg.take_opt("fallback").change_context_lazy(err)?.unwrap_or_default();
+        Ok(opts)
+    }
+}
+
 /// Calculate path to include file relative root file.
 ///
 /// # Errors
@@ -162,11 +189,18 @@ fn calc_path(root_file: &str, include_file: &str) -> Result<String, Error> {
 }
 
 #[cfg(test)]
-#[cfg(target_family = "unix")]
 mod tests {
     use super::calc_path;
+    use crate::config::Config;
+    use resources::{Filter, FontStyle, LoadOptions, Wrap};
+
+    fn parse_opts(json: &str) -> error_stack::Result<LoadOptions, crate::config::Error> {
+        let mut cfg = Config::from_json(&format!("{{opts: {json}}}")).unwrap();
+        cfg.take::<LoadOptions>("opts")
+    }
 
     #[test]
+    #[cfg(target_family = "unix")]
     fn calc_path_test() {
         assert_eq!(calc_path("./cfg/cfg.json", "oth.json").unwrap(), "./cfg/oth.json");
         assert_eq!(calc_path("./cfg/cfg.json", "./oth.json").unwrap(), "./cfg/./oth.json");
@@ -177,4 +211,47 @@ mod tests {
         assert_eq!(calc_path("/cfg/cfg.json", "../oth.json").unwrap(), "/cfg/../oth.json");
         assert!(calc_path("/", "./oth.json").is_err());
     }
+
+    #[test]
+    fn load_options_default() {
+        assert_eq!(parse_opts("{mipmaps: false}").unwrap(), LoadOptions::default());
+    }
+
+    #[test]
+    fn load_options_keys() {
+        let opts = parse_opts(
+            r#"{
+                filter: "linear",
+                wrap: "mirrored_repeat",
+                mipmaps: true,
+                srgb: true,
+                premultiplied_alpha: true,
+                style: "bold_italic",
+                fallback: ["emoji", "cjk"],
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(opts.filter, Filter::Linear);
+        assert_eq!(opts.wrap, Wrap::MirroredRepeat);
+        assert!(opts.mipmaps);
+        assert!(opts.srgb);
+        assert!(opts.premultiplied_alpha);
+        assert_eq!(opts.style, FontStyle::BoldItalic);
+        assert_eq!(opts.fallback, vec!["emoji".to_owned(), "cjk".to_owned()]);
+
+        assert_eq!(parse_opts(r#"{filter: "nearest"}"#).unwrap().filter, Filter::Nearest);
+        assert_eq!(parse_opts(r#"{wrap: "clamp"}"#).unwrap().wrap, Wrap::ClampToEdge);
+        assert_eq!(parse_opts(r#"{wrap: "repeat"}"#).unwrap().wrap, Wrap::Repeat);
+        assert_eq!(parse_opts(r#"{style: "italic"}"#).unwrap().style, FontStyle::Italic);
+    }
+
+    #[test]
+    fn load_options_invalid() {
+        assert!(parse_opts(r#"{filter: "bilinear"}"#).is_err());
+        assert!(parse_opts(r#"{wrap: "mirror"}"#).is_err());
+        assert!(parse_opts(r#"{style: "oblique"}"#).is_err());
+        assert!(parse_opts(r#"{filter: 1}"#).is_err());
+        assert!(parse_opts(r#"{mipmaps: "maybe"}"#).is_err());
+        assert!(parse_opts(r#"{fallback: "emoji"}"#).is_err());
+    }
 }
@@ -1,11 +1,9 @@
 //! GUI library usage example
 
-use std::{cell::RefCell, rc::Rc};
-
 use builder::{config::Config, BuildFromCfg};
 use error_stack::{Result, ResultExt};
 use gui::{
-    manager::{widget::Widget, Manager},
+    manager::{widget::TypedId, Manager},
     widget::{Builder, Button, Flag, Label, Panel},
 };
 use renderer::Drawable;
@@ -50,7 +48,7 @@ fn run() -> Result<(), Error> {
 
 struct MainScene {
     gui: Manager,
-    cursor_pos_label: Rc<RefCell<Label>>,
+    cursor_pos_label: TypedId<Label>,
 }
 
 impl Scene for MainScene {
@@ -60,7 +58,9 @@ impl Scene for MainScene {
         _state: &mut dyn State,
     ) -> Result<(), scene::Error> {
         if let Event::MouseMove(x, y) = e {
-            self.cursor_pos_label.borrow_mut().set_text(&format!("Cursor pos: ({x}, {y})"));
+            if let Some(label) = self.gui.tree_mut().cast_mut(self.cursor_pos_label) {
+                label.set_text(&format!("Cursor pos: ({x}, {y})"));
+            }
         }
         self.gui.handle_event(e).change_context(scene::Error::msg("Failed to update gui"))?;
         Ok(())
@@ -81,24 +81,33 @@ impl BuildFromCfg<Box<dyn Scene>> for MainScene {
         let gui_cfg = cfg
             .take::<Config>("gui")
             .change_context(builder::Error::msg("Failed to build scene GUI"))?;
-        let gui = Manager::new(&Builder::default(), res, gui_cfg)
+        let mut gui = Manager::new(&Builder::default(), res, gui_cfg)
             .change_context(builder::Error::msg("Failed to init GUI manager"))?;
 
         let err = || builder::Error::msg("Failed to build scene, required widget not fount");
         gui.get_by_id_cast::<Panel>("middle_panel").change_context_lazy(err)?;
-        let btn = gui.get_by_id_cast::<Button>("hello_button").change_context_lazy(err)?;
-        btn.borrow_mut().click_cb(|button| {
-            println!("Button \"{}\" clicked!", button.get_id());
-        });
+        gui.get_by_id_cast::<Button>("hello_button").change_context_lazy(err)?;
+        gui.connect("hello_button", "clicked", |tree, button, _| {
+            if let Some(button) = tree.get(button) {
+                println!("Button \"{}\" clicked!", button.get_id());
+            }
+        })
+        .change_context_lazy(err)?;
         let cursor_pos_label =
             gui.get_by_id_cast::<Label>("cursor_pos").change_context_lazy(err)?;
 
         let flag_state = gui.get_by_id_cast::<Label>("flag_state").change_context_lazy(err)?;
-        let flag = gui.get_by_id_cast::<Flag>("hello_flag").change_context_lazy(err)?;
-        flag.borrow_mut().change_state_cb(move |flag, state| {
-            flag_state.borrow_mut().set_text(&format!("Flag state: {}", state));
-            println!("Flag \"{}\" change state: {}", flag.get_id(), state);
-        });
+        gui.get_by_id_cast::<Flag>("hello_flag").change_context_lazy(err)?;
+        gui.connect("hello_flag", "value_changed", move |tree, flag, signal| {
+            let state = signal.as_bool().unwrap_or_default();
+            if let Some(label) = tree.cast_mut(flag_state) {
+                label.set_text(&format!("Flag state: {}", state));
+            }
+            if let Some(flag) = tree.get(flag) {
+                println!("Flag \"{}\" change state: {}", flag.get_id(), state);
+            }
+        })
+        .change_context_lazy(err)?;
 
         Ok(Box::new(Self { gui, cursor_pos_label }))
     }
@@ -0,0 +1,116 @@
+//! Arena with generational identifiers.
+//!
+//! Removed slots are reused by new values, but the slot generation is increased, so identifiers
+//! of removed values stay invalid.
+
+/// Generational widget identifier. It is stable while widget exists.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WidgetId {
+    /// Slot index.
+    index: u32,
+    /// Slot generation.
+    generation: u32,
+}
+
+/// Arena slot.
+struct Slot<T> {
+    /// Slot generation. It is increased, when the value is removed.
+    generation: u32,
+    /// Stored value. `None` if slot is free.
+    value: Option<T>,
+}
+
+/// Arena of values with generational identifiers.
+pub struct Arena<T> {
+    /// Slots.
+    slots: Vec<Slot<T>>,
+    /// Indices of free slots.
+    free: Vec<u32>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self { slots: Vec::new(), free: Vec::new() }
+    }
+}
+
+impl<T> Arena<T> {
+    /// Insert value. Returns its identifier.
+    ///
+    /// # Panics
+    /// Panics if arena contains more than `u32::MAX` slots.
+    pub fn insert(&mut self, value: T) -> WidgetId {
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.value = Some(value);
+            return WidgetId { index, generation: slot.generation };
+        }
+        let index = u32::try_from(self.slots.len()).expect("Arena slots overflow");
+        self.slots.push(Slot { generation: 0, value: Some(value) });
+        WidgetId { index, generation: 0 }
+    }
+
+    /// Remove value. Returns `None` if identifier is not valid.
+    pub fn remove(&mut self, id: WidgetId) -> Option<T> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        Some(value)
+    }
+
+    /// Get value. Returns `None` if identifier is not valid.
+    #[must_use]
+    pub fn get(&self, id: WidgetId) -> Option<&T> {
+        let slot = self.slots.get(id.index as usize)?;
+        (slot.generation == id.generation).then_some(slot.value.as_ref()).flatten()
+    }
+
+    /// Get mutable value. Returns `None` if identifier is not valid.
+    #[must_use]
+    pub fn get_mut(&mut self, id: WidgetId) -> Option<&mut T> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        (slot.generation == id.generation).then_some(slot.value.as_mut()).flatten()
+    }
+
+    /// Check if identifier is valid.
+    #[must_use]
+    pub fn contains(&self, id: WidgetId) -> bool {
+        self.get(id).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Arena;
+
+    #[test]
+    fn insert_remove() {
+        let mut arena = Arena::default();
+        let a = arena.insert('a');
+        let b = arena.insert('b');
+        assert_eq!(arena.get(a), Some(&'a'));
+        *arena.get_mut(b).unwrap() = 'c';
+        assert_eq!(arena.get(b), Some(&'c'));
+        assert_eq!(arena.remove(a), Some('a'));
+        assert_eq!(arena.remove(a), None);
+        assert!(!arena.contains(a));
+        assert!(arena.contains(b));
+    }
+
+    #[test]
+    fn generations() {
+        let mut arena = Arena::default();
+        let a = arena.insert(1);
+        arena.remove(a);
+        // Slot is reused, but old identifier stays invalid.
+        let b = arena.insert(2);
+        assert_ne!(a, b);
+        assert_eq!(arena.get(a), None);
+        assert_eq!(arena.get_mut(a), None);
+        assert_eq!(arena.get(b), Some(&2));
+    }
+}
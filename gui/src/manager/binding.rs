@@ -0,0 +1,360 @@
+//! Data binding between widgets and model values.
+//!
+//! Widget config fields `value` (slider), `state` (flag) and `text` (label, textbox) may be bound
+//! to named values of the manager [`Model`]:
+//! ```json
+//! { "type": "slider", "value": "{bind settings.volume}", ... }
+//! { "type": "label", "text": "{bind player.hp} HP", ... }
+//! ```
+//! Field, that contains only one binding, is bound two-way: widget is updated, when model value
+//! is changed, and model value is updated, when user changes the widget value. Field with other
+//! text is bound one-way.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use builder::config::Config;
+use error_stack::{bail, Result, ResultExt};
+
+use super::{
+    widget::{signal::Value, WidgetId},
+    Error, Tree,
+};
+
+/// Binding prefix.
+const BIND_PREFIX: &str = "{bind ";
+
+/// Fields, that may be bound, with placeholder values. Placeholder is passed to the widget
+/// config instead of binding.
+const FIELDS: [(&str, Value); 3] = [
+    ("value", Value::Number(0.0)),
+    ("state", Value::Bool(false)),
+    ("text", Value::Text(String::new())),
+];
+
+/// Shared state of the property.
+struct Shared<T> {
+    /// Property value.
+    value: T,
+    /// Number of value changes.
+    version: u64,
+    /// Revision of the model, that contains the property. It is increased on value change.
+    revision: Option<Rc<Cell<u64>>>,
+}
+
+/// Observable property. Clones of the property share its value.
+pub struct Property<T>(Rc<RefCell<Shared<T>>>);
+
+impl<T> Clone for Property<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Clone + PartialEq> Property<T> {
+    /// Create new property.
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self(Rc::new(RefCell::new(Shared { value, version: 1, revision: None })))
+    }
+
+    /// Create new property of the model with specified revision.
+    fn with_revision(value: T, revision: Rc<Cell<u64>>) -> Self {
+        Self(Rc::new(RefCell::new(Shared { value, version: 1, revision: Some(revision) })))
+    }
+
+    /// Get property value.
+    #[must_use]
+    pub fn get(&self) -> T {
+        self.0.borrow().value.clone()
+    }
+
+    /// Set property value. Version is increased, if the value is changed.
+    pub fn set(&self, value: T) {
+        let mut shared = self.0.borrow_mut();
+        if shared.value != value {
+            shared.value = value;
+            shared.version += 1;
+            if let Some(ref revision) = shared.revision {
+                revision.set(revision.get() + 1);
+            }
+        }
+    }
+
+    /// Get property version. It is increased on each value change.
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.0.borrow().version
+    }
+}
+
+/// Data model: named properties, that widgets are bound to. Clones of the model share its
+/// properties.
+#[derive(Clone, Default)]
+pub struct Model {
+    /// Properties by name.
+    properties: Rc<RefCell<HashMap<String, Property<Value>>>>,
+    /// Number of changes of all properties.
+    revision: Rc<Cell<u64>>,
+}
+
+impl Model {
+    /// Get property value. Returns `None` if property is not set.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.properties.borrow().get(name).map(Property::get)
+    }
+
+    /// Set property value. Property is created, if it is not set.
+    pub fn set<T: Into<Value>>(&self, name: &str, value: T) {
+        let value = value.into();
+        let property = self.properties.borrow().get(name).cloned();
+        match property {
+            Some(property) => property.set(value),
+            None => {
+                let property = Property::with_revision(value, self.revision.clone());
+                self.properties.borrow_mut().insert(name.to_owned(), property);
+                self.revision.set(self.revision.get() + 1);
+            }
+        }
+    }
+
+    /// Get property. Returns `None` if property is not set.
+    #[must_use]
+    pub fn property(&self, name: &str) -> Option<Property<Value>> {
+        self.properties.borrow().get(name).cloned()
+    }
+
+    /// Get property version. Returns 0 if property is not set.
+    fn version(&self, name: &str) -> u64 {
+        self.properties.borrow().get(name).map_or(0, Property::version)
+    }
+
+    /// Get model revision. It is increased on each property change, so bound widgets are not
+    /// synchronized with unchanged model.
+    pub(crate) fn revision(&self) -> u64 {
+        self.revision.get()
+    }
+}
+
+/// Part of bound field.
+#[derive(Debug, PartialEq)]
+enum Part {
+    /// Plain text.
+    Text(String),
+    /// Name of bound property.
+    Bind(String),
+}
+
+/// Bound field template.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Template(Vec<Part>);
+
+impl Template {
+    /// Parse field template. Returns `None` if field does not contain bindings.
+    ///
+    /// # Errors
+    /// Return error if binding is not closed or property name is empty.
+    fn parse(text: &str) -> Result<Option<Self>, Error> {
+        if !text.contains(BIND_PREFIX) {
+            return Ok(None);
+        }
+        let mut parts = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find(BIND_PREFIX) {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_owned()));
+            }
+            rest = &rest[start + BIND_PREFIX.len()..];
+            let Some(end) = rest.find('}') else {
+                bail!(Error::msg(format!("Binding is not closed: {text:?}")));
+            };
+            let name = rest[..end].trim();
+            if name.is_empty() {
+                bail!(Error::msg(format!("Binding property name is empty: {text:?}")));
+            }
+            parts.push(Part::Bind(name.to_owned()));
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_owned()));
+        }
+        Ok(Some(Self(parts)))
+    }
+
+    /// Get property name, if template contains only one binding.
+    fn single(&self) -> Option<&str> {
+        match self.0.as_slice() {
+            [Part::Bind(name)] => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Get names of bound properties.
+    fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().filter_map(|p| match p {
+            Part::Bind(name) => Some(name.as_str()),
+            Part::Text(_) => None,
+        })
+    }
+
+    /// Make field value by model values. Unset properties are rendered as empty text.
+    /// Returns `None` if template contains only one binding and the property is not set.
+    fn render(&self, model: &Model) -> Option<Value> {
+        if let Some(name) = self.single() {
+            return model.get(name);
+        }
+        Some(Value::Text(
+            self.0
+                .iter()
+                .map(|p| match p {
+                    Part::Text(text) => text.clone(),
+                    Part::Bind(name) => model.get(name).map(|v| v.to_string()).unwrap_or_default(),
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// Take bound fields from widget config. Bound fields are replaced by placeholder values.
+///
+/// # Errors
+/// Return error if some binding is not valid.
+pub(crate) fn take_templates(cfg: &mut Config) -> Result<Vec<Template>, Error> {
+    let mut templates = Vec::new();
+    for (field, placeholder) in FIELDS {
+        let Ok(Some(text)) = cfg.get_opt::<String>(field) else {
+            continue;
+        };
+        let Some(template) = Template::parse(&text)
+            .change_context(Error::msg(format!("Failed to parse binding of field {field:?}")))?
+        else {
+            continue;
+        };
+        match placeholder {
+            Value::Bool(v) => cfg.set(field, v),
+            Value::Number(v) => cfg.set(field, v),
+            Value::Text(v) => cfg.set(field, v),
+        }
+        templates.push(template);
+    }
+    Ok(templates)
+}
+
+/// Binding of widget field to model properties.
+pub(crate) struct Binding {
+    /// Bound widget.
+    widget: WidgetId,
+    /// Field template.
+    template: Template,
+    /// Versions of bound properties, that are applied to the widget.
+    versions: Vec<u64>,
+}
+
+impl Binding {
+    /// Create new binding.
+    pub(crate) const fn new(widget: WidgetId, template: Template) -> Self {
+        Self { widget, template, versions: Vec::new() }
+    }
+
+    /// Update widget, if bound properties are changed.
+    /// Returns `None` if widget is removed from the tree, otherwise `true` if widget is updated.
+    pub(crate) fn sync(&mut self, tree: &mut Tree, model: &Model) -> Option<bool> {
+        let widget = tree.get_mut(self.widget)?;
+        let versions: Vec<u64> = self.template.names().map(|n| model.version(n)).collect();
+        if versions == self.versions {
+            return Some(false);
+        }
+        let value = self.template.render(model);
+        if let Some(ref value) = value {
+            widget.set_bound_value(value);
+        }
+        self.versions = versions;
+        Some(value.is_some())
+    }
+
+    /// Update model property by widget value, if the widget is bound two-way.
+    pub(crate) fn update_model(&mut self, widget: WidgetId, value: &Value, model: &Model) {
+        let Some(name) = self.template.single() else {
+            return;
+        };
+        if self.widget == widget {
+            model.set(name, value.clone());
+            self.versions = vec![model.version(name)];
+        }
+    }
+
+    /// Reset applied versions, so widget is updated on next sync.
+    pub(crate) fn reset(&mut self) {
+        self.versions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{take_templates, Model, Part, Property, Template};
+    use crate::manager::widget::signal::Value;
+    use builder::config::Config;
+
+    #[test]
+    fn property() {
+        let p = Property::new(1);
+        let clone = p.clone();
+        let version = p.version();
+        clone.set(1);
+        assert_eq!(p.version(), version);
+        clone.set(2);
+        assert_eq!(p.get(), 2);
+        assert!(p.version() > version);
+    }
+
+    #[test]
+    fn model_revision() {
+        let model = Model::default();
+        let revision = model.revision();
+        model.set("a", 1.0);
+        assert!(model.revision() > revision);
+        let revision = model.revision();
+        model.set("a", 1.0);
+        assert_eq!(model.revision(), revision);
+        model.property("a").unwrap().set(Value::Number(2.0));
+        assert!(model.revision() > revision);
+        assert_eq!(model.get("a"), Some(Value::Number(2.0)));
+    }
+
+    #[test]
+    fn template() {
+        assert_eq!(Template::parse("plain").unwrap(), None);
+        let t = Template::parse("{bind player.hp} HP").unwrap().unwrap();
+        assert_eq!(t.0, [Part::Bind("player.hp".to_owned()), Part::Text(" HP".to_owned())]);
+        assert_eq!(t.single(), None);
+        let single = Template::parse("{bind a}").unwrap().unwrap();
+        assert_eq!(single.single(), Some("a"));
+        assert!(Template::parse("{bind a").is_err());
+        assert!(Template::parse("{bind }").is_err());
+
+        let model = Model::default();
+        assert_eq!(single.render(&model), None);
+        assert_eq!(t.render(&model), Some(Value::Text(" HP".to_owned())));
+        model.set("player.hp", 10.0);
+        model.set("a", true);
+        assert_eq!(t.render(&model), Some(Value::Text("10 HP".to_owned())));
+        assert_eq!(single.render(&model), Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn config() {
+        let mut cfg =
+            Config::from_json(r#"{ "value": "{bind volume}", "text": "Volume", "state": true }"#)
+                .unwrap();
+        let templates = take_templates(&mut cfg).unwrap();
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].single(), Some("volume"));
+        assert_eq!(cfg.take::<f64>("value").unwrap(), 0.0);
+        assert_eq!(cfg.take::<String>("text").unwrap(), "Volume");
+        assert!(cfg.take::<bool>("state").unwrap());
+    }
+}
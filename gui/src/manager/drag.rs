@@ -0,0 +1,235 @@
+//! Drag and drop.
+//!
+//! Drag source starts drag by [`State::start_drag`] (usually on mouse press) with a typed
+//! [`Payload`]. Drag becomes active, when the cursor is moved farther than drag threshold, so
+//! click on drag source is not a drag. While drag is active, the manager:
+//! - sends [`Event::DragStart`], [`Event::DragMove`] and [`Event::DragEnd`] to the source;
+//! - finds drop target under the cursor: the nearest widget, that accepts the payload by
+//!   [`Widget::accepts_drop`], and sends it [`Event::DragEnter`] and [`Event::DragLeave`];
+//! - sends [`Event::Drop`] to the target on mouse release;
+//! - draws drag [`Preview`] over all widgets.
+//!
+//! Widgets get current drag by [`State::get_drag`]. The tree is not changed by drag.
+//!
+//! [`State::start_drag`]: super::State::start_drag
+//! [`State::get_drag`]: super::State::get_drag
+//! [`Event::DragStart`]: super::widget::event::Event::DragStart
+//! [`Event::DragMove`]: super::widget::event::Event::DragMove
+//! [`Event::DragEnd`]: super::widget::event::Event::DragEnd
+//! [`Event::DragEnter`]: super::widget::event::Event::DragEnter
+//! [`Event::DragLeave`]: super::widget::event::Event::DragLeave
+//! [`Event::Drop`]: super::widget::event::Event::Drop
+//! [`Widget::accepts_drop`]: super::widget::Widget::accepts_drop
+
+use std::any::Any;
+
+use super::widget::WidgetId;
+use utils::vec2::Vec2f;
+
+/// Default distance in pixels, that the cursor passes before drag is started.
+pub const DEFAULT_THRESHOLD: f64 = 4.0;
+
+/// Dragged data. Drop targets check its type.
+pub struct Payload(Box<dyn Any>);
+
+impl Payload {
+    /// Create new payload.
+    #[must_use]
+    pub fn new<T: Any>(value: T) -> Self {
+        Self(Box::new(value))
+    }
+
+    /// Get payload value. Returns `None` if payload has other type.
+    #[must_use]
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+
+    /// Check if payload has specified type.
+    #[must_use]
+    pub fn is<T: Any>(&self) -> bool {
+        self.0.is::<T>()
+    }
+}
+
+/// Drag preview, that is drawn over all widgets while drag is active.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Preview {
+    /// Copy of the source widget is moved with the cursor.
+    #[default]
+    Source,
+    /// The widget is drawn under the cursor. It may be hidden, e.g. it is a preview template.
+    Widget(WidgetId),
+    /// Preview is not drawn, e.g. the source moves itself.
+    None,
+}
+
+/// Drag stage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Drag is started by source, but the cursor is not moved farther than threshold.
+    Pending,
+    /// Payload is dragged.
+    Active,
+    /// Payload is dropped to the target.
+    Dropped,
+    /// Payload is released over widget, that does not accept it.
+    Released,
+    /// Drag is cancelled by `Escape` key or source widget is hidden.
+    Cancelled,
+}
+
+/// Drag state.
+pub struct Drag {
+    /// Source widget.
+    source: WidgetId,
+    /// Dragged data.
+    payload: Payload,
+    /// Cursor position, where drag is started.
+    start: Vec2f,
+    /// Current cursor position.
+    pos: Vec2f,
+    /// Distance, that the cursor passes before drag is activated.
+    threshold: f64,
+    /// Drag preview.
+    preview: Preview,
+    /// Current drop target.
+    target: Option<WidgetId>,
+    /// Drag stage.
+    stage: Stage,
+}
+
+impl Drag {
+    /// Create pending drag.
+    /// - `source`: source widget.
+    /// - `payload`: dragged data.
+    /// - `pos`: cursor position.
+    pub(super) fn new(source: WidgetId, payload: Payload, pos: Vec2f) -> Self {
+        Self {
+            source,
+            payload,
+            start: pos,
+            pos,
+            threshold: DEFAULT_THRESHOLD,
+            preview: Preview::default(),
+            target: None,
+            stage: Stage::Pending,
+        }
+    }
+
+    /// Set distance in pixels, that the cursor passes before drag is activated.
+    pub const fn set_threshold(&mut self, threshold: f64) -> &mut Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set drag preview.
+    pub const fn set_preview(&mut self, preview: Preview) -> &mut Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Get source widget.
+    #[must_use]
+    pub const fn source(&self) -> WidgetId {
+        self.source
+    }
+
+    /// Get dragged data.
+    #[must_use]
+    pub const fn payload(&self) -> &Payload {
+        &self.payload
+    }
+
+    /// Get cursor offset from the position, where drag is started.
+    #[must_use]
+    pub fn delta(&self) -> Vec2f {
+        self.pos - self.start
+    }
+
+    /// Get current cursor position.
+    #[must_use]
+    pub const fn pos(&self) -> Vec2f {
+        self.pos
+    }
+
+    /// Get drag preview.
+    #[must_use]
+    pub const fn preview(&self) -> Preview {
+        self.preview
+    }
+
+    /// Get current drop target.
+    #[must_use]
+    pub const fn target(&self) -> Option<WidgetId> {
+        self.target
+    }
+
+    /// Set current drop target.
+    pub(super) const fn set_target(&mut self, target: Option<WidgetId>) {
+        self.target = target;
+    }
+
+    /// Get drag stage.
+    #[must_use]
+    pub const fn stage(&self) -> Stage {
+        self.stage
+    }
+
+    /// Set drag stage.
+    pub(super) const fn set_stage(&mut self, stage: Stage) {
+        self.stage = stage;
+    }
+
+    /// Check if drag is active.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.stage == Stage::Active
+    }
+
+    /// Update cursor position. Pending drag is activated, if the cursor is moved farther than
+    /// threshold. Returns `true` if drag is activated.
+    pub(super) fn move_to(&mut self, pos: Vec2f) -> bool {
+        self.pos = pos;
+        let delta = self.delta();
+        if self.stage == Stage::Pending && delta.x.hypot(delta.y) >= self.threshold {
+            self.stage = Stage::Active;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Drag, Payload, Stage};
+    use crate::manager::{arena::Arena, widget::WidgetId};
+    use utils::vec2::Vec2f;
+
+    /// Make widget identifier.
+    fn widget() -> WidgetId {
+        Arena::default().insert(())
+    }
+
+    #[test]
+    fn payload() {
+        let payload = Payload::new(5_u32);
+        assert!(payload.is::<u32>());
+        assert_eq!(payload.get::<u32>(), Some(&5));
+        assert!(!payload.is::<String>());
+        assert_eq!(payload.get::<i32>(), None);
+    }
+
+    #[test]
+    fn threshold() {
+        let mut drag = Drag::new(widget(), Payload::new(()), Vec2f::new(10.0, 10.0));
+        drag.set_threshold(5.0);
+        assert!(!drag.move_to(Vec2f::new(13.0, 13.0)));
+        assert_eq!(drag.stage(), Stage::Pending);
+        assert!(drag.move_to(Vec2f::new(14.0, 13.0)));
+        assert!(drag.is_active());
+        assert!(!drag.move_to(Vec2f::new(20.0, 20.0)));
+        let delta = drag.delta();
+        assert_eq!((delta.x, delta.y), (10.0, 10.0));
+    }
+}
@@ -0,0 +1,135 @@
+//! Keyboard focus navigation.
+//!
+//! Focus is moved by `Tab` (`Shift+Tab` moves it back) in traversal order, and by arrow keys to
+//! the nearest widget in the arrow direction.
+
+use scene::event::KeyCode;
+use utils::rect::Rectf;
+
+use crate::widget::Base;
+
+/// Weight of perpendicular offset, when the nearest widget in direction is searched.
+/// Widgets, that are placed in a line with current one, are preferred.
+const OFFSET_WEIGHT: f64 = 2.0;
+
+/// Spatial navigation direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// To the left.
+    Left,
+    /// To the right.
+    Right,
+    /// Up.
+    Up,
+    /// Down.
+    Down,
+}
+
+impl Direction {
+    /// Get direction of the arrow key. Return `None` for other keys.
+    #[must_use]
+    pub const fn from_key(key: KeyCode) -> Option<Self> {
+        match key {
+            KeyCode::ArrowLeft => Some(Self::Left),
+            KeyCode::ArrowRight => Some(Self::Right),
+            KeyCode::ArrowUp => Some(Self::Up),
+            KeyCode::ArrowDown => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
+
+/// Sort widgets by tab index. Widgets with equal index keep their order.
+pub fn tab_order<T>(mut widgets: Vec<(i32, T)>) -> Vec<T> {
+    widgets.sort_by_key(|(index, _)| *index);
+    widgets.into_iter().map(|(_, w)| w).collect()
+}
+
+/// Get index of the next widget in traversal order. Traversal is wrapped around.
+/// - `len`: number of widgets.
+/// - `current`: index of current focused widget.
+/// - `back`: move focus back.
+#[must_use]
+pub const fn step(len: usize, current: Option<usize>, back: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    Some(match (current, back) {
+        (None, false) => 0,
+        (None, true) => len - 1,
+        (Some(i), false) => (i + 1) % len,
+        (Some(i), true) => (i + len - 1) % len,
+    })
+}
+
+/// Find the nearest rectangle in direction from specified one.
+/// Rectangles are compared by their centers, rectangles with the same center are skipped.
+#[must_use]
+pub fn nearest(from: &Rectf, rects: &[Rectf], dir: Direction) -> Option<usize> {
+    let center = |r: &Rectf| (r.x + r.w / 2.0, r.y + r.h / 2.0);
+    let (x, y) = center(from);
+    rects
+        .iter()
+        .enumerate()
+        .filter_map(|(i, r)| {
+            let (rx, ry) = center(r);
+            let (dx, dy) = (rx - x, ry - y);
+            let (distance, offset) = match dir {
+                Direction::Left => (-dx, dy),
+                Direction::Right => (dx, dy),
+                Direction::Up => (-dy, dx),
+                Direction::Down => (dy, dx),
+            };
+            (distance > 0.0).then_some((i, offset.abs().mul_add(OFFSET_WEIGHT, distance)))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+}
+
+/// Get widget boundaries in root widget coordinates.
+pub fn global_rect(widget: &Base) -> Rectf {
+    let pos = widget.get_global_position();
+    let zoom = widget.get_global_zoom() / widget.get_zoom();
+    let rect = widget.get_rect();
+    Rectf { x: pos.x, y: pos.y, w: rect.w * zoom, h: rect.h * zoom }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{nearest, step, tab_order, Direction};
+    use utils::rect::Rectf;
+
+    #[test]
+    fn order() {
+        assert_eq!(tab_order(vec![(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd')]), ['b', 'd', 'a', 'c']);
+    }
+
+    #[test]
+    fn traversal() {
+        assert_eq!(step(3, None, false), Some(0));
+        assert_eq!(step(3, None, true), Some(2));
+        assert_eq!(step(3, Some(2), false), Some(0));
+        assert_eq!(step(3, Some(0), true), Some(2));
+        assert_eq!(step(0, None, false), None);
+    }
+
+    #[test]
+    fn spatial() {
+        // Grid 2x2 and a widget far to the right of the first row.
+        let rects: Vec<Rectf> = [
+            [0.0, 0.0, 10.0, 10.0],
+            [20.0, 0.0, 10.0, 10.0],
+            [0.0, 20.0, 10.0, 10.0],
+            [20.0, 20.0, 10.0, 10.0],
+            [100.0, 0.0, 10.0, 10.0],
+        ]
+        .into_iter()
+        .map(Rectf::from)
+        .collect();
+        assert_eq!(nearest(&rects[0], &rects, Direction::Right), Some(1));
+        assert_eq!(nearest(&rects[0], &rects, Direction::Down), Some(2));
+        assert_eq!(nearest(&rects[3], &rects, Direction::Up), Some(1));
+        assert_eq!(nearest(&rects[1], &rects, Direction::Right), Some(4));
+        assert_eq!(nearest(&rects[0], &rects, Direction::Left), None);
+    }
+}
@@ -0,0 +1,130 @@
+//! GUI layers and popups.
+//!
+//! Root widget of the manager is drawn at the bottom of [`Layer::Content`]. Other top level
+//! widgets (popups) are opened in layers by [`Tree::open_popup`] or [`State::open_popup`]:
+//! popups of the upper layer are drawn over popups of the lower one, popups of the same layer
+//! are drawn in opening order. Modal popup blocks input to popups under it and to root widget,
+//! it is closed by `Escape` key or by mouse press outside it.
+//!
+//! [`Tree::open_popup`]: super::Tree::open_popup
+//! [`State::open_popup`]: super::State::open_popup
+
+use super::widget::WidgetId;
+
+/// Manager layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    /// Main content, root widget is at the bottom of it.
+    Content,
+    /// Popups: dialogs, dropdowns and menus.
+    Popup,
+    /// Tooltips.
+    Tooltip,
+    /// Overlay over all widgets, e.g. notifications.
+    Overlay,
+}
+
+/// Opened popup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Popup {
+    /// Top level widget.
+    widget: WidgetId,
+    /// Layer of the popup.
+    layer: Layer,
+    /// Popup blocks input to lower popups.
+    modal: bool,
+}
+
+impl Popup {
+    /// Create popup.
+    /// - `widget`: top level widget.
+    /// - `layer`: layer of the popup.
+    /// - `modal`: popup blocks input to lower popups.
+    #[must_use]
+    pub const fn new(widget: WidgetId, layer: Layer, modal: bool) -> Self {
+        Self { widget, layer, modal }
+    }
+
+    /// Get top level widget.
+    #[must_use]
+    pub const fn widget(&self) -> WidgetId {
+        self.widget
+    }
+
+    /// Get layer of the popup.
+    #[must_use]
+    pub const fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    /// Check if popup blocks input to lower popups.
+    #[must_use]
+    pub const fn is_modal(&self) -> bool {
+        self.modal
+    }
+}
+
+/// Popup request, that widget makes while handling event.
+pub(super) enum Request {
+    /// Open popup.
+    Open(Popup),
+    /// Close popup of the widget.
+    Close(WidgetId),
+}
+
+/// Opened popups in drawing order.
+#[derive(Default)]
+pub(super) struct Layers {
+    /// Popups from the bottom one.
+    popups: Vec<Popup>,
+}
+
+impl Layers {
+    /// Open popup over other popups of its layer. Popup of the widget, that is opened already,
+    /// is moved.
+    /// - `popup`: popup to open.
+    pub(super) fn open(&mut self, popup: Popup) {
+        self.close(popup.widget);
+        let index = self.popups.partition_point(|p| p.layer <= popup.layer);
+        self.popups.insert(index, popup);
+    }
+
+    /// Close popup of the widget. Returns `false` if the widget is not opened.
+    /// - `widget`: top level widget.
+    pub(super) fn close(&mut self, widget: WidgetId) -> bool {
+        let len = self.popups.len();
+        self.popups.retain(|p| p.widget != widget);
+        self.popups.len() != len
+    }
+
+    /// Get popups from the bottom one.
+    pub(super) fn get(&self) -> &[Popup] {
+        &self.popups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Layer, Layers, Popup};
+    use crate::manager::arena::Arena;
+
+    #[test]
+    fn order() {
+        let mut arena = Arena::default();
+        let [a, b, c, d] = [(); 4].map(|()| arena.insert(()));
+        let mut layers = Layers::default();
+        layers.open(Popup::new(a, Layer::Overlay, false));
+        layers.open(Popup::new(b, Layer::Popup, true));
+        layers.open(Popup::new(c, Layer::Popup, false));
+        layers.open(Popup::new(d, Layer::Tooltip, false));
+        let widgets: Vec<_> = layers.get().iter().map(Popup::widget).collect();
+        assert_eq!(widgets, [b, c, d, a]);
+
+        layers.open(Popup::new(b, Layer::Popup, true));
+        let widgets: Vec<_> = layers.get().iter().map(Popup::widget).collect();
+        assert_eq!(widgets, [c, b, d, a]);
+        assert!(layers.close(b));
+        assert!(!layers.close(b));
+        assert_eq!(layers.get().len(), 3);
+    }
+}
@@ -2,21 +2,40 @@
 //!
 //! This module manages the life cycle of GUI elements.
 
-use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use std::{collections::HashMap, iter, path::PathBuf};
 
-use crate::widget::Builder;
+use crate::widget::{
+    layout::Layout,
+    style::{Style, StyleState, Theme, DEFAULT_CLASS},
+    Builder, Label,
+};
+use binding::{Binding, Model};
 use builder::config::Config;
+use drag::{Preview, Stage};
 use error_stack::{Result, ResultExt};
-use renderer::{Drawable, Renderer};
-use resources::Manager as ResMngr;
-use scene::event::Event as SceneEvent;
-use utils::vec2::Vec2f;
-use widget::{event::Event, WRef};
+use focus::Direction;
+use layer::{Layer, Popup, Request};
+use renderer::{Drawable, Renderer, Stroke};
+use resources::{LoadOptions, Manager as ResMngr};
+use scene::event::{Event as SceneEvent, KeyCode};
+use utils::{color::Color, rect::Rectf, vec2::Vec2f};
+use widget::{
+    event::{Event, Phase},
+    signal::{Signal, SlotId, Slots},
+    Node, TypedId, Widget, WidgetId,
+};
 
+mod arena;
+pub mod binding;
+pub mod drag;
+mod focus;
+pub mod layer;
 mod state;
+mod tree;
 pub mod widget;
 
 pub use state::State;
+pub use tree::Tree;
 
 /// Manager error.
 #[derive(Debug, thiserror::Error)]
@@ -29,30 +48,123 @@ impl Error {
     }
 }
 
+/// Style of focus ring, that is used if theme does not specify it.
+const FOCUS_RING: Style = Style {
+    background_color: None,
+    border_color: Some(Color { r: 0.2, g: 0.5, b: 1.0, a: 1.0 }),
+    border_width: Some(2.0),
+    corner_radius: Some(2.0),
+    color: None,
+    font: None,
+    font_size: None,
+    texture: None,
+    texture_rect: None,
+    texture_rect_on: None,
+    cursor_texture_rect: None,
+    slice: None,
+};
+
+/// Widgets and themes, that are collected while GUI tree is made.
+#[derive(Default)]
+struct Collected {
+    /// Widget tree.
+    tree: Tree,
+    /// Labels, that are updated on locale switching.
+    labels: Vec<WidgetId>,
+    /// Loaded themes by name.
+    themes: HashMap<String, Theme>,
+    /// Bindings of widget fields to model properties.
+    bindings: Vec<Binding>,
+}
+
 /// GUI manager.
 pub struct Manager {
-    /// Reference on root widget.
-    root: WRef,
+    /// Widget tree.
+    tree: Tree,
+    /// Root widget.
+    root: WidgetId,
     /// Manager state.
     state: State,
+    /// Labels, that are updated on locale switching. Only labels with localized text are changed.
+    labels: Vec<WidgetId>,
+    /// Size of area, where root widget is placed. `None` if root widget keeps its bounds.
+    size: Option<Vec2f>,
+    /// Loaded themes by name.
+    themes: HashMap<String, Theme>,
+    /// Current theme name. `None` if widgets have only inline styles.
+    theme: Option<String>,
+    /// Style of focus ring, that is drawn around focused widget.
+    focus_ring: Style,
+    /// Focus ring is drawn. It is shown, when focus is moved by keyboard.
+    focus_visible: bool,
+    /// Shift key is pressed.
+    shift: bool,
+    /// Slots, that are connected to widget signals.
+    slots: Slots,
+    /// Data model, that widgets are bound to.
+    model: Model,
+    /// Bindings of widget fields to model properties.
+    bindings: Vec<Binding>,
+    /// Model revision, that bound widgets are synchronized with. `None` if they are not synced.
+    synced_revision: Option<u64>,
+    /// Widgets must be rearranged: the tree or widget geometry is changed since last arrange.
+    is_dirty: bool,
 }
 
 impl Manager {
     /// Create new GUI manager.
+    /// Root widget config may contain `"theme"` field with name of the theme to apply.
+    /// Focus ring is styled by `"focus_ring"` style of the theme: `border_color`, `border_width`
+    /// and `corner_radius` are used.
     ///
     /// # Errors
     /// Return error if config is not valid.
-    pub fn new(builder: &Builder, res: &mut dyn ResMngr, cfg: Config) -> Result<Self, Error> {
-        let root = Self::make_gui_tree(builder, cfg, res)?;
-        Ok(Self { state: State::new(root.clone()), root })
+    pub fn new(builder: &Builder, res: &mut dyn ResMngr, mut cfg: Config) -> Result<Self, Error> {
+        let theme = cfg
+            .take_opt::<String>("theme")
+            .change_context(Error::msg("Failed to init GUI theme name"))?;
+        let mut collected = Collected::default();
+        let root = Self::make_gui_tree(builder, cfg, res, &mut collected)?;
+        let mut manager = Self::from_collected(root, collected);
+        if let Some(theme) = theme {
+            manager.set_theme(&theme)?;
+        }
+        manager.sync_bindings();
+        manager.arrange();
+        Ok(manager)
+    }
+
+    /// Make manager of collected GUI tree with specified root widget.
+    fn from_collected(root: WidgetId, collected: Collected) -> Self {
+        let Collected { tree, labels, themes, bindings } = collected;
+        Self {
+            tree,
+            state: State::new(root),
+            root,
+            labels,
+            size: None,
+            themes,
+            theme: None,
+            focus_ring: FOCUS_RING,
+            focus_visible: false,
+            shift: false,
+            slots: Slots::default(),
+            model: Model::default(),
+            bindings,
+            synced_revision: None,
+            is_dirty: true,
+        }
     }
 
     /// Recursive make gui tree with given config.
+    /// Widgets are inserted to the collected tree. Labels and loaded themes are collected to
+    /// update them at runtime.
     fn make_gui_tree(
         builder: &Builder,
         mut cfg: Config,
         res_mngr: &mut dyn ResMngr,
-    ) -> Result<WRef, Error> {
+        collected: &mut Collected,
+    ) -> Result<WidgetId, Error> {
         if let Some(res_arr) = cfg
             .take_opt::<Vec<Config>>("recourses")
             .change_context(Error::msg("Failed to init recourses"))?
@@ -67,7 +179,25 @@ impl Manager {
                 let path = res
                     .take::<PathBuf>("path")
                     .change_context(Error::msg("Failed to init resource"))?;
-                res_mngr.load(&kind, &name, &path).change_context(Error::msg(format!(
+                let opts = res
+                    .take_opt::<LoadOptions>("options")
+                    .change_context(Error::msg("Failed to init resource load options"))?
+                    .unwrap_or_default();
+                if kind == "theme" {
+                    let theme = Config::from_file(&path.display().to_string())
+                        .change_context(Error::msg("Failed to load theme config"))
+                        .and_then(|cfg| {
+                            Theme::new(cfg, res_mngr)
+                                .change_context(Error::msg("Failed to init theme"))
+                        })
+                        .change_context(Error::msg(format!(
+                            "Failed to load theme: name: {name:?}, path: {:?}",
+                            path.display()
+                        )))?;
+                    collected.themes.insert(name, theme);
+                    continue;
+                }
+                res_mngr.load(&kind, &name, &path, &opts).change_context(Error::msg(format!(
                     "Failed to load resource: name: {:?}, type: {:?}, path: {:?}",
                     name,
                     kind,
@@ -79,17 +209,20 @@ impl Manager {
         let childs_cfg = cfg
             .take_opt::<Vec<Config>>("childs")
             .change_context(Error::msg("Failed to get childs config"))?;
-        let widget =
+        let templates = binding::take_templates(&mut cfg)?;
+        let node =
             builder.build(cfg, res_mngr).change_context(Error::msg("Failed to build widget"))?;
+        let is_label = node.cast::<Label>().is_some();
+        let widget = collected.tree.insert(node);
+        collected.bindings.extend(templates.into_iter().map(|t| Binding::new(widget, t)));
+        if is_label {
+            collected.labels.push(widget);
+        }
 
         if let Some(childs_cfg) = childs_cfg {
             for child_cfg in childs_cfg {
-                let child = Self::make_gui_tree(builder, child_cfg, res_mngr)?;
-                widget.borrow_mut().add_widget(
-                    widget.clone(),
-                    &mut *child.borrow_mut(),
-                    child.clone(),
-                );
+                let child = Self::make_gui_tree(builder, child_cfg, res_mngr, collected)?;
+                collected.tree.add_widget(widget, child);
             }
         }
 
@@ -101,111 +234,1038 @@ impl Manager {
     /// # Errors
     /// Return error if widget failed to handle event.
     pub fn handle_event(&mut self, event: SceneEvent) -> Result<(), Error> {
+        self.sync_bindings();
         if let SceneEvent::MouseMove(x, y) = event {
             self.state.mouse = (x, y).into();
         }
+        if let SceneEvent::Resize(w, h) = event {
+            self.resize(Vec2f::new(w, h));
+        }
+        match event {
+            SceneEvent::KeyPress(KeyCode::Shift) => self.shift = true,
+            SceneEvent::KeyRelease(KeyCode::Shift) => self.shift = false,
+            SceneEvent::MousePress(_) => self.focus_visible = false,
+            _ => {}
+        }
 
+        // Widgets may be changed through the tree or by the model since last event.
+        self.update_layout();
         let Ok(event) = TryInto::<widget::event::Event>::try_into(event) else {
             return Ok(());
         };
+        self.state.reset_propagation();
+        if !self.handle_drag(&event)? && !self.dismiss_on_press(&event)? {
+            self.route(event)?;
+        }
+        self.apply_popup_requests()?;
+        self.state.reset_propagation();
+        self.update_layout();
+        self.update_hovered(self.state.mouse)?;
+        self.emit_signals();
+        self.update_layout();
+        Ok(())
+    }
+
+    /// Send input event to target widget and other interested widgets: caught, focused and
+    /// hovered ones. Widgets, that are blocked by modal popup, do not get input events.
+    /// Keyboard navigation or closing modal popup by `Escape` key is done after it, if it is not
+    /// prevented.
+    fn route(&mut self, event: Event) -> Result<(), Error> {
+        let caught = self.state.get_caught();
+        if caught.is_some_and(|w| !self.accepts_input(w)) {
+            self.state.release();
+            self.deliver_notifications()
+                .change_context(Error::msg("Caught widget failed when handle uncaught event"))?;
+        }
+        let caught = caught.filter(|w| self.accepts_input(*w));
+        let focused = self.state.get_focused();
+        if focused.is_some_and(|w| !self.accepts_input(w) && !self.state.is_caught(w)) {
+            self.state.set_focused(None);
+            self.deliver_notifications()
+                .change_context(Error::msg("Caught widget failed when handle unfocus event"))?;
+        }
+        let focused = focused.filter(|w| self.accepts_input(*w));
+        let hovered = Some(self.state.hovered).filter(|w| self.accepts_input(*w));
 
-        if let Some(w) = self.state.get_caught() {
-            let is_visible = w.borrow().is_visible();
-            if is_visible {
-                w.borrow_mut()
-                    .handle_event(w.clone(), event.clone(), &mut self.state)
-                    .change_context(Error::msg("Caught widget failed when handle event"))?;
-            } else {
-                self.state.uncatch(&mut *w.borrow_mut(), w.clone()).change_context(Error::msg(
-                    "Caught widget failed when handle uncaught event",
-                ))?;
+        // Keyboard events are targeted to focused widget, mouse events to hovered one.
+        // Other widgets get the event in target phase only, if they are not on the propagation
+        // path of the target.
+        let is_key =
+            matches!(event, Event::KeyPress(_) | Event::KeyRelease(_) | Event::TextInput(_));
+        let target = caught.or(focused.filter(|_| is_key));
+        let target = target.or(hovered).or(focused);
+        if let Some(target) = target {
+            self.dispatch(target, &event)?;
+            let others = [caught, focused, hovered].into_iter().flatten();
+            let mut delivered = self.tree.parents(target);
+            delivered.push(target);
+            for w in others {
+                if self.state.is_propagation_stopped() {
+                    break;
+                }
+                if delivered.contains(&w) {
+                    continue;
+                }
+                self.send(w, event.clone())
+                    .change_context(Error::msg("Widget failed when handle event"))?;
+                delivered.push(w);
             }
         }
 
-        if let Some(w) = self.state.get_focused() {
-            let is_visible = w.borrow().is_visible();
-            if is_visible {
-                w.borrow_mut()
-                    .handle_event(w.clone(), event.clone(), &mut self.state)
-                    .change_context(Error::msg("Focused widget failed when handle event"))?;
-            } else if !self.state.is_caught(w.clone()) {
-                self.state
-                    .unfocus(&mut *w.borrow_mut(), w.clone())
-                    .change_context(Error::msg("Caught widget failed when handle unfocus event"))?;
+        if let Event::KeyPress(key) = event {
+            if !self.state.is_default_prevented() && self.state.get_caught().is_none() {
+                if matches!(key, KeyCode::Escape) {
+                    self.dismiss()?;
+                } else {
+                    self.navigate(key)?;
+                }
             }
         }
+        Ok(())
+    }
 
-        let is_visible = self.state.hovered.borrow().is_visible();
-        if !self.state.is_caught(self.state.hovered.clone())
-            && !self.state.is_focused(self.state.hovered.clone())
-            && is_visible
-        {
-            self.state
-                .hovered
-                .clone()
-                .borrow_mut()
-                .handle_event(self.state.hovered.clone(), event, &mut self.state)
-                .change_context(Error::msg("Hovered widget failed when handle event"))?;
+    /// Close upper modal popup, if mouse is pressed outside it. Returns `true` if the event is
+    /// consumed by closing.
+    fn dismiss_on_press(&mut self, event: &Event) -> Result<bool, Error> {
+        let Some(modal) = self.tree.get_modal() else {
+            return Ok(false);
+        };
+        let is_outside = matches!(event, Event::MousePress(_))
+            && !self.tree.get(modal).is_some_and(|n| n.check_global_bounds(self.state.mouse));
+        if is_outside {
+            self.dismiss()?;
+        }
+        Ok(is_outside)
+    }
+
+    /// Close upper modal popup. Popup widget emits [`Signal::Dismissed`].
+    fn dismiss(&mut self) -> Result<(), Error> {
+        if let Some(modal) = self.tree.get_modal() {
+            self.close_popup(modal)?;
+            self.state.emit(modal, Signal::Dismissed);
+        }
+        Ok(())
+    }
+
+    /// Apply popup requests, that widgets made while handling event. Requests to open root
+    /// widget are ignored.
+    fn apply_popup_requests(&mut self) -> Result<(), Error> {
+        for request in self.state.take_popup_requests() {
+            match request {
+                Request::Open(p) if p.widget() != self.root => {
+                    self.is_dirty |= self.tree.open_popup(p.widget(), p.layer(), p.is_modal());
+                }
+                Request::Open(_) => {}
+                Request::Close(widget) => self.close_popup(widget)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Open the widget as popup (see [`Tree::open_popup`]).
+    /// - `widget`: widget to open.
+    /// - `layer`: layer of the popup.
+    /// - `modal`: popup blocks input to lower popups and root widget.
+    ///
+    /// # Errors
+    /// Return error if widget not found or it is root widget.
+    pub fn open_popup(&mut self, widget: WidgetId, layer: Layer, modal: bool) -> Result<(), Error> {
+        if widget == self.root || !self.tree.open_popup(widget, layer, modal) {
+            return Err(Error::msg("Failed to open popup: widget not found or it is root").into());
+        }
+        self.arrange();
+        Ok(())
+    }
+
+    /// Close popup and hide its widget. Caught and focused widgets of the popup are released.
+    /// - `widget`: widget of the popup.
+    ///
+    /// # Errors
+    /// Return error if widget failed to handle release or unfocus event.
+    pub fn close_popup(&mut self, widget: WidgetId) -> Result<(), Error> {
+        if !self.tree.close_popup(widget) {
+            return Ok(());
+        }
+        self.is_dirty = true;
+        if self.state.get_caught().is_some_and(|w| self.tree.is_in_subtree(w, widget)) {
+            self.state.release();
+        }
+        if self.state.get_focused().is_some_and(|w| self.tree.is_in_subtree(w, widget)) {
+            self.state.set_focused(None);
+        }
+        self.deliver_notifications()
+            .change_context(Error::msg("Widget failed to handle popup closing"))
+    }
+
+    /// Handle event by current drag. Returns `true` if the event is consumed by active drag.
+    /// Pending drag is activated by cursor moving and is finished by mouse release. Active drag
+    /// is dropped by mouse release and cancelled by `Escape` key or if drag source becomes
+    /// inactive.
+    fn handle_drag(&mut self, event: &Event) -> Result<bool, Error> {
+        let Some(drag) = self.state.get_drag() else {
+            return Ok(false);
+        };
+        let (source, target, is_active) = (drag.source(), drag.target(), drag.is_active());
+        if !self.is_active(source) {
+            self.end_drag(Stage::Cancelled)?;
+            return Ok(false);
+        }
+        match event {
+            Event::MouseMove => {
+                let pos = self.state.mouse;
+                if self.state.drag_mut().is_some_and(|d| d.move_to(pos)) {
+                    self.send(source, Event::DragStart)
+                        .change_context(Error::msg("Drag source failed to handle drag start"))?;
+                } else if !is_active {
+                    return Ok(false);
+                }
+                self.update_drop_target()?;
+                self.send(source, Event::DragMove)
+                    .change_context(Error::msg("Drag source failed to handle drag move"))?;
+                Ok(true)
+            }
+            Event::MouseRelease(_) if is_active => {
+                let stage = target.map_or(Stage::Released, |_| Stage::Dropped);
+                self.end_drag(stage)?;
+                Ok(true)
+            }
+            Event::KeyPress(KeyCode::Escape) if is_active => {
+                self.end_drag(Stage::Cancelled)?;
+                Ok(true)
+            }
+            // Other mouse buttons are ignored while payload is dragged.
+            Event::MousePress(_) if is_active => Ok(true),
+            Event::MousePress(_) | Event::MouseRelease(_) => {
+                self.state.take_drag();
+                Ok(false)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Update drop target of active drag: the nearest widget under the cursor, that accepts
+    /// dragged payload. Drag source and its childs are skipped.
+    fn update_drop_target(&mut self) -> Result<(), Error> {
+        let Some(drag) = self.state.get_drag() else {
+            return Ok(());
+        };
+        let hovered = self.find_hovered(drag.pos(), Some(drag.source()));
+        let target = iter::once(hovered).chain(self.tree.parents(hovered)).find(|w| {
+            self.is_active(*w) && self.tree.get(*w).is_some_and(|n| n.accepts_drop(drag.payload()))
+        });
+        let old = drag.target();
+        if old == target {
+            return Ok(());
+        }
+        if let Some(drag) = self.state.drag_mut() {
+            drag.set_target(target);
+        }
+        if let Some(old) = old {
+            self.send(old, Event::DragLeave)
+                .change_context(Error::msg("Drop target failed to handle drag leave"))?;
+        }
+        if let Some(target) = target {
+            self.send(target, Event::DragEnter)
+                .change_context(Error::msg("Drop target failed to handle drag enter"))?;
+        }
+        Ok(())
+    }
+
+    /// Finish active drag. Dropped payload is sent to drop target, otherwise the target gets
+    /// drag leave. Then drag source gets drag end with finish stage.
+    /// - `stage`: finish stage.
+    fn end_drag(&mut self, stage: Stage) -> Result<(), Error> {
+        let Some(drag) = self.state.drag_mut() else {
+            return Ok(());
+        };
+        let was_active = drag.is_active();
+        drag.set_stage(stage);
+        let (source, target) = (drag.source(), drag.target());
+        if let Some(target) = target {
+            let event = if stage == Stage::Dropped { Event::Drop } else { Event::DragLeave };
+            self.send(target, event)
+                .change_context(Error::msg("Drop target failed to handle drag finish"))?;
+        }
+        if was_active {
+            self.send(source, Event::DragEnd)
+                .change_context(Error::msg("Drag source failed to handle drag end"))?;
+        }
+        self.state.take_drag();
+        Ok(())
+    }
+
+    /// Deliver emitted signals to connected slots. Model properties, that are bound two-way to
+    /// changed widgets, are updated before.
+    fn emit_signals(&mut self) {
+        for (widget, signal) in self.state.take_signals() {
+            if let Signal::ValueChanged(ref value) = signal {
+                for b in &mut self.bindings {
+                    b.update_model(widget, value, &self.model);
+                }
+            }
+            self.is_dirty |= self.slots.emit(&mut self.tree, widget, &signal);
+        }
+    }
+
+    /// Update bound widgets by changed model properties.
+    /// It is called on each event handling, bindings are checked only if the model is changed.
+    pub fn sync_bindings(&mut self) {
+        let revision = self.model.revision();
+        if self.synced_revision == Some(revision) {
+            return;
+        }
+        let mut is_changed = false;
+        self.bindings.retain_mut(|b| {
+            b.sync(&mut self.tree, &self.model).inspect(|c| is_changed |= c).is_some()
+        });
+        self.is_dirty |= is_changed;
+        self.synced_revision = Some(revision);
+    }
+
+    /// Get data model, that widgets are bound to.
+    #[must_use]
+    pub const fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// Replace data model, e.g. to share it with other manager. Bound widgets are updated.
+    pub fn set_model(&mut self, model: Model) {
+        self.model = model;
+        self.bindings.iter_mut().for_each(Binding::reset);
+        self.synced_revision = None;
+        self.sync_bindings();
+        self.update_layout();
+    }
+
+    /// Connect slot to the signal of widget with specified identifier.
+    /// Signal names: `clicked`, `value_changed`, `text_submitted`, `hover` and `dismissed`.
+    ///
+    /// # Errors
+    /// Return error if widget not found or signal name is unknown.
+    pub fn connect<F: 'static + FnMut(&mut Tree, WidgetId, &Signal)>(
+        &mut self,
+        id: &str,
+        name: &str,
+        slot: F,
+    ) -> Result<SlotId, Error> {
+        let widget = self
+            .get_by_id(id)
+            .ok_or_else(|| Error::msg(format!("Failed to find requested widget: id: \"{id}\"")))?;
+        self.connect_widget(widget, name, slot)
+    }
+
+    /// Connect slot to the widget signal.
+    ///
+    /// # Errors
+    /// Return error if signal name is unknown.
+    pub fn connect_widget<F: 'static + FnMut(&mut Tree, WidgetId, &Signal)>(
+        &mut self,
+        widget: WidgetId,
+        name: &str,
+        slot: F,
+    ) -> Result<SlotId, Error> {
+        Ok(self
+            .slots
+            .connect(widget, name, Box::new(slot))
+            .ok_or_else(|| Error::msg(format!("Unknown widget signal: {name:?}")))?)
+    }
+
+    /// Disconnect slot. Returns `false` if slot is not connected.
+    pub fn disconnect(&mut self, id: SlotId) -> bool {
+        self.slots.disconnect(id)
+    }
+
+    /// Send event to the target widget through propagation path: capture phase from root widget
+    /// to the target parent, target phase and bubble phase from the target parent to root
+    /// widget. Hidden and disabled parents are skipped.
+    fn dispatch(&mut self, target: WidgetId, event: &Event) -> Result<(), Error> {
+        let path = self.tree.parents(target);
+        let capture = path.iter().rev().map(|w| (*w, Phase::Capture));
+        let bubble = path.iter().map(|w| (*w, Phase::Bubble));
+        let phases = capture.chain(iter::once((target, Phase::Target))).chain(bubble);
+        for (w, phase) in phases {
+            if self.state.is_propagation_stopped() {
+                break;
+            }
+            if phase != Phase::Target && !self.is_active(w) {
+                continue;
+            }
+            self.state.set_phase(phase);
+            self.send(w, event.clone()).change_context(Error::msg(format!(
+                "Widget failed when handle event: {phase:?}"
+            )))?;
+        }
+        self.state.set_phase(Phase::Target);
+        Ok(())
+    }
+
+    /// Send event to the widget in current phase. Notifications, that the widget sends to other
+    /// widgets, are delivered after it.
+    fn send(&mut self, widget: WidgetId, event: Event) -> Result<(), widget::Error> {
+        self.handle(widget, event)?;
+        self.deliver_notifications()
+    }
+
+    /// Let the widget handle event. Widgets are rearranged later, if the widget changes its
+    /// geometry, e.g. panel is moved by mouse.
+    fn handle(&mut self, widget: WidgetId, event: Event) -> Result<(), widget::Error> {
+        let Some(node) = self.tree.get_mut(widget) else {
+            return Ok(());
+        };
+        let geometry = |n: &Node| {
+            let rect = n.get_rect();
+            ([rect.x, rect.y, rect.w, rect.h], n.get_zoom(), n.is_visible())
+        };
+        let old = geometry(node);
+        node.handle_event(widget, event, &mut self.state)?;
+        self.is_dirty |= geometry(node) != old;
+        Ok(())
+    }
+
+    /// Deliver notifications to widgets in target phase. Notifications, that are sent while
+    /// delivering, are delivered too.
+    fn deliver_notifications(&mut self) -> Result<(), widget::Error> {
+        let phase = self.state.get_phase();
+        self.state.set_phase(Phase::Target);
+        let mut notifications = self.state.take_notifications();
+        while !notifications.is_empty() {
+            for (widget, event) in notifications {
+                self.handle(widget, event)?;
+            }
+            notifications = self.state.take_notifications();
+        }
+        self.state.set_phase(phase);
+        Ok(())
+    }
+
+    /// Check if widget is in the tree, visible and enabled.
+    fn is_active(&self, widget: WidgetId) -> bool {
+        self.tree.get(widget).is_some_and(|n| n.is_visible() && n.is_enabled())
+    }
+
+    /// Check if widget is active and it is not blocked by modal popup.
+    fn accepts_input(&self, widget: WidgetId) -> bool {
+        self.is_active(widget) && !self.tree.is_blocked(widget)
+    }
+
+    /// Move focus by navigation key: `Tab` moves focus in traversal order (back if `Shift` is
+    /// pressed), arrows move focus to the nearest widget in the direction, if focused widget
+    /// does not use them. It is default action of key press, widgets may prevent it by
+    /// [`State::prevent_default`].
+    fn navigate(&mut self, key: KeyCode) -> Result<(), Error> {
+        let focused = self.state.get_focused();
+        let order = match key {
+            KeyCode::Tab => self.tab_order(),
+            key if Direction::from_key(key).is_some() => {
+                if focused.is_some_and(|w| self.tree.get(w).is_some_and(|n| n.uses_key(key))) {
+                    return Ok(());
+                }
+                self.tab_order()
+            }
+            _ => return Ok(()),
+        };
+        let current = focused.and_then(|f| order.iter().position(|w| *w == f));
+        let next = match (Direction::from_key(key), current) {
+            (Some(dir), Some(current)) => {
+                // Traversal order contains only widgets of the tree.
+                let rects: Vec<Rectf> = order
+                    .iter()
+                    .filter_map(|w| self.tree.get(*w))
+                    .map(|n| focus::global_rect(n))
+                    .collect();
+                focus::nearest(&rects[current], &rects, dir)
+            }
+            (Some(_), None) => focus::step(order.len(), None, false),
+            (None, current) => focus::step(order.len(), current, self.shift),
+        };
+        if let Some(next) = next {
+            self.focus(Some(order[next]))?;
+            self.focus_visible = true;
         }
-        self.update_hovered(self.state.mouse)?;
         Ok(())
     }
 
+    /// Get widgets, that can be focused by keyboard, in traversal order. Widgets of root widget
+    /// tree go before widgets of opened popups.
+    fn tab_order(&self) -> Vec<WidgetId> {
+        let popups = self.tree.popups().iter().map(Popup::widget);
+        let widgets = iter::once(self.root)
+            .chain(popups)
+            .flat_map(|w| self.tree.subtree(w))
+            .filter_map(|w| {
+                let index = self.tree.get(w)?.get_tab_index().filter(|i| *i >= 0)?;
+                (self.is_shown(w) && !self.tree.is_blocked(w)).then_some((index, w))
+            })
+            .collect();
+        focus::tab_order(widgets)
+    }
+
+    /// Check if widget and all its parents are visible and enabled, and widget is in root widget
+    /// tree or in opened popup.
+    fn is_shown(&self, widget: WidgetId) -> bool {
+        let mut widget = widget;
+        loop {
+            let Some(node) = self.tree.get(widget) else {
+                return false;
+            };
+            if !(node.is_visible() && node.is_enabled()) {
+                return false;
+            }
+            match node.get_parent() {
+                Some(parent) => widget = parent,
+                None => return widget == self.root || self.tree.is_popup(widget),
+            }
+        }
+    }
+
+    /// Focus a widget or remove focus, if `widget` is `None`.
+    ///
+    /// # Errors
+    /// Return error if widget failed to handle focus or unfocus event.
+    pub fn focus(&mut self, widget: Option<WidgetId>) -> Result<(), Error> {
+        self.state.set_focused(widget);
+        self.deliver_notifications()
+            .change_context(Error::msg("Widget failed to handle focus change"))?;
+        self.update_layout();
+        Ok(())
+    }
+
+    /// Get focused widget.
+    #[must_use]
+    pub const fn get_focused(&self) -> Option<WidgetId> {
+        self.state.get_focused()
+    }
+
+    /// Find the widget under the cursor. Popups are checked from the upper one, widgets under
+    /// modal popup are not found: the modal popup is got instead.
+    /// - `pos`: cursor position.
+    /// - `skip`: root of the subtree, that is skipped.
+    fn find_hovered(&self, pos: Vec2f, skip: Option<WidgetId>) -> WidgetId {
+        for popup in self.tree.popups().iter().rev() {
+            let w = popup.widget();
+            let Some(node) = self.tree.get(w).filter(|n| n.is_visible()) else {
+                continue;
+            };
+            if Some(w) != skip {
+                let hovered = self.tree.get_hovered_except(w, pos, skip);
+                if let Some(hovered) = hovered.or_else(|| node.check_bounds(pos).then_some(w)) {
+                    return hovered;
+                }
+            }
+            if popup.is_modal() {
+                return w;
+            }
+        }
+        self.tree.get_hovered_except(self.root, pos, skip).unwrap_or(self.root)
+    }
+
     /// Update hovered widget.
     fn update_hovered(&mut self, pos: Vec2f) -> Result<(), Error> {
-        let hovered = self.root.borrow().get_hovered(pos).unwrap_or_else(|| self.root.clone());
-        if self.state.hovered != hovered {
-            hovered
-                .borrow_mut()
-                .handle_event(hovered.clone(), Event::MouseEnter, &mut self.state)
+        let hovered = self.find_hovered(pos, None);
+        let old = self.state.hovered;
+        if old != hovered {
+            self.send(hovered, Event::MouseEnter)
                 .change_context(Error::msg("Widget failed to handle mouse enter event"))?;
-            self.state
-                .hovered
-                .clone()
-                .borrow_mut()
-                .handle_event(self.state.hovered.clone(), Event::MouseLeave, &mut self.state)
+            self.send(old, Event::MouseLeave)
                 .change_context(Error::msg("Widget failed to handle mouse leave event"))?;
+            self.state.emit(old, Signal::Hover(false));
+            self.state.emit(hovered, Signal::Hover(true));
             self.state.hovered = hovered;
         }
         Ok(())
     }
 
-    /// Find widget by specified identification.
-    #[must_use]
-    pub fn get_by_id(&self, id: &str) -> Option<WRef> {
-        if self.root.borrow().get_id() == id {
-            return Some(self.root.clone());
+    /// Set size of area, where root widget is placed, and rearrange widgets.
+    /// Usually it is window size, it is set on [`Resize`] event.
+    ///
+    /// [`Resize`]: SceneEvent::Resize
+    pub fn resize(&mut self, size: Vec2f) {
+        self.size = Some(size);
+        self.arrange();
+    }
+
+    /// Arrange widgets, if they are changed since last arrange.
+    fn update_layout(&mut self) {
+        if self.is_dirty {
+            self.arrange();
         }
-        self.root.borrow().find(id)
     }
 
-    /// Find widget by specified identification and downcast it.
+    /// Arrange widgets tree and opened popups. Root widget and popups are placed by their anchors
+    /// into area with manager size. Positions and zooms of widgets relative to root widget are
+    /// updated too.
+    fn arrange(&mut self) {
+        let popups: Vec<WidgetId> = self.tree.popups().iter().map(Popup::widget).collect();
+        for widget in iter::once(self.root).chain(popups) {
+            let Some(rect) = self.tree.get(widget).map(|node| {
+                self.size.map_or(*node.get_rect(), |size| {
+                    Layout::default().arrange(size, &[(node.get_layout_item(), *node.get_rect())])
+                        [0]
+                })
+            }) else {
+                continue;
+            };
+            self.tree.arrange(widget, rect);
+        }
+        self.is_dirty = false;
+    }
+
+    /// Switch locale and update all labels with localized text.
+    ///
+    /// # Errors
+    /// Return error if no strings loaded for the locale or some label string is not found.
+    pub fn set_locale(&mut self, res: &mut dyn ResMngr, locale: &str) -> Result<(), Error> {
+        res.set_locale(locale)
+            .change_context(Error::msg(format!("Failed to set locale {locale:?}")))?;
+        self.localize(res)
+    }
+
+    /// Update all labels with localized text to current locale.
+    /// Labels of the tree, that got localized text by [`Label::set_text_key`], are updated too.
+    ///
+    /// # Errors
+    /// Return error if some label string is not found.
+    pub fn localize(&mut self, res: &dyn ResMngr) -> Result<(), Error> {
+        self.labels.retain(|l| self.tree.contains(*l));
+        for label in self.labels.iter().filter_map(|l| self.tree.get(*l)) {
+            if let Some(label) = label.cast::<Label>() {
+                label
+                    .localize(res)
+                    .change_context(Error::msg("Failed to update localized label text"))?;
+            }
+        }
+        self.arrange();
+        Ok(())
+    }
+
+    /// Switch theme of widgets of root widget tree and opened popups.
+    /// Themes are loaded as GUI resources with type `"theme"`.
+    ///
+    /// # Errors
+    /// Return error if theme is not loaded.
+    pub fn set_theme(&mut self, name: &str) -> Result<(), Error> {
+        let theme = self
+            .themes
+            .get(name)
+            .ok_or_else(|| Error::msg(format!("Theme {name:?} is not loaded")))?;
+        let popups = self.tree.popups().iter().map(Popup::widget);
+        let classes: Vec<(WidgetId, String)> = iter::once(self.root)
+            .chain(popups)
+            .flat_map(|w| self.tree.subtree(w))
+            .map(|w| (w, self.style_class(w).to_owned()))
+            .collect();
+        for (widget, class) in classes {
+            if let Some(widget) = self.tree.get_mut(widget) {
+                widget.apply_theme(theme, &class);
+            }
+        }
+        self.focus_ring =
+            FOCUS_RING.merge(&theme.get("focus_ring", DEFAULT_CLASS).get(StyleState::Normal));
+        self.theme = Some(name.to_owned());
+        self.arrange();
+        Ok(())
+    }
+
+    /// Get style class of the widget. Widget inherits class from its parent, if class is not
+    /// specified by `"style"` field. Opened popup inherits it from parent, it is returned to.
+    fn style_class(&self, widget: WidgetId) -> &str {
+        let mut widget = Some(widget);
+        while let Some(node) = widget.and_then(|w| self.tree.get(w)) {
+            if let Some(class) = node.get_style_class() {
+                return class;
+            }
+            widget = node.get_parent().or_else(|| widget.and_then(|w| self.tree.get_home(w)));
+        }
+        DEFAULT_CLASS
+    }
+
+    /// Get current theme name.
+    #[must_use]
+    pub fn get_theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    /// Get widget tree.
+    #[must_use]
+    pub const fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Get mutable widget tree. Widgets are changed through it, changed widgets are rearranged
+    /// on the next handled event.
+    #[must_use]
+    pub const fn tree_mut(&mut self) -> &mut Tree {
+        self.is_dirty = true;
+        &mut self.tree
+    }
+
+    /// Find widget by specified identification. Root widget tree is checked first, then opened
+    /// popups from the upper one.
+    #[must_use]
+    pub fn get_by_id(&self, id: &str) -> Option<WidgetId> {
+        let popups = self.tree.popups().iter().rev().map(Popup::widget);
+        iter::once(self.root).chain(popups).find_map(|w| self.tree.find(w, id))
+    }
+
+    /// Find widget by specified identification and get its typed identifier.
     ///
     /// # Errors
     /// Return error if widget not found or can not be casted to specified type.
-    pub fn get_by_id_cast<T: 'static>(&self, id: &str) -> Result<Rc<RefCell<T>>, Error> {
-        Ok(self
+    pub fn get_by_id_cast<T: Widget>(&self, id: &str) -> Result<TypedId<T>, Error> {
+        let widget = self
             .get_by_id(id)
-            .ok_or_else(|| Error::msg(format!("Failed to find requested widget: id: \"{id}\"")))?
-            .try_cast::<T>()
-            .ok_or_else(|| {
-                Error::msg(format!(
-                    "Widget \"{}\" has unexpected type. Expected: {}",
-                    id,
-                    std::any::type_name::<T>()
-                ))
-            })?)
+            .ok_or_else(|| Error::msg(format!("Failed to find requested widget: id: \"{id}\"")))?;
+        Ok(self.tree.typed::<T>(widget).ok_or_else(|| {
+            Error::msg(format!(
+                "Widget \"{}\" has unexpected type. Expected: {}",
+                id,
+                std::any::type_name::<T>()
+            ))
+        })?)
+    }
+
+    /// Draw preview of active drag.
+    fn draw_drag_preview(&self, renderer: &mut dyn Renderer) {
+        let Some(drag) = self.state.get_drag().filter(|d| d.is_active()) else {
+            return;
+        };
+        let (widget, pos, zoom) = match drag.preview() {
+            Preview::Source => {
+                // Source is drawn in root coordinates, so transform of its parents is applied.
+                let Some(node) = self.tree.get(drag.source()) else {
+                    return;
+                };
+                let (origin, zoom) = node.parent_global_transform();
+                (drag.source(), origin + drag.delta(), zoom)
+            }
+            Preview::Widget(w) => {
+                // Preview widget is placed under the cursor.
+                let Some(node) = self.tree.get(w) else {
+                    return;
+                };
+                (w, drag.pos() - node.get_position(), 1.0)
+            }
+            Preview::None => return,
+        };
+        renderer.push_state();
+        renderer.translate(pos.x, pos.y);
+        renderer.scale(zoom, zoom);
+        self.tree.draw(widget, renderer);
+        renderer.pop_state();
     }
 }
 
 impl Drawable for Manager {
-    /// Draw all visible widgets.
+    /// Draw all visible widgets, opened popups in order of their layers, drag preview and focus
+    /// ring.
     fn draw(&self, renderer: &mut dyn Renderer) {
-        if self.root.borrow().is_visible() {
-            self.root.borrow().draw(renderer);
+        let popups = self.tree.popups().iter().map(Popup::widget);
+        for widget in iter::once(self.root).chain(popups) {
+            if self.tree.get(widget).is_some_and(|w| w.is_visible()) {
+                self.tree.draw(widget, renderer);
+            }
+        }
+        self.draw_drag_preview(renderer);
+        let focused = self.state.get_focused().filter(|_| self.focus_visible);
+        let ring = &self.focus_ring;
+        let focused = focused.and_then(|w| self.tree.get(w)).filter(|w| w.is_visible());
+        if let (Some(w), Some(color), Some(width)) = (focused, ring.border_color, ring.border_width)
+        {
+            let rect = focus::global_rect(w);
+            let rect = Rectf {
+                x: rect.x - width,
+                y: rect.y - width,
+                w: rect.w + width * 2.0,
+                h: rect.h + width * 2.0,
+            };
+            renderer.stroke_rounded_rect(
+                &rect,
+                ring.corner_radius.unwrap_or_default(),
+                &color,
+                &Stroke::new(width),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Collected, Manager, State};
+    use crate::{
+        manager::widget::{
+            event::{Event, Phase},
+            Error, Node, Widget, WidgetId,
+        },
+        widget::{
+            style::{StyleSet, StyleState, Theme},
+            Base,
+        },
+    };
+    use builder::config::Config;
+    use renderer::Renderer;
+    use scene::event::{Event as SceneEvent, KeyCode, MouseButton};
+    use std::{cell::RefCell, rc::Rc};
+    use utils::{rect::Rectf, vec2::Vec2f};
+
+    /// Shared log of widget calls.
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    /// Widget, that records its calls to the log.
+    struct Recorder {
+        /// Widget identifier in log records.
+        id: String,
+        /// Shared log.
+        log: Log,
+        /// Phase, where the widget stops event propagation.
+        stop: Option<Phase>,
+        /// Widget prevents default action of events.
+        prevent: bool,
+    }
+
+    impl Widget for Recorder {
+        fn type_name(&self) -> &'static str {
+            "recorder"
+        }
+
+        fn handle_event(
+            &mut self,
+            _base: &mut Base,
+            _self_id: WidgetId,
+            event: Event,
+            state: &mut State,
+        ) -> error_stack::Result<(), Error> {
+            let phase = state.get_phase();
+            self.log.borrow_mut().push(format!("{} {phase:?} {event:?}", self.id));
+            if self.stop == Some(phase) {
+                state.stop_propagation();
+            }
+            if self.prevent {
+                state.prevent_default();
+            }
+            Ok(())
+        }
+
+        fn draw(&self, _base: &Base, _renderer: &mut dyn Renderer) {}
+
+        fn arrange(&mut self, _rect: &Rectf) {
+            self.log.borrow_mut().push(format!("arrange {}", self.id));
+        }
+    }
+
+    /// Make recording widget. Recorder config fields: `"stop"` is phase name, where propagation
+    /// is stopped, `"prevent"` flag prevents default actions.
+    fn recorder(log: &Log, cfg: &str) -> Node {
+        let mut cfg = Config::from_json(cfg).unwrap();
+        let stop = cfg.take_opt::<String>("stop").unwrap().map(|p| match p.as_str() {
+            "capture" => Phase::Capture,
+            "target" => Phase::Target,
+            _ => Phase::Bubble,
+        });
+        let prevent = cfg.take_opt::<bool>("prevent").unwrap().unwrap_or_default();
+        let base = Base::new(cfg).unwrap();
+        let id = base.get_id();
+        Node::new(base, Recorder { id, log: log.clone(), stop, prevent })
+    }
+
+    /// Make manager with recording widgets. The first widget is root, others are added to
+    /// widgets with specified index.
+    fn manager(log: &Log, widgets: &[(usize, &str)]) -> Manager {
+        let mut collected = Collected::default();
+        let mut ids = Vec::new();
+        for (parent, cfg) in widgets {
+            let widget = collected.tree.insert(recorder(log, cfg));
+            if let Some(parent) = ids.get(*parent) {
+                collected.tree.add_widget(*parent, widget);
+            }
+            ids.push(widget);
+        }
+        let mut manager = Manager::from_collected(ids[0], collected);
+        manager.arrange();
+        log.borrow_mut().clear();
+        manager
+    }
+
+    /// Make manager, where root widget contains `panel`, that contains `button`.
+    /// - `panel`: additional fields of panel config.
+    fn nested(log: &Log, panel: &str) -> Manager {
+        let panel = format!(r#"{{ "id": "panel", "rect": [10, 10, 50, 50] {panel} }}"#);
+        manager(
+            log,
+            &[
+                (0, r#"{ "id": "root", "rect": [0, 0, 100, 100] }"#),
+                (0, &panel),
+                (1, r#"{ "id": "button", "rect": [10, 10, 20, 20] }"#),
+            ],
+        )
+    }
+
+    /// Take log records of arrangement.
+    fn arranged(log: &Log) -> Vec<String> {
+        let records = log.borrow_mut().drain(..).collect::<Vec<_>>();
+        records.into_iter().filter(|r| r.starts_with("arrange ")).collect()
+    }
+
+    /// Take log records of the event.
+    fn take(log: &Log, event: &str) -> Vec<String> {
+        let records = log.borrow_mut().drain(..).collect::<Vec<_>>();
+        records.into_iter().filter_map(|r| r.strip_suffix(event).map(str::to_owned)).collect()
+    }
+
+    /// Press and release left mouse button at the position.
+    fn click(m: &mut Manager, x: f64, y: f64) {
+        m.handle_event(SceneEvent::MouseMove(x, y)).unwrap();
+        m.handle_event(SceneEvent::MousePress(MouseButton::Left)).unwrap();
+        m.handle_event(SceneEvent::MouseRelease(MouseButton::Left)).unwrap();
+    }
+
+    #[test]
+    fn phases_order() {
+        let log = Log::default();
+        let mut m = nested(&log, "");
+        click(&mut m, 25.0, 25.0);
+        assert_eq!(
+            take(&log, "MousePress(Left)"),
+            ["root Capture ", "panel Capture ", "button Target ", "panel Bubble ", "root Bubble "]
+        );
+
+        // Root widget is the target without capture and bubble phases.
+        click(&mut m, 90.0, 90.0);
+        assert_eq!(take(&log, "MousePress(Left)"), ["root Target "]);
+    }
+
+    #[test]
+    fn stop_propagation() {
+        let log = Log::default();
+        let mut m = nested(&log, r#", "stop": "capture""#);
+        click(&mut m, 25.0, 25.0);
+        assert_eq!(take(&log, "MousePress(Left)"), ["root Capture ", "panel Capture "]);
+
+        let mut m = nested(&log, r#", "stop": "bubble""#);
+        click(&mut m, 25.0, 25.0);
+        assert_eq!(
+            take(&log, "MousePress(Left)"),
+            ["root Capture ", "panel Capture ", "button Target ", "panel Bubble "]
+        );
+    }
+
+    #[test]
+    fn skip_inactive_parents() {
+        let log = Log::default();
+        let mut m = nested(&log, r#", "is_enabled": false"#);
+        click(&mut m, 25.0, 25.0);
+        assert_eq!(
+            take(&log, "MousePress(Left)"),
+            ["root Capture ", "button Target ", "root Bubble "]
+        );
+
+        let mut m = nested(&log, "");
+        let (panel, button) = (m.get_by_id("panel").unwrap(), m.get_by_id("button").unwrap());
+        m.focus(Some(button)).unwrap();
+        if let Some(node) = m.tree_mut().get_mut(panel) {
+            node.set_visible_flag(false);
         }
-        if let Some(ref c) = self.state.get_caught() {
-            c.borrow().draw(renderer);
+        m.handle_event(SceneEvent::KeyPress(KeyCode::Enter)).unwrap();
+        assert_eq!(
+            take(&log, "KeyPress(Enter)"),
+            ["root Capture ", "button Target ", "root Bubble "]
+        );
+    }
+
+    /// Make manager with two focusable widgets: `a` on the left of `b`.
+    /// - `root`: additional fields of root config.
+    fn focusable(log: &Log, root: &str) -> Manager {
+        let root = format!(r#"{{ "id": "root", "rect": [0, 0, 100, 100] {root} }}"#);
+        manager(
+            log,
+            &[
+                (0, &root),
+                (0, r#"{ "id": "a", "rect": [10, 10, 20, 20], "tab_index": 0 }"#),
+                (0, r#"{ "id": "b", "rect": [50, 10, 20, 20], "tab_index": 1 }"#),
+            ],
+        )
+    }
+
+    /// Press and release the key.
+    fn press(m: &mut Manager, key: KeyCode) {
+        m.handle_event(SceneEvent::KeyPress(key)).unwrap();
+        m.handle_event(SceneEvent::KeyRelease(key)).unwrap();
+    }
+
+    #[test]
+    fn prevent_default() {
+        let log = Log::default();
+        let mut m = focusable(&log, "");
+        let (a, b) = (m.get_by_id("a").unwrap(), m.get_by_id("b").unwrap());
+        press(&mut m, KeyCode::Tab);
+        assert_eq!(m.get_focused(), Some(a));
+        press(&mut m, KeyCode::Tab);
+        assert_eq!(m.get_focused(), Some(b));
+        press(&mut m, KeyCode::ArrowLeft);
+        assert_eq!(m.get_focused(), Some(a));
+
+        // Root widget prevents navigation in capture phase.
+        let mut m = focusable(&log, r#", "prevent": true"#);
+        let a = m.get_by_id("a").unwrap();
+        press(&mut m, KeyCode::Tab);
+        assert_eq!(m.get_focused(), None);
+        m.focus(Some(a)).unwrap();
+        press(&mut m, KeyCode::Tab);
+        press(&mut m, KeyCode::ArrowRight);
+        assert_eq!(m.get_focused(), Some(a));
+    }
+
+    #[test]
+    fn focus_inserted() {
+        let log = Log::default();
+        let mut m = focusable(&log, "");
+        let (root, b) = (m.get_by_id("root").unwrap(), m.get_by_id("b").unwrap());
+        let c = r#"{ "id": "c", "rect": [10, 50, 20, 20], "tab_index": 2 }"#;
+        let c = m.tree_mut().insert(recorder(&log, c));
+        m.tree_mut().add_widget(root, c);
+        m.focus(Some(b)).unwrap();
+        press(&mut m, KeyCode::Tab);
+        assert_eq!(m.get_focused(), Some(c));
+        press(&mut m, KeyCode::ArrowUp);
+        assert_eq!(m.get_focused(), m.get_by_id("a"));
+    }
+
+    #[test]
+    fn theme_inserted() {
+        let log = Log::default();
+        let mut m = focusable(&log, r#", "style": "dark""#);
+        let mut set = StyleSet::default();
+        set.get_mut(StyleState::Normal).border_width = Some(2.0);
+        let mut theme = Theme::default();
+        theme.insert("recorder", "dark", set);
+        m.themes.insert("dark".to_owned(), theme);
+        let root = m.get_by_id("root").unwrap();
+        let c = m.tree_mut().insert(recorder(&log, r#"{ "id": "c" }"#));
+        m.tree_mut().add_widget(root, c);
+        m.set_theme("dark").unwrap();
+        assert_eq!(m.tree().get(c).unwrap().style().border_width, Some(2.0));
+    }
+
+    #[test]
+    fn arrange_on_changes() {
+        let log = Log::default();
+        let mut m = nested(&log, "");
+        m.handle_event(SceneEvent::MouseMove(25.0, 25.0)).unwrap();
+        m.handle_event(SceneEvent::MouseMove(35.0, 35.0)).unwrap();
+        m.handle_event(SceneEvent::TimeTick(1)).unwrap();
+        assert!(arranged(&log).is_empty());
+
+        m.handle_event(SceneEvent::Resize(200.0, 100.0)).unwrap();
+        assert_eq!(arranged(&log), ["arrange root", "arrange panel", "arrange button"]);
+
+        let button = m.get_by_id("button").unwrap();
+        if let Some(node) = m.tree_mut().get_mut(button) {
+            node.set_position(Vec2f::new(20.0, 20.0));
         }
+        m.handle_event(SceneEvent::MouseMove(45.0, 45.0)).unwrap();
+        assert_eq!(arranged(&log).len(), 3);
+        let pos = m.tree().get(button).unwrap().get_global_position();
+        assert_eq!((pos.x, pos.y), (30.0, 30.0));
+        m.handle_event(SceneEvent::MouseMove(46.0, 46.0)).unwrap();
+        assert!(arranged(&log).is_empty());
     }
 }
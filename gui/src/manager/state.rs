@@ -1,58 +1,184 @@
 //! GUI manager state.
-use super::widget::{event::Event, Error, WRef, Widget};
+use super::{
+    drag::{Drag, Payload},
+    layer::{Layer, Popup, Request},
+    widget::{
+        event::{Event, Phase},
+        signal::Signal,
+        Error, Widget, WidgetId,
+    },
+};
+use crate::widget::Base;
 use error_stack::Result;
 use utils::vec2::Vec2f;
 
 /// Manager state.
 pub struct State {
     /// Caught widget.
-    caught: Option<WRef>,
+    caught: Option<WidgetId>,
     /// Focused widget.
-    focused: Option<WRef>,
+    focused: Option<WidgetId>,
     /// Cursor position.
     pub mouse: Vec2f,
     /// Hovered widget.
-    pub(super) hovered: WRef,
+    pub(super) hovered: WidgetId,
+    /// Current event propagation phase.
+    phase: Phase,
+    /// Event propagation is stopped.
+    stopped: bool,
+    /// Default action of the event is prevented.
+    default_prevented: bool,
+    /// Emitted signals, that are not delivered to slots yet.
+    signals: Vec<(WidgetId, Signal)>,
+    /// Notifications of other widgets, that are not delivered yet. They are delivered by manager
+    /// after event handling by current widget.
+    notifications: Vec<(WidgetId, Event)>,
+    /// Current drag.
+    drag: Option<Drag>,
+    /// Popup requests, that are applied by manager after event handling.
+    popups: Vec<Request>,
 }
 
 impl State {
     /// Create new state.
     #[must_use]
-    pub(super) fn new(hovered: WRef) -> Self {
-        Self { hovered, caught: None, mouse: (0.0, 0.0).into(), focused: None }
+    pub(super) fn new(hovered: WidgetId) -> Self {
+        Self {
+            hovered,
+            caught: None,
+            mouse: (0.0, 0.0).into(),
+            focused: None,
+            phase: Phase::Target,
+            stopped: false,
+            default_prevented: false,
+            signals: Vec::new(),
+            notifications: Vec::new(),
+            drag: None,
+            popups: Vec::new(),
+        }
+    }
+
+    /// Emit widget signal. Signal is delivered to connected slots after event handling.
+    /// - `s_id`: emitter widget.
+    /// - `signal`: signal to emit.
+    pub fn emit(&mut self, s_id: WidgetId, signal: Signal) {
+        self.signals.push((s_id, signal));
+    }
+
+    /// Take emitted signals.
+    pub(super) fn take_signals(&mut self) -> Vec<(WidgetId, Signal)> {
+        std::mem::take(&mut self.signals)
+    }
+
+    /// Take notifications, that are not delivered yet.
+    pub(super) fn take_notifications(&mut self) -> Vec<(WidgetId, Event)> {
+        std::mem::take(&mut self.notifications)
+    }
+
+    /// Get current event propagation phase.
+    #[must_use]
+    pub const fn get_phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Set event propagation phase.
+    pub(super) const fn set_phase(&mut self, phase: Phase) {
+        self.phase = phase;
+    }
+
+    /// Stop event propagation. The event is not sent to the next widgets of propagation path and
+    /// to other widgets.
+    pub const fn stop_propagation(&mut self) {
+        self.stopped = true;
+    }
+
+    /// Check if event propagation is stopped.
+    #[must_use]
+    pub const fn is_propagation_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Prevent default action of the event, that is done by manager after event handling
+    /// (e.g. keyboard focus navigation).
+    pub const fn prevent_default(&mut self) {
+        self.default_prevented = true;
+    }
+
+    /// Check if default action of the event is prevented.
+    #[must_use]
+    pub const fn is_default_prevented(&self) -> bool {
+        self.default_prevented
+    }
+
+    /// Reset propagation flags before new event handling.
+    pub(super) const fn reset_propagation(&mut self) {
+        self.phase = Phase::Target;
+        self.stopped = false;
+        self.default_prevented = false;
+    }
+
+    /// Send notification event to the widget. Notifications are not propagated, so they are
+    /// always handled in target phase.
+    fn notify(
+        &mut self,
+        s: &mut dyn Widget,
+        base: &mut Base,
+        s_id: WidgetId,
+        event: Event,
+    ) -> Result<(), Error> {
+        let phase = self.phase;
+        self.phase = Phase::Target;
+        let res = s.handle_event(base, s_id, event, self);
+        self.phase = phase;
+        res
+    }
+
+    /// Send notification event to other widget. It is delivered after event handling by current
+    /// widget.
+    fn notify_other(&mut self, id: WidgetId, event: Event) {
+        self.notifications.push((id, event));
     }
 
     /// Check if specified widget is hovered.
     #[must_use]
-    pub fn is_hovered(&self, wref: &WRef) -> bool {
-        self.hovered == *wref
+    pub fn is_hovered(&self, id: WidgetId) -> bool {
+        self.hovered == id
     }
 
     /// Get current caught widget.
     #[must_use]
-    pub fn get_caught(&self) -> Option<WRef> {
-        self.caught.clone()
+    pub const fn get_caught(&self) -> Option<WidgetId> {
+        self.caught
     }
 
     /// Check if specified widget is caught.
     #[must_use]
-    pub fn is_caught(&self, wref: WRef) -> bool {
-        self.caught == Some(wref)
+    pub fn is_caught(&self, id: WidgetId) -> bool {
+        self.caught == Some(id)
     }
 
     /// Catch a widget.
-    /// The function takes pointers to the widget that calls this method and the widget that
-    /// needs to be captured.
+    /// The function takes the widget that calls this method and the widget that needs to be
+    /// captured.
     /// If you need to capture the widget that calls the method, use [`catch_self`].
     ///
     /// # Errors
     /// Return error if widget hailed to handle caught event.
     ///
     /// [`catch_self`]: State::catch_self
-    pub fn catch(&mut self, s: &mut dyn Widget, s_ref: WRef, wref: WRef) -> Result<(), Error> {
-        self.uncatch(s, s_ref)?;
-        wref.borrow_mut().handle_event(wref.clone(), Event::Caught, self)?;
-        self.caught = Some(wref);
+    pub fn catch(
+        &mut self,
+        s: &mut dyn Widget,
+        base: &mut Base,
+        s_id: WidgetId,
+        id: WidgetId,
+    ) -> Result<(), Error> {
+        if id == s_id {
+            return self.catch_self(s, base, s_id);
+        }
+        self.uncatch(s, base, s_id)?;
+        self.notify_other(id, Event::Caught);
+        self.caught = Some(id);
         Ok(())
     }
 
@@ -60,10 +186,15 @@ impl State {
     ///
     /// # Errors
     /// Return error if widget hailed to handle caught event.
-    pub fn catch_self(&mut self, s: &mut dyn Widget, s_ref: WRef) -> Result<(), Error> {
-        self.uncatch(s, s_ref.clone())?;
-        s.handle_event(s_ref.clone(), Event::Caught, self)?;
-        self.caught = Some(s_ref);
+    pub fn catch_self(
+        &mut self,
+        s: &mut dyn Widget,
+        base: &mut Base,
+        s_id: WidgetId,
+    ) -> Result<(), Error> {
+        self.uncatch(s, base, s_id)?;
+        self.notify(s, base, s_id, Event::Caught)?;
+        self.caught = Some(s_id);
         Ok(())
     }
 
@@ -71,42 +202,66 @@ impl State {
     ///
     /// # Errors
     /// Return error if widget hailed to handle release event.
-    pub fn uncatch(&mut self, s: &mut dyn Widget, s_ref: WRef) -> Result<(), Error> {
-        if let Some(w) = self.caught.take() {
-            if w == s_ref {
-                s.handle_event(s_ref, Event::Released, self)?;
+    pub fn uncatch(
+        &mut self,
+        s: &mut dyn Widget,
+        base: &mut Base,
+        s_id: WidgetId,
+    ) -> Result<(), Error> {
+        if let Some(id) = self.caught.take() {
+            if id == s_id {
+                self.notify(s, base, s_id, Event::Released)?;
             } else {
-                w.borrow_mut().handle_event(w.clone(), Event::Released, self)?;
+                self.notify_other(id, Event::Released);
             }
         }
         Ok(())
     }
 
+    /// Release caught widget.
+    /// Unlike [`uncatch`], it is called by manager outside of event handling.
+    ///
+    /// [`uncatch`]: State::uncatch
+    pub(super) fn release(&mut self) {
+        if let Some(id) = self.caught.take() {
+            self.notify_other(id, Event::Released);
+        }
+    }
+
     /// Get current focused widget.
     #[must_use]
-    pub fn get_focused(&self) -> Option<WRef> {
-        self.focused.clone()
+    pub const fn get_focused(&self) -> Option<WidgetId> {
+        self.focused
     }
 
     /// Check if specified widget is focused.
     #[must_use]
-    pub fn is_focused(&self, wref: WRef) -> bool {
-        self.focused == Some(wref)
+    pub fn is_focused(&self, id: WidgetId) -> bool {
+        self.focused == Some(id)
     }
 
     /// Focus a widget.
-    /// The function takes pointers to the widget that calls this method and the widget that
-    /// needs to be captured.
-    /// If you need to capture the widget that calls the method, use [`focus_self`].
+    /// The function takes the widget that calls this method and the widget that needs to be
+    /// focused.
+    /// If you need to focus the widget that calls the method, use [`focus_self`].
     ///
     /// # Errors
     /// Return error if widget hailed to handle focus event.
     ///
     /// [`focus_self`]: State::focus_self
-    pub fn focus(&mut self, s: &mut dyn Widget, s_ref: WRef, wref: WRef) -> Result<(), Error> {
-        self.unfocus(s, s_ref)?;
-        wref.borrow_mut().handle_event(wref.clone(), Event::Focused, self)?;
-        self.focused = Some(wref);
+    pub fn focus(
+        &mut self,
+        s: &mut dyn Widget,
+        base: &mut Base,
+        s_id: WidgetId,
+        id: WidgetId,
+    ) -> Result<(), Error> {
+        if id == s_id {
+            return self.focus_self(s, base, s_id);
+        }
+        self.unfocus(s, base, s_id)?;
+        self.notify_other(id, Event::Focused);
+        self.focused = Some(id);
         Ok(())
     }
 
@@ -114,10 +269,15 @@ impl State {
     ///
     /// # Errors
     /// Return error if widget hailed to handle focus event.
-    pub fn focus_self(&mut self, s: &mut dyn Widget, s_ref: WRef) -> Result<(), Error> {
-        self.unfocus(s, s_ref.clone())?;
-        s.handle_event(s_ref.clone(), Event::Focused, self)?;
-        self.focused = Some(s_ref);
+    pub fn focus_self(
+        &mut self,
+        s: &mut dyn Widget,
+        base: &mut Base,
+        s_id: WidgetId,
+    ) -> Result<(), Error> {
+        self.unfocus(s, base, s_id)?;
+        self.notify(s, base, s_id, Event::Focused)?;
+        self.focused = Some(s_id);
         Ok(())
     }
 
@@ -125,14 +285,82 @@ impl State {
     ///
     /// # Errors
     /// Return error if widget hailed to handle unfocus event.
-    pub fn unfocus(&mut self, s: &mut dyn Widget, s_ref: WRef) -> Result<(), Error> {
-        if let Some(w) = self.focused.take() {
-            if w == s_ref {
-                s.handle_event(s_ref, Event::Unfocused, self)?;
+    pub fn unfocus(
+        &mut self,
+        s: &mut dyn Widget,
+        base: &mut Base,
+        s_id: WidgetId,
+    ) -> Result<(), Error> {
+        if let Some(id) = self.focused.take() {
+            if id == s_id {
+                self.notify(s, base, s_id, Event::Unfocused)?;
             } else {
-                w.borrow_mut().handle_event(w.clone(), Event::Unfocused, self)?;
+                self.notify_other(id, Event::Unfocused);
             }
         }
         Ok(())
     }
+
+    /// Focus a widget or remove focus, if `id` is `None`.
+    /// Unlike [`focus`], it is called by manager outside of event handling.
+    ///
+    /// [`focus`]: State::focus
+    pub(super) fn set_focused(&mut self, id: Option<WidgetId>) {
+        if self.focused == id {
+            return;
+        }
+        if let Some(focused) = self.focused.take() {
+            self.notify_other(focused, Event::Unfocused);
+        }
+        if let Some(id) = id {
+            self.notify_other(id, Event::Focused);
+            self.focused = Some(id);
+        }
+    }
+
+    /// Start drag of the payload from the widget at current cursor position. Drag is activated
+    /// when the cursor is moved farther than drag threshold, so it is usually started on mouse
+    /// press. Previous drag is replaced. Returns the drag to set its threshold and preview.
+    /// - `s_id`: drag source widget.
+    /// - `payload`: dragged data.
+    pub fn start_drag(&mut self, s_id: WidgetId, payload: Payload) -> &mut Drag {
+        self.drag.insert(Drag::new(s_id, payload, self.mouse))
+    }
+
+    /// Get current drag.
+    #[must_use]
+    pub const fn get_drag(&self) -> Option<&Drag> {
+        self.drag.as_ref()
+    }
+
+    /// Get mutable current drag.
+    pub(super) const fn drag_mut(&mut self) -> Option<&mut Drag> {
+        self.drag.as_mut()
+    }
+
+    /// Finish current drag.
+    pub(super) fn take_drag(&mut self) -> Option<Drag> {
+        self.drag.take()
+    }
+
+    /// Open the widget as popup after event handling (see [`Tree::open_popup`]).
+    /// - `id`: widget to open.
+    /// - `layer`: layer of the popup.
+    /// - `modal`: popup blocks input to lower popups and root widget.
+    ///
+    /// [`Tree::open_popup`]: super::Tree::open_popup
+    pub fn open_popup(&mut self, id: WidgetId, layer: Layer, modal: bool) {
+        self.popups.push(Request::Open(Popup::new(id, layer, modal)));
+    }
+
+    /// Close popup of the widget after event handling.
+    /// - `id`: widget of the popup.
+    pub fn close_popup(&mut self, id: WidgetId) {
+        self.popups.push(Request::Close(id));
+    }
+
+    /// Take popup requests.
+    pub(super) fn take_popup_requests(&mut self) -> Vec<Request> {
+        std::mem::take(&mut self.popups)
+    }
 }
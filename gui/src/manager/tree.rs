@@ -0,0 +1,504 @@
+//! Widget tree.
+//!
+//! Widget nodes are stored in the arena and linked by generational identifiers, so widgets do not
+//! keep references on each other. The tree is owned by the [`Manager`]: widgets are changed
+//! through it, and widget does not access other widgets while handling events.
+//!
+//! Besides root widget of the manager, the tree contains top level widgets, that are opened as
+//! popups in manager [`layer`]s. Sibling widgets are drawn in order of their z-index.
+//!
+//! [`layer`]: super::layer
+//!
+//! [`Manager`]: super::Manager
+
+use std::collections::HashMap;
+
+use super::{
+    arena::Arena,
+    layer::{Layer, Layers, Popup},
+    widget::{Node, TypedId, Widget, WidgetId},
+};
+use renderer::Renderer;
+use utils::{color, rect::Rectf, vec2::Vec2f};
+
+/// Widget tree.
+#[derive(Default)]
+pub struct Tree {
+    /// Widget nodes.
+    arena: Arena<Node>,
+    /// Opened popups.
+    layers: Layers,
+    /// Parents of opened popups with child indices. Popups are returned to them on closing.
+    homes: HashMap<WidgetId, (WidgetId, usize)>,
+}
+
+impl Tree {
+    /// Insert widget without parent. Returns its identifier.
+    /// - `node`: widget to insert.
+    pub fn insert(&mut self, node: Node) -> WidgetId {
+        self.arena.insert(node)
+    }
+
+    /// Add child widget. Widget is detached from previous parent, its popup is closed.
+    /// Returns `false` if widget or parent is not found, or parent is the widget or its child.
+    /// - `parent`: parent widget.
+    /// - `widget`: widget to add.
+    pub fn add_widget(&mut self, parent: WidgetId, widget: WidgetId) -> bool {
+        if !self.contains(widget) || !self.contains(parent) || self.is_in_subtree(parent, widget) {
+            return false;
+        }
+        self.detach(widget);
+        self.forget_popup(widget);
+        if let Some(node) = self.get_mut(widget) {
+            node.set_parent(Some(parent));
+        }
+        if let Some(node) = self.get_mut(parent) {
+            node.childs_mut().push(widget);
+        }
+        true
+    }
+
+    /// Detach widget from its parent. Widget is kept in the tree.
+    /// - `widget`: widget to detach.
+    pub fn detach(&mut self, widget: WidgetId) {
+        let Some(parent) = self.get_mut(widget).and_then(|n| n.get_parent()) else {
+            return;
+        };
+        if let Some(node) = self.get_mut(widget) {
+            node.set_parent(None);
+        }
+        if let Some(node) = self.get_mut(parent) {
+            node.childs_mut().retain(|c| *c != widget);
+        }
+    }
+
+    /// Remove widget with all its childs. Identifiers of removed widgets become invalid.
+    /// Returns `false` if widget is not found.
+    /// - `widget`: widget to remove.
+    pub fn remove(&mut self, widget: WidgetId) -> bool {
+        if !self.contains(widget) {
+            return false;
+        }
+        self.detach(widget);
+        self.forget_popup(widget);
+        let mut stack = vec![widget];
+        while let Some(id) = stack.pop() {
+            if let Some(node) = self.arena.remove(id) {
+                stack.extend_from_slice(node.get_childs());
+            }
+        }
+        true
+    }
+
+    /// Check if widget is in the tree.
+    #[must_use]
+    pub fn contains(&self, widget: WidgetId) -> bool {
+        self.arena.contains(widget)
+    }
+
+    /// Get widget node. Returns `None` if widget is not found.
+    #[must_use]
+    pub fn get(&self, widget: WidgetId) -> Option<&Node> {
+        self.arena.get(widget)
+    }
+
+    /// Get mutable widget node. Returns `None` if widget is not found.
+    #[must_use]
+    pub fn get_mut(&mut self, widget: WidgetId) -> Option<&mut Node> {
+        self.arena.get_mut(widget)
+    }
+
+    /// Get typed identifier of the widget. Returns `None` if widget is not found or has other
+    /// type.
+    #[must_use]
+    pub fn typed<T: Widget>(&self, widget: WidgetId) -> Option<TypedId<T>> {
+        self.get(widget)?.cast::<T>().is_some().then_some(TypedId::new(widget))
+    }
+
+    /// Get widget of concrete type. Returns `None` if widget is not found.
+    #[must_use]
+    pub fn cast<T: Widget>(&self, widget: TypedId<T>) -> Option<&T> {
+        self.get(widget.id())?.cast()
+    }
+
+    /// Get mutable widget of concrete type. Returns `None` if widget is not found.
+    #[must_use]
+    pub fn cast_mut<T: Widget>(&mut self, widget: TypedId<T>) -> Option<&mut T> {
+        self.get_mut(widget.id())?.cast_mut()
+    }
+
+    /// Get parents of the widget from the nearest one.
+    #[must_use]
+    pub fn parents(&self, widget: WidgetId) -> Vec<WidgetId> {
+        let mut parents = Vec::new();
+        let mut parent = self.get(widget).and_then(|n| n.get_parent());
+        while let Some(p) = parent {
+            parents.push(p);
+            parent = self.get(p).and_then(|n| n.get_parent());
+        }
+        parents
+    }
+
+    /// Open the widget as popup over other popups of the layer. Widget is detached from its
+    /// parent and shown, it is placed in root widget coordinates. Opened popup is moved to the
+    /// top of the layer. Returns `false` if widget is not found.
+    /// Widget is returned to its parent, when popup is closed, so popups may be declared in
+    /// GUI config as hidden widgets.
+    /// - `widget`: widget to open.
+    /// - `layer`: layer of the popup.
+    /// - `modal`: popup blocks input to lower popups and root widget.
+    pub fn open_popup(&mut self, widget: WidgetId, layer: Layer, modal: bool) -> bool {
+        if !self.contains(widget) {
+            return false;
+        }
+        if let Some(parent) = self.get(widget).and_then(|n| n.get_parent()) {
+            let index =
+                self.get(parent).and_then(|p| p.get_childs().iter().position(|c| *c == widget));
+            self.homes.insert(widget, (parent, index.unwrap_or_default()));
+        }
+        self.detach(widget);
+        if let Some(node) = self.get_mut(widget) {
+            node.set_parent_global_transform(Vec2f::new(0.0, 0.0), 1.0);
+            node.set_visible_flag(true);
+        }
+        self.layers.open(Popup::new(widget, layer, modal));
+        true
+    }
+
+    /// Close popup and hide its widget. Widget is returned to its parent, so it may be opened
+    /// again. Returns `false` if popup of the widget is not opened.
+    /// - `widget`: widget of the popup.
+    pub fn close_popup(&mut self, widget: WidgetId) -> bool {
+        if !self.layers.close(widget) {
+            return false;
+        }
+        if let Some(node) = self.get_mut(widget) {
+            node.set_visible_flag(false);
+        }
+        let home = self.homes.remove(&widget).filter(|(p, _)| self.contains(*p));
+        if let Some((parent, index)) = home {
+            if let Some(node) = self.get_mut(parent) {
+                let childs = node.childs_mut();
+                childs.insert(index.min(childs.len()), widget);
+            }
+            if let Some(node) = self.get_mut(widget) {
+                node.set_parent(Some(parent));
+            }
+        }
+        true
+    }
+
+    /// Forget popup of the widget without closing, e.g. widget is moved or removed.
+    fn forget_popup(&mut self, widget: WidgetId) {
+        self.layers.close(widget);
+        self.homes.remove(&widget);
+    }
+
+    /// Get parent, that popup of the widget is returned to on closing.
+    #[must_use]
+    pub fn get_home(&self, widget: WidgetId) -> Option<WidgetId> {
+        self.homes.get(&widget).map(|(parent, _)| *parent)
+    }
+
+    /// Get opened popups in drawing order.
+    #[must_use]
+    pub fn popups(&self) -> &[Popup] {
+        self.layers.get()
+    }
+
+    /// Check if popup of the widget is opened.
+    #[must_use]
+    pub fn is_popup(&self, widget: WidgetId) -> bool {
+        self.popups().iter().any(|p| p.widget() == widget)
+    }
+
+    /// Get widget of the upper visible modal popup.
+    #[must_use]
+    pub fn get_modal(&self) -> Option<WidgetId> {
+        self.popups()
+            .iter()
+            .rev()
+            .filter(|p| p.is_modal())
+            .map(Popup::widget)
+            .find(|w| self.get(*w).is_some_and(|n| n.is_visible()))
+    }
+
+    /// Check if input to the widget is blocked by modal popup, i.e. the widget is not in the
+    /// modal popup or in popups over it.
+    #[must_use]
+    pub fn is_blocked(&self, widget: WidgetId) -> bool {
+        let Some(modal) = self.get_modal() else {
+            return false;
+        };
+        let top = self.parents(widget).last().copied().unwrap_or(widget);
+        // Root widget has no index, so it is under all popups.
+        let index = |w| self.popups().iter().position(|p| p.widget() == w);
+        index(top) < index(modal)
+    }
+
+    /// Get child widgets in drawing order: by z-index, then by insertion order.
+    fn ordered_childs(&self, node: &Node) -> Vec<WidgetId> {
+        let mut childs = node.get_childs().to_vec();
+        childs.sort_by_key(|c| self.get(*c).map_or(0, |n| n.get_z_index()));
+        childs
+    }
+
+    /// Get widgets of the subtree in depth-first order: widget goes before its childs, childs go
+    /// in insertion order.
+    /// - `root`: root widget of the subtree.
+    #[must_use]
+    pub fn subtree(&self, root: WidgetId) -> Vec<WidgetId> {
+        let mut widgets = Vec::new();
+        let mut stack = vec![root];
+        while let Some(widget) = stack.pop() {
+            let Some(node) = self.get(widget) else {
+                continue;
+            };
+            widgets.push(widget);
+            stack.extend(node.get_childs().iter().rev());
+        }
+        widgets
+    }
+
+    /// Check if widget is the root widget or one of its childs.
+    pub(crate) fn is_in_subtree(&self, widget: WidgetId, root: WidgetId) -> bool {
+        widget == root || self.parents(widget).contains(&root)
+    }
+
+    /// Find widget with specified id in the subtree.
+    /// Returns the first widget with specified id.
+    /// - `root`: root widget of the subtree. It is checked first.
+    /// - `id`: widget id.
+    #[must_use]
+    pub fn find(&self, root: WidgetId, id: &str) -> Option<WidgetId> {
+        if self.get(root)?.get_id() == id {
+            return Some(root);
+        }
+        self.find_in_childs(root, id)
+    }
+
+    /// Find widget with specified id among childs of the widget. Last childs are checked first.
+    fn find_in_childs(&self, widget: WidgetId, id: &str) -> Option<WidgetId> {
+        let node = self.get(widget)?;
+        for c in node.get_childs().iter().rev() {
+            if let Some(c) = self.find_in_childs(*c, id) {
+                return Some(c);
+            }
+            if self.get(*c).is_some_and(|c| c.get_id() == id) {
+                return Some(*c);
+            }
+        }
+        None
+    }
+
+    /// Find the child widget under the cursor.
+    /// - `widget`: widget, which childs are checked.
+    /// - `pos`: cursor position in widget parent coordinates.
+    #[must_use]
+    pub fn get_hovered(&self, widget: WidgetId, pos: Vec2f) -> Option<WidgetId> {
+        self.get_hovered_except(widget, pos, None)
+    }
+
+    /// Find the child widget under the cursor, skipping the subtree (e.g. dragged widget).
+    /// - `widget`: widget, which childs are checked.
+    /// - `pos`: cursor position in widget parent coordinates.
+    /// - `skip`: root of the subtree, that is skipped.
+    pub(crate) fn get_hovered_except(
+        &self,
+        widget: WidgetId,
+        pos: Vec2f,
+        skip: Option<WidgetId>,
+    ) -> Option<WidgetId> {
+        let node = self.get(widget)?;
+        if !node.is_visible() || (node.is_clipped() && !node.check_bounds(pos)) {
+            return None;
+        }
+        let (rect, zoom) = (node.get_rect(), node.get_zoom());
+        let pos = Vec2f::new((pos.x - rect.x) / zoom, (pos.y - rect.y) / zoom);
+        for c in self.ordered_childs(node).iter().rev().filter(|c| Some(**c) != skip) {
+            if let Some(c) = self.get_hovered_except(*c, pos, skip) {
+                return Some(c);
+            }
+            if self.get(*c).is_some_and(|c| c.check_bounds(pos)) {
+                return Some(*c);
+            }
+        }
+        None
+    }
+
+    /// Set widget boundaries in parent coordinates and arrange visible childs by widget layout.
+    /// Positions and zooms of parents relative to root widget are updated, so widgets get their
+    /// global position without access to parents.
+    /// - `widget`: widget to arrange.
+    /// - `rect`: new widget boundaries.
+    pub(crate) fn arrange(&mut self, widget: WidgetId, rect: Rectf) {
+        if let Some((origin, zoom)) = self.get(widget).map(|n| n.parent_global_transform()) {
+            self.arrange_node(widget, rect, origin, zoom);
+        }
+    }
+
+    /// Arrange widget with specified transform of parent relative to root widget.
+    fn arrange_node(&mut self, widget: WidgetId, rect: Rectf, origin: Vec2f, zoom: f64) {
+        let Some(node) = self.get_mut(widget) else {
+            return;
+        };
+        node.set_parent_global_transform(origin, zoom);
+        node.arrange(rect);
+        let Some(node) = self.get(widget) else {
+            return;
+        };
+        let size = Vec2f::new(rect.w / node.get_zoom(), rect.h / node.get_zoom());
+        let childs: Vec<_> = node
+            .get_childs()
+            .iter()
+            .filter_map(|c| self.get(*c).map(|n| (*c, n)))
+            .filter(|(_, n)| n.is_visible())
+            .collect();
+        let items: Vec<_> =
+            childs.iter().map(|(_, n)| (n.get_layout_item(), *n.get_rect())).collect();
+        let rects = node.get_layout().arrange(size, &items);
+        let childs: Vec<WidgetId> = childs.iter().map(|(c, _)| *c).collect();
+        let (origin, zoom) = (node.get_global_position(), node.get_global_zoom());
+        for (c, rect) in childs.into_iter().zip(rects) {
+            self.arrange_node(c, rect, origin, zoom);
+        }
+    }
+
+    /// Draw widget and its visible childs in order of their z-index. Childs are clipped if widget
+    /// `clip` flag is set.
+    /// - `widget`: widget to draw.
+    /// - `renderer`: renderer to draw.
+    pub(crate) fn draw(&self, widget: WidgetId, renderer: &mut dyn Renderer) {
+        let Some(node) = self.get(widget) else {
+            return;
+        };
+        node.draw(renderer);
+        let rect = node.get_rect();
+        renderer.push_state();
+        if node.is_debug() {
+            renderer.draw_rect(rect, &color::RED);
+        }
+        if node.is_clipped() {
+            renderer.push_clip(rect);
+        }
+        renderer.translate(rect.x, rect.y);
+        renderer.scale(node.get_zoom(), node.get_zoom());
+        for c in self.ordered_childs(node) {
+            if !self.get(c).is_some_and(|c| c.is_visible()) {
+                continue;
+            }
+            self.draw(c, renderer);
+            if node.is_debug() {
+                if let Some(c) = self.get(c) {
+                    renderer.draw_line(&[(0.0, 0.0).into(), c.get_position()], &color::RED);
+                }
+            }
+        }
+        if node.is_clipped() {
+            renderer.pop_clip();
+        }
+        renderer.pop_state();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tree;
+    use crate::{
+        manager::{layer::Layer, widget::Node},
+        widget::{Base, Container},
+    };
+    use builder::config::Config;
+    use utils::{rect::Rectf, vec2::Vec2f};
+
+    /// Make container widget.
+    fn container(cfg: &str) -> Node {
+        Node::new(Base::new(Config::from_json(cfg).unwrap()).unwrap(), Container)
+    }
+
+    #[test]
+    fn links() {
+        let mut tree = Tree::default();
+        let root = tree.insert(container(r#"{ "id": "root" }"#));
+        let a = tree.insert(container(r#"{ "id": "a" }"#));
+        let b = tree.insert(container(r#"{ "id": "b" }"#));
+        assert!(tree.add_widget(root, a));
+        assert!(tree.add_widget(a, b));
+        assert!(!tree.add_widget(b, root));
+        assert_eq!(tree.parents(b), [a, root]);
+        assert_eq!(tree.find(root, "b"), Some(b));
+        assert_eq!(tree.find(root, "root"), Some(root));
+        assert_eq!(tree.find(a, "root"), None);
+
+        assert!(tree.add_widget(root, b));
+        assert_eq!(tree.get(root).unwrap().get_childs(), [a, b]);
+        assert!(tree.get(a).unwrap().get_childs().is_empty());
+        assert!(tree.typed::<Container>(a).is_some());
+
+        tree.detach(b);
+        assert_eq!(tree.get(b).unwrap().get_parent(), None);
+        assert!(tree.add_widget(a, b));
+        assert!(tree.remove(a));
+        assert!(!tree.contains(a));
+        assert!(!tree.contains(b));
+        assert!(tree.get(root).unwrap().get_childs().is_empty());
+        assert!(!tree.remove(a));
+    }
+
+    #[test]
+    fn geometry() {
+        let mut tree = Tree::default();
+        let root = tree.insert(container(r#"{ "rect": [0, 0, 100, 100], "zoom": 2 }"#));
+        let panel = tree.insert(container(r#"{ "rect": [10, 10, 40, 40] }"#));
+        let button = tree.insert(container(r#"{ "rect": [5, 5, 10, 10] }"#));
+        tree.add_widget(root, panel);
+        tree.add_widget(panel, button);
+        tree.arrange(root, Rectf::from([0.0, 0.0, 100.0, 100.0]));
+
+        let node = tree.get(button).unwrap();
+        let pos = node.get_global_position();
+        assert_eq!((pos.x, pos.y), (30.0, 30.0));
+        assert_eq!(node.get_global_zoom(), 2.0);
+        assert!(node.check_global_bounds(Vec2f::new(35.0, 35.0)));
+        assert!(!node.check_global_bounds(Vec2f::new(55.0, 35.0)));
+        assert_eq!(tree.get_hovered(root, Vec2f::new(35.0, 35.0)), Some(button));
+        assert_eq!(tree.get_hovered(root, Vec2f::new(25.0, 25.0)), Some(panel));
+        assert_eq!(tree.get_hovered(root, Vec2f::new(5.0, 5.0)), None);
+        assert_eq!(
+            tree.get_hovered_except(root, Vec2f::new(35.0, 35.0), Some(button)),
+            Some(panel)
+        );
+        assert_eq!(tree.get_hovered_except(root, Vec2f::new(35.0, 35.0), Some(panel)), None);
+    }
+
+    #[test]
+    fn layers() {
+        let mut tree = Tree::default();
+        let root = tree.insert(container(r#"{ "rect": [0, 0, 100, 100] }"#));
+        let a = tree.insert(container(r#"{ "rect": [10, 10, 40, 40], "z_index": 1 }"#));
+        let b = tree.insert(container(r#"{ "rect": [20, 20, 40, 40] }"#));
+        let dialog = tree.insert(container(r#"{ "rect": [50, 50, 20, 20], "is_visible": false }"#));
+        tree.add_widget(root, a);
+        tree.add_widget(root, b);
+        tree.add_widget(root, dialog);
+        assert_eq!(tree.get_hovered(root, Vec2f::new(30.0, 30.0)), Some(a));
+
+        assert!(tree.open_popup(dialog, Layer::Popup, true));
+        assert_eq!(tree.get(root).unwrap().get_childs(), [a, b]);
+        assert!(tree.get(dialog).unwrap().is_visible());
+        assert_eq!(tree.get_modal(), Some(dialog));
+        assert!(tree.is_blocked(b));
+        assert!(!tree.is_blocked(dialog));
+
+        let tooltip = tree.insert(container(r#"{ "rect": [0, 0, 10, 10] }"#));
+        tree.open_popup(tooltip, Layer::Tooltip, false);
+        assert!(!tree.is_blocked(tooltip));
+        assert!(tree.close_popup(dialog));
+        assert!(!tree.get(dialog).unwrap().is_visible());
+        assert_eq!(tree.get(root).unwrap().get_childs(), [a, b, dialog]);
+        assert_eq!(tree.get_modal(), None);
+        assert!(!tree.is_blocked(b));
+        assert!(tree.add_widget(root, tooltip));
+        assert!(tree.popups().is_empty());
+    }
+}
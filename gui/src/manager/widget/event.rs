@@ -3,7 +3,7 @@
 use scene::event::KeyCode;
 
 /// Mouse buttons.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum MouseButton {
     /// Mouse left button.
     Left,
@@ -14,7 +14,7 @@ pub enum MouseButton {
 }
 
 /// Widget events.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum Event {
     /// Mouse press button event.
     MousePress(MouseButton),
@@ -44,6 +44,36 @@ pub enum Event {
     ///
     /// [`Scancode`]: scene::event::Scancode
     KeyRelease(KeyCode),
+    /// Drag of the widget payload is activated. It is sent to drag source.
+    DragStart,
+    /// Dragged payload is moved. It is sent to drag source.
+    DragMove,
+    /// Drag is finished, drag stage tells how. It is sent to drag source.
+    DragEnd,
+    /// Dragged payload, that the widget accepts, entered in widget bounds.
+    DragEnter,
+    /// Dragged payload left widget bounds.
+    DragLeave,
+    /// Dragged payload is dropped to the widget.
+    Drop,
+}
+
+/// Event propagation phase.
+///
+/// Input event is sent to its target widget in three phases: capture phase from root widget down
+/// to the target parent, target phase and bubble phase from the target parent up to root widget.
+/// Widget gets current phase by [`State::get_phase`].
+///
+/// [`State::get_phase`]: crate::manager::State::get_phase
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Phase {
+    /// Event goes down from root widget to the target.
+    Capture,
+    /// Event is handled by the target widget.
+    #[default]
+    Target,
+    /// Event goes up from the target to root widget.
+    Bubble,
 }
 
 /// Event conversion error.
@@ -69,7 +99,7 @@ impl TryFrom<scene::event::Event> for Event {
             event::Event::TextInput(text) => Self::TextInput(text),
             event::Event::KeyPress(k) => Self::KeyPress(k),
             event::Event::KeyRelease(k) => Self::KeyRelease(k),
-            e @ event::Event::TimeTick(_) => {
+            e @ (event::Event::TimeTick(_) | event::Event::Resize(..)) => {
                 Err(Self::Error::msg(format!("Failed to convert scene event {e:?} to GUI event")))?
             }
         })
@@ -0,0 +1,52 @@
+//! Typed widget identifier.
+
+use std::marker::PhantomData;
+
+use super::WidgetId;
+
+/// Identifier of widget of concrete type. It is got by [`Tree::typed`] or
+/// [`Manager::get_by_id_cast`], widget is accessed by [`Tree::cast`] and [`Tree::cast_mut`].
+///
+/// [`Tree::typed`]: crate::manager::Tree::typed
+/// [`Tree::cast`]: crate::manager::Tree::cast
+/// [`Tree::cast_mut`]: crate::manager::Tree::cast_mut
+/// [`Manager::get_by_id_cast`]: crate::manager::Manager::get_by_id_cast
+pub struct TypedId<T> {
+    /// Widget identifier.
+    id: WidgetId,
+    /// Widget type.
+    widget: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedId<T> {
+    /// Make typed identifier. Widget type must be checked before.
+    pub(crate) const fn new(id: WidgetId) -> Self {
+        Self { id, widget: PhantomData }
+    }
+
+    /// Get untyped widget identifier. It is used for operations, common for all widgets.
+    #[must_use]
+    pub const fn id(&self) -> WidgetId {
+        self.id
+    }
+}
+
+impl<T> Clone for TypedId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TypedId<T> {}
+
+impl<T> PartialEq for TypedId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> std::fmt::Debug for TypedId<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TypedId").field(&self.id).finish()
+    }
+}
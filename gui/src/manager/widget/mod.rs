@@ -1,16 +1,23 @@
 //! Widget interface.
 
 use error_stack::Result;
-use std::{any::Any, cell::RefCell, rc::Weak};
+use std::any::Any;
 
 pub mod event;
-mod wref;
+mod id;
+mod node;
+pub mod signal;
 
-use super::State;
+pub use super::arena::WidgetId;
+use super::{drag::Payload, State};
+use crate::widget::Base;
 use event::Event;
-use renderer::Drawable;
-use utils::{rect::Rectf, vec2::Vec2f};
-pub use wref::WRef;
+pub use id::TypedId;
+pub use node::Node;
+use renderer::Renderer;
+use scene::event::KeyCode;
+use signal::Value;
+use utils::rect::Rectf;
 
 /// Widget error
 #[derive(Debug, thiserror::Error)]
@@ -19,13 +26,23 @@ pub struct Error;
 
 /// Widget interface.
 ///
-/// Implement it if you want to create a widget.
-/// You can use the composition with the [`Base`] widget to implement a new one.
+/// Implement it if you want to create a widget. Widget implements only its behavior: event
+/// handling and drawing of own content. Common properties and tree operations (parent, childs,
+/// position, identifier, visibility, style) are provided by [`Base`], that is owned by the widget
+/// [`Node`] together with the widget. Nodes are owned by the manager [`Tree`], so widget does not
+/// access other widgets while handling events: it requests changes through [`State`].
 ///
-/// [`base`]: crate::widget::Base
-pub trait Widget: Drawable + Any {
+/// [`Tree`]: crate::manager::Tree
+pub trait Widget: Any {
+    /// Get widget type name. Theme styles of the widget are selected by it.
+    fn type_name(&self) -> &'static str;
+
     /// Handle input event.
-    /// - `self_ref`: ref on self. Do not try borrow, use `self`.
+    /// Input events are propagated through parents of the target widget, current propagation
+    /// phase is got by [`State::get_phase`]. Notification events (focus, catch, mouse enter and
+    /// leave) are sent in target phase only.
+    /// - `base`: common properties of the widget.
+    /// - `self_id`: identifier of the widget.
     /// - `event`: event to handle.
     /// - `state`: current state.
     ///
@@ -33,68 +50,48 @@ pub trait Widget: Drawable + Any {
     /// Return error if widget failed to handle event.
     fn handle_event(
         &mut self,
-        self_ref: WRef,
-        event: Event,
-        state: &mut State,
-    ) -> Result<(), Error>;
-
-    /// Find the widget under the cursor.
-    /// - `pos`: cursor position in local coordinates.
-    fn get_hovered(&self, pos: Vec2f) -> Option<WRef>;
-
-    /// Add child widget.
-    /// - `self_ref`: ref on self. Do not try borrow, use `self`.
-    /// - `widget`: widget to add.
-    /// - `widget_ref`: ref on widget to add. Do not try borrow, use `widget`.
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef);
-
-    /// Remove child widget.
-    /// - `widget_ref`: ref on widget to remove. Do not try borrow.
-    fn erase_widget(&mut self, widget_ref: &WRef);
-
-    /// Detach widget.
-    /// -`self_ref`:  ref on self. Do not try borrow, use `self`.
-    fn detach(&mut self, self_ref: &WRef);
-
-    /// Set parent.
-    /// - `parent`: parent to set (May be `None` for remove parent).
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>);
-
-    /// Get parent.
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>>;
-
-    /// Set widget local (relative to parent) position.
-    /// - `pos`: new widget position.
-    fn set_position(&mut self, pos: Vec2f);
-
-    /// Get widget local (relative to parent) position.
-    fn get_position(&self) -> Vec2f;
-
-    /// Set widget global (relative to root widget) position.
-    /// - `pos`: new widget position.
-    fn set_global_position(&mut self, pos: Vec2f);
-
-    /// Get widget global (relative to root widget) position.
-    fn get_global_position(&self) -> Vec2f;
-
-    /// Check that the point is within the widget boundaries.
-    /// - `pos`: position of the point in local (relative to parent) coordinates.
-    fn check_bounds(&self, pos: Vec2f) -> bool;
-
-    /// Get widget boundaries in local (relative to parent) coordinates.
-    fn get_rect(&self) -> &Rectf;
-
-    /// Get widget identifier.
-    fn get_id(&self) -> String;
-
-    /// Find widget by with specified id.
-    /// Returns the first widget witt specified id.
-    /// - `id`: widget id.
-    fn find(&self, id: &str) -> Option<WRef>;
-
-    /// Set widget is visible flag.
-    fn set_visible_flag(&mut self, is_visible: bool);
+        _base: &mut Base,
+        _self_id: WidgetId,
+        _event: Event,
+        _state: &mut State,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Draw widget content. Background is drawn before and child widgets after it.
+    /// - `base`: common properties of the widget.
+    /// - `renderer`: renderer to draw.
+    fn draw(&self, base: &Base, renderer: &mut dyn Renderer);
+
+    /// Update widget content by new widget boundaries. It is called, when widget is arranged.
+    /// - `rect`: widget boundaries in local (relative to parent) coordinates.
+    fn arrange(&mut self, _rect: &Rectf) {}
+
+    /// Check if widget can be focused by keyboard, when tab index is not specified.
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    /// Check if focused widget handles the key itself. Arrow keys, that are not used by the
+    /// focused widget, move focus to the nearest widget.
+    /// - `key`: pressed key.
+    fn uses_key(&self, _key: KeyCode) -> bool {
+        false
+    }
+
+    /// Set widget value from bound model property (see [`binding`]). Widgets without value
+    /// ignore it.
+    /// - `value`: new value.
+    ///
+    /// [`binding`]: crate::manager::binding
+    fn set_bound_value(&mut self, _value: &Value) {}
 
-    /// Check if widget is visible.
-    fn is_visible(&self) -> bool;
+    /// Check if widget accepts dragged payload (see [`drag`]). Payload is dropped to the nearest
+    /// widget under the cursor, that accepts it.
+    /// - `payload`: dragged data.
+    ///
+    /// [`drag`]: crate::manager::drag
+    fn accepts_drop(&self, _payload: &Payload) -> bool {
+        false
+    }
 }
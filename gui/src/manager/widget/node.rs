@@ -0,0 +1,133 @@
+//! Widget tree node.
+
+use error_stack::Result;
+use std::{
+    any::Any,
+    ops::{Deref, DerefMut},
+};
+
+use super::{event::Event, signal::Value, Error, Widget, WidgetId};
+use crate::{
+    manager::{drag::Payload, State},
+    widget::{style::Theme, Base},
+};
+use renderer::Renderer;
+use scene::event::KeyCode;
+use utils::rect::Rectf;
+
+/// Widget tree node.
+///
+/// Node owns common widget properties and tree links ([`Base`]) and the widget behavior.
+/// Node derefs to [`Base`], so common operations (parent, childs, position, identifier,
+/// visibility) are available for widget of any type. Nodes are stored in the manager [`Tree`].
+///
+/// [`Tree`]: crate::manager::Tree
+pub struct Node {
+    /// Common widget properties and tree links.
+    base: Base,
+    /// Widget behavior.
+    widget: Box<dyn Widget>,
+}
+
+impl Node {
+    /// Create new node.
+    /// - `base`: common widget properties.
+    /// - `widget`: widget behavior.
+    #[must_use]
+    pub fn new<T: Widget>(base: Base, widget: T) -> Self {
+        Self { base, widget: Box::new(widget) }
+    }
+
+    /// Get widget of concrete type. Returns `None` if widget has other type.
+    #[must_use]
+    pub fn cast<T: Widget>(&self) -> Option<&T> {
+        (&*self.widget as &dyn Any).downcast_ref()
+    }
+
+    /// Get mutable widget of concrete type. Returns `None` if widget has other type.
+    #[must_use]
+    pub fn cast_mut<T: Widget>(&mut self) -> Option<&mut T> {
+        (&mut *self.widget as &mut dyn Any).downcast_mut()
+    }
+
+    /// Handle input event by the widget.
+    /// - `self_id`: identifier of the node.
+    /// - `event`: event to handle.
+    /// - `state`: current state.
+    ///
+    /// # Errors
+    /// Return error if widget failed to handle event.
+    pub fn handle_event(
+        &mut self,
+        self_id: WidgetId,
+        event: Event,
+        state: &mut State,
+    ) -> Result<(), Error> {
+        self.widget.handle_event(&mut self.base, self_id, event, state)
+    }
+
+    /// Set widget boundaries in local (relative to parent) coordinates. Child widgets are
+    /// arranged by the [`Tree`].
+    /// - `rect`: new widget boundaries.
+    ///
+    /// [`Tree`]: crate::manager::Tree
+    pub fn arrange(&mut self, rect: Rectf) {
+        self.base.set_rect(rect);
+        self.widget.arrange(self.base.get_rect());
+    }
+
+    /// Draw widget background and widget content. Child widgets are drawn by the [`Tree`].
+    ///
+    /// [`Tree`]: crate::manager::Tree
+    pub fn draw(&self, renderer: &mut dyn Renderer) {
+        self.base.draw_background(renderer);
+        self.widget.draw(&self.base, renderer);
+    }
+
+    /// Apply theme style of the widget type.
+    /// - `theme`: theme to apply.
+    /// - `class`: widget style class.
+    pub fn apply_theme(&mut self, theme: &Theme, class: &str) {
+        self.base.set_theme_style(theme.get(self.widget.type_name(), class));
+    }
+
+    /// Get keyboard focus traversal index. Widgets with lower index are focused first.
+    /// Returns `None` or negative index if widget can not be focused by keyboard.
+    #[must_use]
+    pub fn get_tab_index(&self) -> Option<i32> {
+        self.base.get_tab_index().or_else(|| self.widget.is_focusable().then_some(0))
+    }
+
+    /// Check if focused widget handles the key itself.
+    /// - `key`: pressed key.
+    #[must_use]
+    pub fn uses_key(&self, key: KeyCode) -> bool {
+        self.widget.uses_key(key)
+    }
+
+    /// Set widget value from bound model property.
+    /// - `value`: new value.
+    pub fn set_bound_value(&mut self, value: &Value) {
+        self.widget.set_bound_value(value);
+    }
+
+    /// Check if widget accepts dragged payload.
+    /// - `payload`: dragged data.
+    #[must_use]
+    pub fn accepts_drop(&self, payload: &Payload) -> bool {
+        self.widget.accepts_drop(payload)
+    }
+}
+
+impl Deref for Node {
+    type Target = Base;
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Node {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
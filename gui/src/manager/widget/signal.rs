@@ -0,0 +1,239 @@
+//! Widget signals.
+//!
+//! Widget emits signal by [`State::emit`], when user interacts with it. Signals are delivered to
+//! slots, that are connected by [`Manager::connect`], after event handling, so slots may change
+//! any widget of the tree, including the emitter.
+//!
+//! [`State::emit`]: crate::manager::State::emit
+//! [`Manager::connect`]: crate::manager::Manager::connect
+
+use std::fmt::Display;
+
+use super::WidgetId;
+use crate::manager::Tree;
+
+/// Widget value, that is passed by [`Signal::ValueChanged`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Boolean value (flag state).
+    Bool(bool),
+    /// Number value (slider value).
+    Number(f64),
+    /// Text value (textbox text).
+    Text(String),
+}
+
+impl Value {
+    /// Get boolean value.
+    #[must_use]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Get number value.
+    #[must_use]
+    pub const fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::Number(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Get text value.
+    #[must_use]
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bool(v) => write!(f, "{v}"),
+            Self::Number(v) => write!(f, "{v}"),
+            Self::Text(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+/// Widget signal.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Signal {
+    /// Widget is clicked (by mouse or keyboard). Name: `clicked`.
+    Clicked,
+    /// Widget value is changed by user. Name: `value_changed`.
+    ValueChanged(Value),
+    /// Text is submitted by `Enter` key. Name: `text_submitted`.
+    TextSubmitted(String),
+    /// Cursor enters (`true`) or leaves (`false`) widget. Name: `hover`.
+    Hover(bool),
+    /// Modal popup is closed by `Escape` key or mouse press outside it. Name: `dismissed`.
+    Dismissed,
+}
+
+impl Signal {
+    /// Names of all signals.
+    pub const NAMES: [&'static str; 5] =
+        ["clicked", "value_changed", "text_submitted", "hover", "dismissed"];
+
+    /// Get signal name.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Clicked => Self::NAMES[0],
+            Self::ValueChanged(_) => Self::NAMES[1],
+            Self::TextSubmitted(_) => Self::NAMES[2],
+            Self::Hover(_) => Self::NAMES[3],
+            Self::Dismissed => Self::NAMES[4],
+        }
+    }
+
+    /// Get boolean value of the signal.
+    #[must_use]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::ValueChanged(Value::Bool(v)) | Self::Hover(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Get number value of the signal.
+    #[must_use]
+    pub const fn as_number(&self) -> Option<f64> {
+        match self {
+            Self::ValueChanged(Value::Number(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Get text value of the signal.
+    #[must_use]
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::ValueChanged(Value::Text(v)) | Self::TextSubmitted(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Signal slot. It gets widget tree, emitter widget and signal.
+pub type Slot = dyn FnMut(&mut Tree, WidgetId, &Signal);
+
+/// Slot connection identifier. It is used to disconnect the slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotId(usize);
+
+/// Connection of the slot to the widget signal.
+struct Connection {
+    /// Connection identifier.
+    id: SlotId,
+    /// Emitter widget.
+    widget: WidgetId,
+    /// Signal name.
+    name: &'static str,
+    /// Connected slot.
+    slot: Box<Slot>,
+}
+
+/// Slots, that are connected to widget signals.
+#[derive(Default)]
+pub(crate) struct Slots {
+    /// Connections in the order they were made.
+    connections: Vec<Connection>,
+    /// Next connection identifier.
+    next_id: usize,
+}
+
+impl Slots {
+    /// Connect slot to the widget signal. Returns `None` if signal name is unknown.
+    pub(crate) fn connect(
+        &mut self,
+        widget: WidgetId,
+        name: &str,
+        slot: Box<Slot>,
+    ) -> Option<SlotId> {
+        let name = Signal::NAMES.into_iter().find(|n| *n == name)?;
+        let id = SlotId(self.next_id);
+        self.next_id += 1;
+        self.connections.push(Connection { id, widget, name, slot });
+        Some(id)
+    }
+
+    /// Disconnect slot. Returns `false` if slot is not connected.
+    pub(crate) fn disconnect(&mut self, id: SlotId) -> bool {
+        let len = self.connections.len();
+        self.connections.retain(|c| c.id != id);
+        len != self.connections.len()
+    }
+
+    /// Call all slots, that are connected to the widget signal. Returns `true` if some slot is
+    /// called. Connections of widgets, that are removed from the tree, are removed.
+    pub(crate) fn emit(&mut self, tree: &mut Tree, widget: WidgetId, signal: &Signal) -> bool {
+        self.connections.retain(|c| tree.contains(c.widget));
+        let mut is_called = false;
+        for c in &mut self.connections {
+            if c.name == signal.name() && c.widget == widget {
+                (c.slot)(tree, widget, signal);
+                is_called = true;
+            }
+        }
+        is_called
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Signal, Value};
+
+    #[test]
+    fn names() {
+        let signals = [
+            Signal::Clicked,
+            Signal::ValueChanged(Value::Bool(true)),
+            Signal::TextSubmitted(String::new()),
+            Signal::Hover(false),
+            Signal::Dismissed,
+        ];
+        for (signal, name) in signals.iter().zip(Signal::NAMES) {
+            assert_eq!(signal.name(), name);
+        }
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(Signal::ValueChanged(Value::Number(2.0)).as_number(), Some(2.0));
+        assert_eq!(Signal::Hover(true).as_bool(), Some(true));
+        assert_eq!(Signal::TextSubmitted("a".to_owned()).as_text(), Some("a"));
+        assert_eq!(Signal::Clicked.as_number(), None);
+    }
+}
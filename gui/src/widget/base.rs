@@ -1,44 +1,70 @@
-//! Base implementation of widget. It used as root of GUI tree and for implementing other widget.
+//! Common widget properties and tree links.
 
 use error_stack::{Result, ResultExt};
-use std::{
-    cell::RefCell,
-    rc::{Rc, Weak},
-};
 
-use renderer::{Drawable, Renderer};
+use renderer::{Renderer, Stroke};
 use resources::Manager;
-use utils::{color, rect::Rectf, vec2::Vec2f};
+use utils::{rect::Rectf, vec2::Vec2f};
 
-use crate::manager::{
-    widget::{event::Event, Error, WRef, Widget},
-    State,
+use super::{
+    layout::{Item, Layout},
+    style::{Style, StyleSet, StyleState},
 };
+use crate::manager::widget::{Node, Widget, WidgetId};
 use builder::{self, config::Config, BuildFromCfg};
 
-/// Base implementation of widget.
+/// Common widget properties and tree links. It is owned by widget [`Node`].
+/// Tree links are changed by the manager [`Tree`].
+///
+/// [`Tree`]: crate::manager::Tree
 pub struct Base {
     /// Widget bounds.
     rect: Rectf,
     /// Widget childs.
-    childs: Vec<WRef>,
-    /// Reference on parent widget.
-    parent: Option<Weak<RefCell<dyn Widget>>>,
+    childs: Vec<WidgetId>,
+    /// Parent widget.
+    parent: Option<WidgetId>,
+    /// Position of parent widget relative to root widget. It is updated on arrange.
+    parent_origin: Vec2f,
+    /// Zoom of parent widget relative to root widget. It is updated on arrange.
+    parent_zoom: f64,
     /// Enable debug mode.
     debug: bool,
     /// Widget identifier.
     id: String,
     /// Is visibility flag.
     is_visible: bool,
+    /// Is enabled flag. Disabled widget does not handle events.
+    is_enabled: bool,
+    /// Style, specified by widget config. It overrides theme style.
+    inline_style: StyleSet,
+    /// Style from theme.
+    theme_style: StyleSet,
+    /// Current widget state, that selects style.
+    style_state: StyleState,
+    /// Style class of theme. `None` if class is inherited from parent.
+    style_class: Option<String>,
+    /// Zoom of child widgets.
+    zoom: f64,
+    /// Clip child widgets by widget bounds.
+    clip: bool,
+    /// Widget placement in parent layout.
+    item: Item,
+    /// Child widgets layout.
+    layout: Layout,
+    /// Keyboard focus traversal index.
+    tab_index: Option<i32>,
+    /// Drawing order among sibling widgets.
+    z_index: i32,
 }
 
 impl Base {
-    /// Create new Base widget from config.
+    /// Create common widget properties from config.
     ///
     /// # Errors
     /// Return error if config is not valid.
     pub fn new(mut cfg: Config) -> Result<Self, builder::Error> {
-        let rect = cfg
+        let rect: Rectf = cfg
             .take_opt::<[f64; 4]>("rect")
             .change_context(builder::Error::msg("Failed to init base widget bounds"))?
             .unwrap_or([0.0; 4])
@@ -55,146 +81,327 @@ impl Base {
             .take_opt::<bool>("is_visible")
             .change_context(builder::Error::msg("Failed to widget is visible flag"))?
             .unwrap_or(true);
-        Ok(Self { rect, childs: Vec::new(), parent: None, debug, id, is_visible })
+        let is_enabled = cfg
+            .take_opt::<bool>("is_enabled")
+            .change_context(builder::Error::msg("Failed to widget is enabled flag"))?
+            .unwrap_or(true);
+        let style_class = cfg
+            .take_opt::<String>("style")
+            .change_context(builder::Error::msg("Failed to init widget style class"))?;
+        let mut inline_style = StyleSet::default();
+        *inline_style.get_mut(StyleState::Normal) = Style {
+            background_color: cfg
+                .take_opt("background_color")
+                .change_context(builder::Error::msg("Failed to init widget background color"))?,
+            border_color: cfg
+                .take_opt("border_color")
+                .change_context(builder::Error::msg("Failed to init widget border color"))?,
+            border_width: cfg
+                .take_opt("border_width")
+                .change_context(builder::Error::msg("Failed to init widget border width"))?,
+            corner_radius: cfg
+                .take_opt("corner_radius")
+                .change_context(builder::Error::msg("Failed to init widget corner radius"))?,
+            ..Style::default()
+        };
+        let zoom = cfg
+            .take_opt::<f64>("zoom")
+            .change_context(builder::Error::msg("Failed to init widget zoom"))?
+            .unwrap_or(1.0);
+        let clip = cfg
+            .take_opt::<bool>("clip")
+            .change_context(builder::Error::msg("Failed to init widget clip flag"))?
+            .unwrap_or(false);
+        let item = Item::new(&mut cfg, &rect)?;
+        let layout = cfg
+            .take_opt::<Config>("layout")
+            .change_context(builder::Error::msg("Failed to init widget layout"))?
+            .map(Layout::new)
+            .transpose()?
+            .unwrap_or_default();
+        let tab_index = cfg
+            .take_opt::<i32>("tab_index")
+            .change_context(builder::Error::msg("Failed to init widget tab index"))?;
+        let z_index = cfg
+            .take_opt::<i32>("z_index")
+            .change_context(builder::Error::msg("Failed to init widget z-index"))?
+            .unwrap_or(0);
+        Ok(Self {
+            rect,
+            childs: Vec::new(),
+            parent: None,
+            parent_origin: Vec2f::new(0.0, 0.0),
+            parent_zoom: 1.0,
+            debug,
+            id,
+            is_visible,
+            is_enabled,
+            inline_style,
+            theme_style: StyleSet::default(),
+            style_state: StyleState::Normal,
+            style_class,
+            zoom,
+            clip,
+            item,
+            layout,
+            tab_index,
+            z_index,
+        })
     }
-}
 
-impl Widget for Base {
-    fn handle_event(
-        &mut self,
-        _self_rc: WRef,
-        _event: Event,
-        _state: &mut State,
-    ) -> Result<(), Error> {
-        Ok(())
+    /// Get position and zoom of parent widget relative to root widget.
+    /// They are got on last arrange of the tree.
+    #[must_use]
+    pub const fn parent_global_transform(&self) -> (Vec2f, f64) {
+        (self.parent_origin, self.parent_zoom)
     }
 
-    fn get_hovered(&self, mut pos: Vec2f) -> Option<WRef> {
-        if !self.is_visible {
-            return None;
+    /// Set position and zoom of parent widget relative to root widget.
+    pub(crate) const fn set_parent_global_transform(&mut self, origin: Vec2f, zoom: f64) {
+        self.parent_origin = origin;
+        self.parent_zoom = zoom;
+    }
+
+    /// Get mutable inline style of the state. Widgets fill it by their config fields.
+    pub fn inline_style_mut(&mut self, state: StyleState) -> &mut Style {
+        self.inline_style.get_mut(state)
+    }
+
+    /// Set style from theme. Inline style overrides it.
+    pub fn set_theme_style(&mut self, style: StyleSet) {
+        self.theme_style = style;
+    }
+
+    /// Set style class of theme. `None` if class is inherited from parent.
+    pub fn set_style_class(&mut self, class: Option<&str>) {
+        self.style_class = class.map(str::to_owned);
+    }
+
+    /// Get style class of theme, that is specified for the widget.
+    #[must_use]
+    pub fn get_style_class(&self) -> Option<&str> {
+        self.style_class.as_deref()
+    }
+
+    /// Set current widget state, that selects style.
+    pub const fn set_style_state(&mut self, state: StyleState) {
+        self.style_state = state;
+    }
+
+    /// Get style of the current widget state. Disabled widget is always in disabled state.
+    /// State styles override normal style, inline styles override theme styles of the same state.
+    #[must_use]
+    pub fn style(&self) -> Style {
+        let state = if self.is_enabled { self.style_state } else { StyleState::Disabled };
+        let normal = StyleState::Normal;
+        let style = self.theme_style.get(normal).merge(&self.inline_style.get(normal));
+        if state == normal {
+            return style;
         }
-        pos = pos - (self.rect.x, self.rect.y).into();
-        for c in self.childs.iter().rev() {
-            if let Some(c) = c.borrow().get_hovered(pos) {
-                return Some(c);
-            }
-            if c.borrow().check_bounds(pos) {
-                return Some(c.clone());
-            }
+        style.merge(&self.theme_style.get(state)).merge(&self.inline_style.get(state))
+    }
+
+    /// Draw widget background and border, if they are specified.
+    /// Widgets should call it before drawing own content.
+    pub fn draw_background(&self, renderer: &mut dyn Renderer) {
+        let style = self.style();
+        let radius = style.corner_radius.unwrap_or(0.0);
+        if let Some(ref color) = style.background_color {
+            renderer.fill_rounded_rect(&self.rect, radius, color);
+        }
+        if let Some(ref color) = style.border_color {
+            let stroke = Stroke::new(style.border_width.unwrap_or(1.0));
+            renderer.stroke_rounded_rect(&self.rect, radius, color, &stroke);
         }
-        None
     }
 
-    fn check_bounds(&self, pos: Vec2f) -> bool {
+    /// Check that the point is within the widget boundaries.
+    /// - `pos`: position of the point in local (relative to parent) coordinates.
+    #[must_use]
+    pub fn check_bounds(&self, pos: Vec2f) -> bool {
         if !self.is_visible {
             return false;
         }
         self.rect.check_bounds(pos.x, pos.y)
     }
 
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>) {
+    /// Check that the point is within the widget boundaries.
+    /// - `pos`: position of the point in root widget coordinates.
+    #[must_use]
+    pub fn check_global_bounds(&self, pos: Vec2f) -> bool {
+        let (origin, zoom) = self.parent_global_transform();
+        self.check_bounds(Vec2f::new((pos.x - origin.x) / zoom, (pos.y - origin.y) / zoom))
+    }
+
+    /// Set parent.
+    /// - `parent`: parent to set (May be `None` for remove parent).
+    pub(crate) const fn set_parent(&mut self, parent: Option<WidgetId>) {
         self.parent = parent;
     }
 
-    fn detach(&mut self, self_rc: &WRef) {
-        if let Some(ref p) = self.parent {
-            if let Some(ref p) = p.upgrade() {
-                p.borrow_mut().erase_widget(self_rc);
-            }
-        }
-        self.parent = None;
+    /// Get parent.
+    #[must_use]
+    pub const fn get_parent(&self) -> Option<WidgetId> {
+        self.parent
     }
 
-    fn erase_widget(&mut self, widget: &WRef) {
-        self.childs.retain(|c| c != widget);
+    /// Get child widgets in insertion order. Widgets are drawn in order of their z-index, widgets
+    /// with the same z-index are drawn in insertion order.
+    #[must_use]
+    pub fn get_childs(&self) -> &[WidgetId] {
+        &self.childs
     }
 
-    fn set_position(&mut self, pos: Vec2f) {
+    /// Get mutable child widgets.
+    pub(crate) const fn childs_mut(&mut self) -> &mut Vec<WidgetId> {
+        &mut self.childs
+    }
+
+    /// Set widget local (relative to parent) position.
+    /// - `pos`: new widget position.
+    pub const fn set_position(&mut self, pos: Vec2f) {
         self.rect.x = pos.x;
         self.rect.y = pos.y;
     }
 
-    fn get_position(&self) -> Vec2f {
+    /// Get widget local (relative to parent) position.
+    #[must_use]
+    pub const fn get_position(&self) -> Vec2f {
         Vec2f::new(self.rect.x, self.rect.y)
     }
 
-    fn set_global_position(&mut self, mut pos: Vec2f) {
-        self.parent
-            .as_ref()
-            .map(|p| p.upgrade().map(|p| pos = pos - p.borrow().get_global_position()));
-        self.rect.x = pos.x;
-        self.rect.y = pos.y;
+    /// Set widget global (relative to root widget) position.
+    /// - `pos`: new widget position.
+    pub fn set_global_position(&mut self, pos: Vec2f) {
+        let (parent_pos, parent_zoom) = self.parent_global_transform();
+        self.rect.x = (pos.x - parent_pos.x) / parent_zoom;
+        self.rect.y = (pos.y - parent_pos.y) / parent_zoom;
     }
 
-    fn get_global_position(&self) -> Vec2f {
-        let mut pos = Vec2f::new(self.rect.x, self.rect.y);
-        self.parent
-            .as_ref()
-            .map(|p| p.upgrade().map(|p| pos = pos + p.borrow().get_global_position()));
-        pos
+    /// Get widget global (relative to root widget) position.
+    #[must_use]
+    pub fn get_global_position(&self) -> Vec2f {
+        let (parent_pos, parent_zoom) = self.parent_global_transform();
+        Vec2f::new(
+            self.rect.x.mul_add(parent_zoom, parent_pos.x),
+            self.rect.y.mul_add(parent_zoom, parent_pos.y),
+        )
+    }
+
+    /// Set zoom of child widgets.
+    /// - `zoom`: scale factor, must be positive.
+    pub const fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom;
+    }
+
+    /// Get zoom of child widgets.
+    #[must_use]
+    pub const fn get_zoom(&self) -> f64 {
+        self.zoom
+    }
+
+    /// Get zoom of child widgets relative to root widget (including zoom of all parents).
+    #[must_use]
+    pub fn get_global_zoom(&self) -> f64 {
+        self.zoom * self.parent_global_transform().1
     }
 
-    fn get_rect(&self) -> &Rectf {
+    /// Get widget boundaries in local (relative to parent) coordinates.
+    #[must_use]
+    pub const fn get_rect(&self) -> &Rectf {
         &self.rect
     }
 
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>> {
-        self.parent.clone()
+    /// Get widget placement params in parent layout.
+    #[must_use]
+    pub const fn get_layout_item(&self) -> &Item {
+        &self.item
     }
 
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef) {
-        widget.set_parent(Some(Rc::downgrade(&self_ref)));
-        self.childs.push(widget_ref);
+    /// Set widget boundaries in local (relative to parent) coordinates.
+    /// - `rect`: new widget boundaries.
+    pub(crate) const fn set_rect(&mut self, rect: Rectf) {
+        self.rect = rect;
     }
 
-    fn get_id(&self) -> String {
-        self.id.clone()
+    /// Get child widgets layout.
+    #[must_use]
+    pub const fn get_layout(&self) -> &Layout {
+        &self.layout
     }
 
-    fn find(&self, id: &str) -> Option<WRef> {
-        for c in self.childs.iter().rev() {
-            if let Some(c) = c.borrow().find(id) {
-                return Some(c);
-            }
-            if c.borrow().get_id() == id {
-                return Some(c.clone());
-            }
-        }
-        None
+    /// Check if child widgets are clipped by widget bounds.
+    #[must_use]
+    pub const fn is_clipped(&self) -> bool {
+        self.clip
+    }
+
+    /// Check if debug mode is enabled.
+    #[must_use]
+    pub const fn is_debug(&self) -> bool {
+        self.debug
+    }
+
+    /// Get widget identifier.
+    #[must_use]
+    pub fn get_id(&self) -> String {
+        self.id.clone()
     }
 
-    fn set_visible_flag(&mut self, is_visible: bool) {
+    /// Set widget is visible flag.
+    pub const fn set_visible_flag(&mut self, is_visible: bool) {
         self.is_visible = is_visible;
     }
 
-    fn is_visible(&self) -> bool {
+    /// Check if widget is visible.
+    #[must_use]
+    pub const fn is_visible(&self) -> bool {
         self.is_visible
     }
+
+    /// Set widget is enabled flag. Disabled widget does not handle events.
+    pub const fn set_enabled_flag(&mut self, is_enabled: bool) {
+        self.is_enabled = is_enabled;
+    }
+
+    /// Check if widget is enabled.
+    #[must_use]
+    pub const fn is_enabled(&self) -> bool {
+        self.is_enabled
+    }
+
+    /// Get keyboard focus traversal index, that is specified by config.
+    #[must_use]
+    pub const fn get_tab_index(&self) -> Option<i32> {
+        self.tab_index
+    }
+
+    /// Set drawing order among sibling widgets. Widget with greater z-index is drawn over
+    /// siblings and gets mouse events first.
+    pub const fn set_z_index(&mut self, z_index: i32) {
+        self.z_index = z_index;
+    }
+
+    /// Get drawing order among sibling widgets.
+    #[must_use]
+    pub const fn get_z_index(&self) -> i32 {
+        self.z_index
+    }
 }
 
-impl Drawable for Base {
-    fn draw(&self, renderer: &mut dyn Renderer) {
-        renderer.push_state();
-        if self.debug {
-            renderer.draw_rect(&self.rect, &color::RED);
-        }
-        renderer.translate(self.rect.x, self.rect.y);
-        for c in &self.childs {
-            if !c.borrow().is_visible() {
-                continue;
-            }
-            c.borrow().draw(renderer);
-            if self.debug {
-                renderer
-                    .draw_line(&[(0.0, 0.0).into(), c.borrow_mut().get_position()], &color::RED);
-            }
-        }
-        renderer.pop_state();
+/// Widget without own content. It is used as root of GUI tree and for grouping other widgets.
+pub struct Container;
+
+impl Widget for Container {
+    fn type_name(&self) -> &'static str {
+        "base"
     }
+
+    fn draw(&self, _base: &Base, _renderer: &mut dyn Renderer) {}
 }
 
-impl BuildFromCfg<WRef> for Base {
-    fn build(cfg: Config, _r: &mut dyn Manager) -> Result<WRef, builder::Error> {
-        Ok(WRef::new(Self::new(cfg)?))
+impl BuildFromCfg<Node> for Container {
+    fn build(cfg: Config, _r: &mut dyn Manager) -> Result<Node, builder::Error> {
+        Ok(Node::new(Base::new(cfg)?, Self))
     }
 }
@@ -3,202 +3,153 @@
 //! Button widget.
 
 use error_stack::{Result, ResultExt};
-use std::{cell::RefCell, rc::Weak};
 
-use super::Base;
+use super::{
+    style::{Style, StyleState},
+    Base,
+};
 use crate::manager::{
     widget::{
-        event::{Event, MouseButton},
-        Error, WRef, Widget,
+        event::{Event, MouseButton, Phase},
+        signal::Signal,
+        Error, Node, Widget, WidgetId,
     },
     State,
 };
 use builder::{self, config::Config, BuildFromCfg};
-use renderer::{Drawable, Renderer};
-use resources::TextureId;
-use utils::{rect::Rectf, vec2::Vec2f};
-
-/// Button click callback. Called then user click on button.
-type ButtonCb = dyn FnMut(&mut Button);
+use renderer::{slice::Insets, Renderer};
+use scene::event::KeyCode;
+use utils::rect::Rectf;
 
-/// Button widget.
+/// Button widget. Emits `clicked` signal.
 pub struct Button {
-    /// Base widget.
-    base: Base,
-    /// Background texture.
-    texture: TextureId,
-    /// Background texture rectangle when button is preset.
-    texture_rect_pressed: Rectf,
-    /// Background texture rectangle when button is released.
-    texture_rect: Rectf,
-    /// Background texture rectangle when button is hovered and released.
-    texture_rect_hovered: Rectf,
     /// Is widget hovered.
     hovered: bool,
     /// Button is pressed.
     state: bool,
-    /// Pressed cb.
-    cb: Option<Box<ButtonCb>>,
+    /// Is widget focused.
+    focused: bool,
 }
 
-impl Button {
-    /// Set click callback. This callback will be called when user click on button.
-    pub fn click_cb<F: 'static + FnMut(&mut Self)>(&mut self, cb: F) {
-        self.cb = Some(Box::new(cb));
+impl Widget for Button {
+    fn type_name(&self) -> &'static str {
+        "button"
     }
-}
 
-impl Widget for Button {
     fn handle_event(
         &mut self,
-        self_rc: WRef,
+        base: &mut Base,
+        self_id: WidgetId,
         event: Event,
         state: &mut State,
     ) -> Result<(), Error> {
+        if state.get_phase() != Phase::Target {
+            return Ok(());
+        }
         match event {
             Event::MousePress(mouse_button) => {
                 if matches!(mouse_button, MouseButton::Left) && state.get_caught().is_none() {
-                    self.set_position(self.get_global_position());
-                    self.get_parent()
-                        .map(|p| p.upgrade().map(|p| p.borrow_mut().erase_widget(&self_rc)));
-                    state.catch_self(self, self_rc)?;
+                    state.catch_self(self, base, self_id)?;
                     self.state = true;
                 }
             }
             Event::MouseRelease(mouse_button) => {
-                if matches!(mouse_button, MouseButton::Left) && state.is_caught(self_rc.clone()) {
+                if matches!(mouse_button, MouseButton::Left) && state.is_caught(self_id) {
                     self.state = false;
-                    if self.check_bounds(state.mouse) {
-                        if let Some(mut cb) = self.cb.take() {
-                            cb(self);
-                            self.cb = Some(cb);
-                        }
+                    if base.check_global_bounds(state.mouse) {
+                        state.emit(self_id, Signal::Clicked);
                     }
-                    state.uncatch(self, self_rc.clone())?;
-                    self.get_parent().map(|p| {
-                        p.upgrade().map(|p| {
-                            p.clone().borrow_mut().add_widget(p.into(), self, self_rc);
-                        })
-                    });
-                    self.hovered = self.check_bounds(state.mouse);
-                    self.set_global_position(self.get_position());
+                    state.uncatch(self, base, self_id)?;
+                    self.hovered = base.check_global_bounds(state.mouse);
                 }
             }
+            Event::KeyPress(KeyCode::Enter | KeyCode::Space) if self.focused => {
+                self.state = true;
+            }
+            Event::KeyRelease(KeyCode::Enter | KeyCode::Space)
+                if self.state && !state.is_caught(self_id) =>
+            {
+                self.state = false;
+                state.emit(self_id, Signal::Clicked);
+            }
+            Event::Focused => self.focused = true,
+            Event::Unfocused => {
+                self.focused = false;
+                self.state &= state.is_caught(self_id);
+            }
             Event::MouseEnter => self.hovered = true,
-            Event::MouseLeave => self.hovered = state.is_caught(self_rc),
+            Event::MouseLeave => self.hovered = state.is_caught(self_id),
             Event::MouseMove
             | Event::TextInput(_)
             | Event::Caught
             | Event::Released
-            | Event::Focused
-            | Event::Unfocused
             | Event::KeyPress(_)
-            | Event::KeyRelease(_) => {}
+            | Event::KeyRelease(_)
+            | Event::DragStart
+            | Event::DragMove
+            | Event::DragEnd
+            | Event::DragEnter
+            | Event::DragLeave
+            | Event::Drop => {}
         }
+        base.set_style_state(if self.state {
+            StyleState::Pressed
+        } else if self.hovered {
+            StyleState::Hovered
+        } else if self.focused {
+            StyleState::Focused
+        } else {
+            StyleState::Normal
+        });
         Ok(())
     }
 
-    fn get_hovered(&self, pos: Vec2f) -> Option<WRef> {
-        self.base.get_hovered(pos)
-    }
-
-    fn check_bounds(&self, pos: Vec2f) -> bool {
-        self.base.check_bounds(pos)
-    }
-
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef) {
-        self.base.add_widget(self_ref, widget, widget_ref);
-    }
-
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>) {
-        self.base.set_parent(parent);
-    }
-
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>> {
-        self.base.get_parent()
-    }
-
-    fn detach(&mut self, self_rc: &WRef) {
-        self.base.detach(self_rc);
-    }
-
-    fn erase_widget(&mut self, widget: &WRef) {
-        self.base.erase_widget(widget);
-    }
-
-    fn set_position(&mut self, pos: Vec2f) {
-        self.base.set_position(pos);
-    }
-
-    fn get_position(&self) -> Vec2f {
-        self.base.get_position()
-    }
-
-    fn set_global_position(&mut self, pos: Vec2f) {
-        self.base.set_global_position(pos);
-    }
-
-    fn get_global_position(&self) -> Vec2f {
-        self.base.get_global_position()
-    }
-
-    fn get_rect(&self) -> &Rectf {
-        self.base.get_rect()
-    }
-
-    fn find(&self, id: &str) -> Option<WRef> {
-        self.base.find(id)
-    }
-
-    fn get_id(&self) -> String {
-        self.base.get_id()
-    }
-
-    fn set_visible_flag(&mut self, is_visible: bool) {
-        self.base.set_visible_flag(is_visible);
-    }
-
-    fn is_visible(&self) -> bool {
-        self.base.is_visible()
+    fn draw(&self, base: &Base, renderer: &mut dyn Renderer) {
+        let style = base.style();
+        if let (Some(texture), Some(rect)) = (style.texture, style.texture_rect) {
+            let slice = style.slice.unwrap_or_default();
+            renderer.draw_img_sliced(base.get_rect(), texture, &rect, &slice);
+        }
     }
-}
 
-impl Drawable for Button {
-    fn draw(&self, renderer: &mut dyn Renderer) {
-        let rect = if self.state {
-            &self.texture_rect_pressed
-        } else if self.hovered {
-            &self.texture_rect_hovered
-        } else {
-            &self.texture_rect
-        };
-        renderer.draw_img(self.base.get_rect(), self.texture, rect);
-        self.base.draw(renderer);
+    fn is_focusable(&self) -> bool {
+        true
     }
 }
 
-impl BuildFromCfg<WRef> for Button {
-    fn build(mut cfg: Config, res: &mut dyn resources::Manager) -> Result<WRef, builder::Error> {
-        let bg_name = cfg
-            .take::<String>("background")
-            .change_context(builder::Error::msg("Failed to init button background texture"))?;
-        let texture = res.get_texture(&bg_name).change_context(builder::Error::msg(format!(
-            "Failed to init button, texture: \"{bg_name}\" not found"
-        )))?;
-
-        let mut get_rect = |name| -> Result<Rectf, builder::Error> {
-            cfg.take(name).change_context(builder::Error::msg("Failed to init button"))
+impl BuildFromCfg<Node> for Button {
+    fn build(mut cfg: Config, res: &mut dyn resources::Manager) -> Result<Node, builder::Error> {
+        let texture = cfg
+            .take_opt::<String>("background")
+            .change_context(builder::Error::msg("Failed to init button background texture"))?
+            .map(|name| {
+                res.get_texture(&name).change_context(builder::Error::msg(format!(
+                    "Failed to init button, texture: \"{name}\" not found"
+                )))
+            })
+            .transpose()?;
+        let slice = cfg
+            .take_opt::<[f64; 4]>("slice")
+            .change_context(builder::Error::msg("Failed to init button texture slice insets"))?
+            .map(Insets::from);
+
+        let mut get_rect = |name| -> Result<Option<Rectf>, builder::Error> {
+            cfg.take_opt(name).change_context(builder::Error::msg("Failed to init button"))
         };
-
-        Ok(WRef::new(Self {
-            hovered: false,
-            state: false,
-            texture,
-            texture_rect_pressed: get_rect("texture_rect_pressed")?,
-            texture_rect_hovered: get_rect("texture_rect_hovered")?,
-            texture_rect: get_rect("texture_rect")?,
-            base: Base::new(cfg)?,
-            cb: None,
-        }))
+        let normal =
+            Style { texture, texture_rect: get_rect("texture_rect")?, slice, ..Style::default() };
+        let hovered = Style { texture_rect: get_rect("texture_rect_hovered")?, ..Style::default() };
+        let pressed = Style { texture_rect: get_rect("texture_rect_pressed")?, ..Style::default() };
+
+        let mut base = Base::new(cfg)?;
+        for (state, style) in [
+            (StyleState::Normal, normal),
+            (StyleState::Hovered, hovered),
+            (StyleState::Pressed, pressed),
+        ] {
+            let inline = base.inline_style_mut(state);
+            *inline = inline.merge(&style);
+        }
+        Ok(Node::new(base, Self { hovered: false, state: false, focused: false }))
     }
 }
@@ -4,89 +4,82 @@
 
 use crate::manager::{
     widget::{
-        event::{Event, MouseButton},
-        Error, WRef, Widget,
+        event::{Event, MouseButton, Phase},
+        signal::{Signal, Value},
+        Error, Node, Widget, WidgetId,
     },
     State,
 };
 use builder::{self, config::Config, BuildFromCfg};
 use error_stack::{Result, ResultExt};
-use renderer::{Drawable, Renderer};
-use resources::TextureId;
-use std::{cell::RefCell, rc::Weak};
-use utils::{rect::Rectf, vec2::Vec2f};
+use renderer::{slice::Insets, Renderer};
+use scene::event::KeyCode;
+use utils::rect::Rectf;
 
-use super::Base;
-
-/// Change state callback. Args: flag and it is state.
-type FlagCb = dyn FnMut(&mut Flag, bool);
+use super::{
+    style::{Style, StyleState},
+    Base,
+};
 
-/// Flag widget.
+/// Flag widget. Emits `clicked` and `value_changed` signals.
+/// Background is drawn by style: `texture_rect` in off state and `texture_rect_on` in on state.
 pub struct Flag {
-    /// Base widget.
-    base: Base,
-    /// Background texture.
-    texture: TextureId,
-    /// Background texture rectangle on on state.
-    texture_rect_on: Rectf,
-    /// Background texture rectangle on off state.
-    texture_rect_off: Rectf,
-    /// Background texture rectangle on hovered and on state.
-    texture_rect_hovered_on: Rectf,
-    /// Background texture rectangle on hovered and off state.
-    texture_rect_hovered_off: Rectf,
     /// Is widget hovered.
     hovered: bool,
     /// Flag state.
     state: bool,
-    /// Change state cb.
-    cb: Option<Box<FlagCb>>,
 }
 
 impl Flag {
-    /// Set change state callback. This callback will be called when flag change state.
-    pub fn change_state_cb<F: 'static + FnMut(&mut Self, bool)>(&mut self, cb: F) {
-        self.cb = Some(Box::new(cb));
+    /// Toggle flag state. Emits `clicked` and `value_changed` signals.
+    fn toggle(&mut self, self_id: WidgetId, state: &mut State) {
+        self.state = !self.state;
+        state.emit(self_id, Signal::Clicked);
+        state.emit(self_id, Signal::ValueChanged(Value::Bool(self.state)));
+    }
+
+    /// Get flag state.
+    #[must_use]
+    pub const fn get_state(&self) -> bool {
+        self.state
     }
 }
 
 impl Widget for Flag {
+    fn type_name(&self) -> &'static str {
+        "flag"
+    }
+
     fn handle_event(
         &mut self,
-        self_rc: WRef,
+        base: &mut Base,
+        self_id: WidgetId,
         event: Event,
         state: &mut State,
     ) -> Result<(), Error> {
+        if state.get_phase() != Phase::Target {
+            return Ok(());
+        }
         match event {
             Event::MousePress(mouse_button) => {
                 if matches!(mouse_button, MouseButton::Left) && state.get_caught().is_none() {
-                    self.set_position(self.get_global_position());
-                    self.get_parent()
-                        .map(|p| p.upgrade().map(|p| p.borrow_mut().erase_widget(&self_rc)));
-                    state.catch_self(self, self_rc)?;
+                    state.catch_self(self, base, self_id)?;
                 }
             }
             Event::MouseRelease(mouse_button) => {
-                if matches!(mouse_button, MouseButton::Left) && state.is_caught(self_rc.clone()) {
-                    if self.check_bounds(state.mouse) {
-                        self.state = !self.state;
-                        if let Some(mut cb) = self.cb.take() {
-                            cb(self, self.state);
-                            self.cb = Some(cb);
-                        }
+                if matches!(mouse_button, MouseButton::Left) && state.is_caught(self_id) {
+                    if base.check_global_bounds(state.mouse) {
+                        self.toggle(self_id, state);
                     }
-                    state.uncatch(self, self_rc.clone())?;
-                    self.get_parent().map(|p| {
-                        p.upgrade().map(|p| {
-                            p.clone().borrow_mut().add_widget(p.into(), self, self_rc);
-                        })
-                    });
-                    self.hovered = self.check_bounds(state.mouse);
-                    self.set_global_position(self.get_position());
+                    state.uncatch(self, base, self_id)?;
+                    self.hovered = base.check_global_bounds(state.mouse);
                 }
             }
+            Event::KeyPress(KeyCode::Enter | KeyCode::Space) if state.is_focused(self_id) => {
+                self.toggle(self_id, state);
+            }
             Event::MouseEnter => self.hovered = true,
-            Event::MouseLeave => self.hovered = state.get_caught() == Some(self_rc),
+            Event::MouseLeave => self.hovered = state.is_caught(self_id),
             Event::MouseMove
             | Event::TextInput(_)
             | Event::Caught
@@ -94,116 +87,92 @@ impl Widget for Flag {
             | Event::Focused
             | Event::Unfocused
             | Event::KeyPress(_)
-            | Event::KeyRelease(_) => {}
+            | Event::KeyRelease(_)
+            | Event::DragStart
+            | Event::DragMove
+            | Event::DragEnd
+            | Event::DragEnter
+            | Event::DragLeave
+            | Event::Drop => {}
         }
+        base.set_style_state(if state.is_caught(self_id) {
+            StyleState::Pressed
+        } else if self.hovered {
+            StyleState::Hovered
+        } else if state.is_focused(self_id) {
+            StyleState::Focused
+        } else {
+            StyleState::Normal
+        });
         Ok(())
     }
 
-    fn get_hovered(&self, pos: Vec2f) -> Option<WRef> {
-        self.base.get_hovered(pos)
-    }
-
-    fn check_bounds(&self, pos: Vec2f) -> bool {
-        self.base.check_bounds(pos)
-    }
-
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef) {
-        self.base.add_widget(self_ref, widget, widget_ref);
-    }
-
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>) {
-        self.base.set_parent(parent);
-    }
-
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>> {
-        self.base.get_parent()
-    }
-
-    fn detach(&mut self, self_rc: &WRef) {
-        self.base.detach(self_rc);
-    }
-
-    fn erase_widget(&mut self, widget: &WRef) {
-        self.base.erase_widget(widget);
-    }
-
-    fn set_position(&mut self, pos: Vec2f) {
-        self.base.set_position(pos);
-    }
-
-    fn get_position(&self) -> Vec2f {
-        self.base.get_position()
-    }
-
-    fn set_global_position(&mut self, pos: Vec2f) {
-        self.base.set_global_position(pos);
-    }
-
-    fn get_global_position(&self) -> Vec2f {
-        self.base.get_global_position()
-    }
-
-    fn get_rect(&self) -> &Rectf {
-        self.base.get_rect()
-    }
-
-    fn find(&self, id: &str) -> Option<WRef> {
-        self.base.find(id)
-    }
-
-    fn get_id(&self) -> String {
-        self.base.get_id()
-    }
-
-    fn set_visible_flag(&mut self, is_visible: bool) {
-        self.base.set_visible_flag(is_visible);
+    fn draw(&self, base: &Base, renderer: &mut dyn Renderer) {
+        let style = base.style();
+        let rect = if self.state { style.texture_rect_on } else { style.texture_rect };
+        if let (Some(texture), Some(rect)) = (style.texture, rect) {
+            let slice = style.slice.unwrap_or_default();
+            renderer.draw_img_sliced(base.get_rect(), texture, &rect, &slice);
+        }
     }
 
-    fn is_visible(&self) -> bool {
-        self.base.is_visible()
+    fn is_focusable(&self) -> bool {
+        true
     }
-}
 
-impl Drawable for Flag {
-    fn draw(&self, renderer: &mut dyn Renderer) {
-        let rect = match (self.hovered, self.state) {
-            (true, true) => &self.texture_rect_hovered_on,
-            (true, false) => &self.texture_rect_hovered_off,
-            (false, true) => &self.texture_rect_on,
-            (false, false) => &self.texture_rect_off,
-        };
-        renderer.draw_img(self.base.get_rect(), self.texture, rect);
-        self.base.draw(renderer);
+    fn set_bound_value(&mut self, value: &Value) {
+        if let Some(state) = value.as_bool() {
+            self.state = state;
+        }
     }
 }
 
-impl BuildFromCfg<WRef> for Flag {
-    fn build(mut cfg: Config, res: &mut dyn resources::Manager) -> Result<WRef, builder::Error> {
-        let bg_name = cfg
-            .take::<String>("background")
-            .change_context(builder::Error::msg("Failed to init flag background texture"))?;
+impl BuildFromCfg<Node> for Flag {
+    fn build(mut cfg: Config, res: &mut dyn resources::Manager) -> Result<Node, builder::Error> {
+        let texture = cfg
+            .take_opt::<String>("background")
+            .change_context(builder::Error::msg("Failed to init flag background texture"))?
+            .map(|name| {
+                res.get_texture(&name).change_context(builder::Error::msg(format!(
+                    "Failed to init flag, texture: \"{name}\" not found"
+                )))
+            })
+            .transpose()?;
         let state = cfg
             .take_opt::<bool>("state")
             .change_context(builder::Error::msg("Failed to init flag state"))?
             .unwrap_or(false);
-        let texture = res.get_texture(&bg_name).change_context(builder::Error::msg(format!(
-            "Failed to init flag, texture: \"{bg_name}\" not found"
-        )))?;
+        let slice = cfg
+            .take_opt::<[f64; 4]>("slice")
+            .change_context(builder::Error::msg("Failed to init flag texture slice insets"))?
+            .map(Insets::from);
 
-        let mut get_rect = |name| -> Result<Rectf, builder::Error> {
-            cfg.take(name).change_context(builder::Error::msg("Failed to init flag"))
+        let mut get_rect = |name| -> Result<Option<Rectf>, builder::Error> {
+            cfg.take_opt(name).change_context(builder::Error::msg("Failed to init flag"))
         };
-
-        Ok(WRef::new(Self {
-            hovered: false,
-            state,
+        let normal = Style {
             texture,
+            texture_rect: get_rect("texture_rect_off")?,
             texture_rect_on: get_rect("texture_rect_on")?,
-            texture_rect_hovered_off: get_rect("texture_rect_hovered_off")?,
-            texture_rect_hovered_on: get_rect("texture_rect_hovered_on")?,
-            texture_rect_off: get_rect("texture_rect_off")?,
-            base: Base::new(cfg)?,
-            cb: None,
-        }))
+            slice,
+            ..Style::default()
+        };
+        let hovered = Style {
+            texture_rect: get_rect("texture_rect_hovered_off")?,
+            texture_rect_on: get_rect("texture_rect_hovered_on")?,
+            ..Style::default()
+        };
+
+        let mut base = Base::new(cfg)?;
+        // Flag config has no pressed rectangles, pressed flag looks hovered.
+        for (state, style) in [
+            (StyleState::Normal, normal),
+            (StyleState::Hovered, hovered),
+            (StyleState::Pressed, hovered),
+        ] {
+            let inline = base.inline_style_mut(state);
+            *inline = inline.merge(&style);
+        }
+        Ok(Node::new(base, Self { hovered: false, state }))
     }
 }
@@ -1,26 +1,22 @@
 //! Simple graph.
 
 use error_stack::{Result, ResultExt};
-use renderer::{Drawable, Renderer};
-use std::{cell::RefCell, rc::Weak};
+use renderer::{LineJoin, Renderer, Stroke};
 use utils::{
     color::{self, Color},
     rect::Rectf,
     vec2::Vec2f,
 };
 
-use crate::manager::{
-    widget::{event::Event, Error, WRef, Widget},
-    State,
-};
+use crate::manager::widget::{Node, Widget};
 use builder::{self, config::Config, BuildFromCfg};
 
 use super::Base;
 
 /// Simple graph.
 pub struct Graph {
-    /// Base widget.
-    base: Base,
+    /// Graph size.
+    size: Vec2f,
     /// Queue length.
     value_count: usize,
     /// Points.
@@ -31,6 +27,10 @@ pub struct Graph {
     value_min: f64,
     /// Graph color.
     color: Color,
+    /// Graph line width.
+    line_width: f64,
+    /// Color of the area under graph. `None` if area is not filled.
+    fill_color: Option<Color>,
 }
 
 impl Graph {
@@ -38,13 +38,12 @@ impl Graph {
     pub fn push(&mut self, value: f64) {
         let mut points = Vec::with_capacity(self.value_count);
         std::mem::swap(&mut self.points, &mut points);
-        let bounds = self.base.get_rect();
-        let v = bounds.h
-            - bounds.h * (value.clamp(self.value_min, self.value_max) - self.value_min)
-                / (self.value_max - self.value_min);
-        self.points.push((bounds.w, v).into());
+        let (w, h) = (self.size.x, self.size.y);
+        let v = h - h * (value.clamp(self.value_min, self.value_max) - self.value_min)
+            / (self.value_max - self.value_min);
+        self.points.push((w, v).into());
         #[allow(clippy::cast_precision_loss)]
-        let x_step = bounds.w / (self.value_count - 1) as f64;
+        let x_step = w / (self.value_count - 1) as f64;
         for (v, i) in points.into_iter().zip(1..self.value_count) {
             #[allow(clippy::cast_precision_loss)]
             let x = x_step * (self.value_count - 1 - i) as f64;
@@ -54,95 +53,42 @@ impl Graph {
 }
 
 impl Widget for Graph {
-    fn handle_event(
-        &mut self,
-        _self_rc: WRef,
-        _event: Event,
-        _state: &mut State,
-    ) -> Result<(), Error> {
-        Ok(())
-    }
-
-    fn get_hovered(&self, pos: Vec2f) -> Option<WRef> {
-        self.base.get_hovered(pos)
-    }
-
-    fn check_bounds(&self, pos: Vec2f) -> bool {
-        self.base.check_bounds(pos)
-    }
-
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef) {
-        self.base.add_widget(self_ref, widget, widget_ref);
-    }
-
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>) {
-        self.base.set_parent(parent);
-    }
-
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>> {
-        self.base.get_parent()
-    }
-
-    fn detach(&mut self, self_rc: &WRef) {
-        self.base.detach(self_rc);
-    }
-
-    fn erase_widget(&mut self, widget: &WRef) {
-        self.base.erase_widget(widget);
-    }
-
-    fn set_position(&mut self, pos: Vec2f) {
-        self.base.set_position(pos);
-    }
-
-    fn get_position(&self) -> Vec2f {
-        self.base.get_position()
+    fn type_name(&self) -> &'static str {
+        "graph"
     }
 
-    fn set_global_position(&mut self, pos: Vec2f) {
-        self.base.set_global_position(pos);
-    }
-
-    fn get_global_position(&self) -> Vec2f {
-        self.base.get_global_position()
-    }
-
-    fn get_rect(&self) -> &Rectf {
-        self.base.get_rect()
-    }
-
-    fn find(&self, id: &str) -> Option<WRef> {
-        self.base.find(id)
-    }
-
-    fn get_id(&self) -> String {
-        self.base.get_id()
-    }
-
-    fn set_visible_flag(&mut self, is_visible: bool) {
-        self.base.set_visible_flag(is_visible);
-    }
-
-    fn is_visible(&self) -> bool {
-        self.base.is_visible()
-    }
-}
-
-impl Drawable for Graph {
-    fn draw(&self, renderer: &mut dyn Renderer) {
-        let bounds = self.base.get_rect();
+    fn draw(&self, base: &Base, renderer: &mut dyn Renderer) {
+        let bounds = base.get_rect();
         renderer.draw_rect(bounds, &color::BLACK);
         renderer.push_state();
         renderer.translate(bounds.x, bounds.y);
-        renderer.draw_line(&self.points, &self.color);
+        if let Some(ref fill_color) = self.fill_color {
+            let h = bounds.h;
+            let area: Vec<_> = self
+                .points
+                .windows(2)
+                .flat_map(|w| {
+                    let (a, b) = (w[0], w[1]);
+                    let (a_bottom, b_bottom) = (Vec2f::new(a.x, h), Vec2f::new(b.x, h));
+                    [[a, b, b_bottom], [a, b_bottom, a_bottom]]
+                })
+                .collect();
+            renderer.fill_triangles(&area, fill_color);
+        }
+        let stroke = Stroke { join: LineJoin::Round, ..Stroke::new(self.line_width) };
+        renderer.stroke_polyline(&self.points, false, &self.color, &stroke);
         renderer.pop_state();
-        self.base.draw(renderer);
+    }
+
+    fn arrange(&mut self, rect: &Rectf) {
+        self.size = Vec2f::new(rect.w, rect.h);
     }
 }
 
-impl BuildFromCfg<WRef> for Graph {
-    fn build(mut cfg: Config, _res: &mut dyn resources::Manager) -> Result<WRef, builder::Error> {
-        Ok(WRef::new(Self {
+impl BuildFromCfg<Node> for Graph {
+    fn build(mut cfg: Config, _res: &mut dyn resources::Manager) -> Result<Node, builder::Error> {
+        let mut graph = Self {
+            size: Vec2f::new(0.0, 0.0),
             value_count: cfg
                 .take("value_count")
                 .change_context(builder::Error::msg("Failed to init max values count"))?,
@@ -154,7 +100,16 @@ impl BuildFromCfg<WRef> for Graph {
                 .change_context(builder::Error::msg("Failed to init value maximum"))?,
             points: Vec::new(),
             color: cfg.take("color").change_context(builder::Error::msg("Failed to init color"))?,
-            base: Base::new(cfg)?,
-        }))
+            line_width: cfg
+                .take_opt("line_width")
+                .change_context(builder::Error::msg("Failed to init line width"))?
+                .unwrap_or(1.0),
+            fill_color: cfg
+                .take_opt("fill_color")
+                .change_context(builder::Error::msg("Failed to init fill color"))?,
+        };
+        let base = Base::new(cfg)?;
+        graph.arrange(base.get_rect());
+        Ok(Node::new(base, graph))
     }
 }
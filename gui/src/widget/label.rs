@@ -2,79 +2,217 @@
 //!
 //! Label widget, that used for display text.
 
-use crate::manager::{
-    widget::{event::Event, Error, WRef, Widget},
-    State,
-};
+use crate::manager::widget::{signal::Value, Node, Widget};
 use builder::{self, config::Config, BuildFromCfg};
 use error_stack::{Result, ResultExt};
-use renderer::{Drawable, Renderer, TextTruncateMode};
-use resources::FontId;
+use renderer::{
+    text::{self, HAlign, LayoutOptions, Span, TextLayout, TextStyle, VAlign},
+    Renderer, TextTruncateMode,
+};
+use resources::FontStyle;
 use std::{
     cell::{Ref, RefCell, RefMut},
-    rc::Weak,
+    collections::HashMap,
+    str::FromStr,
+};
+use utils::color::Color;
+
+use super::{
+    style::{Style, StyleState},
+    Base,
 };
-use utils::{color::Color, rect::Rectf, vec2::Vec2f};
 
-use super::Base;
+/// Font size, used if it is not specified by config or theme.
+const DEFAULT_FONT_SIZE: f64 = 10.0;
+/// Text color, used if it is not specified by config or theme.
+const DEFAULT_COLOR: Color = Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 };
+
+/// Localized text source.
+struct Localized {
+    /// String key.
+    key: String,
+    /// Format arguments.
+    args: Vec<(String, String)>,
+}
+
+impl Localized {
+    /// Get string in current locale.
+    fn get(&self, res: &dyn resources::Manager) -> Result<String, resources::Error> {
+        let args: Vec<_> = self.args.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        res.get_string(&self.key, &args)
+    }
+}
 
 /// Label widget.
+///
+/// Text started with `@` is a localized string key (`"@menu.start"`), use `@@` for literal `@`.
+/// Localized string arguments are specified by `"args"` config field.
+/// If `"markup"` is enabled, text may contain color and size tags, see [`text::parse_markup`].
 pub struct Label {
-    /// Base widget.
-    base: Base,
     /// Label text.
     text: RefCell<Vec<char>>,
-    /// Font size.
-    size: f64,
-    /// Font identification.
-    font: FontId,
-    /// Text color.
-    color: Color,
+    /// Localized text source. `None` if text is literal.
+    localized: RefCell<Option<Localized>>,
     /// Text draw truncate mode.
     draw_truncate: TextTruncateMode,
     /// Text truncate mode.
     need_to_truncate_text: bool,
-    /// Label border color.
-    rect_color: Color,
+    /// Text layout options.
+    options: LayoutOptions,
+    /// Parse text markup.
+    markup: bool,
+    /// Layout of the last drawn text.
+    layout: RefCell<TextLayout>,
 }
 
 impl Label {
-    /// Create new label.
+    /// Create new label and its common properties.
     ///
     /// # Errors
     /// Return error if the config is incorrect or the required resource is not found.
-    pub fn new(mut cfg: Config, res: &mut dyn resources::Manager) -> Result<Self, builder::Error> {
-        Ok(Self {
-            text: RefCell::new(
-                cfg.take::<String>("text")
-                    .change_context(builder::Error::msg("Failed to init label text"))?
-                    .chars()
-                    .collect(),
-            ),
-            size: cfg
-                .take::<f64>("font_size")
+    pub fn new(
+        mut cfg: Config,
+        res: &mut dyn resources::Manager,
+    ) -> Result<(Base, Self), builder::Error> {
+        let text = cfg
+            .take::<String>("text")
+            .change_context(builder::Error::msg("Failed to init label text"))?;
+        let args = cfg
+            .take_opt::<HashMap<String, String>>("args")
+            .change_context(builder::Error::msg("Failed to init label text arguments"))?
+            .unwrap_or_default();
+        let localized = text
+            .strip_prefix('@')
+            .filter(|key| !key.starts_with('@'))
+            .map(|key| Localized { key: key.to_owned(), args: args.into_iter().collect() });
+        let text = if let Some(ref l) = localized {
+            l.get(res).change_context(builder::Error::msg("Failed to init localized label text"))?
+        } else {
+            text.strip_prefix('@').unwrap_or(&text).to_owned()
+        };
+        let h_align = cfg
+            .take_opt::<String>("h_align")
+            .change_context(builder::Error::msg("Failed to init label horizontal align"))?
+            .map(|s| HAlign::from_str(&s))
+            .transpose()
+            .change_context(builder::Error::msg("Failed to parse label horizontal align"))?
+            .unwrap_or_default();
+        let v_align = cfg
+            .take_opt::<String>("v_align")
+            .change_context(builder::Error::msg("Failed to init label vertical align"))?
+            .map(|s| VAlign::from_str(&s))
+            .transpose()
+            .change_context(builder::Error::msg("Failed to parse label vertical align"))?
+            .unwrap_or_default();
+        let wrap = cfg
+            .take_opt::<bool>("wrap")
+            .change_context(builder::Error::msg("Failed to init label wrap flag"))?
+            .unwrap_or(true);
+        let ellipsis = cfg
+            .take_opt::<bool>("ellipsis")
+            .change_context(builder::Error::msg("Failed to init label ellipsis flag"))?
+            .unwrap_or(false);
+        let line_spacing = cfg
+            .take_opt::<f64>("line_spacing")
+            .change_context(builder::Error::msg("Failed to init label line spacing"))?
+            .unwrap_or(1.0);
+        let markup = cfg
+            .take_opt::<bool>("markup")
+            .change_context(builder::Error::msg("Failed to init label markup flag"))?
+            .unwrap_or(false);
+        let options = LayoutOptions {
+            h_align,
+            v_align,
+            wrap,
+            ellipsis,
+            line_spacing,
+            truncate: TextTruncateMode::Back,
+        };
+        let font_style = cfg
+            .take_opt::<String>("font_style")
+            .change_context(builder::Error::msg("Failed to init label font style"))?
+            .map(|s| FontStyle::from_str(&s))
+            .transpose()
+            .change_context(builder::Error::msg("Failed to parse label font style"))?
+            .unwrap_or_default();
+        let font = cfg
+            .take_opt::<String>("font")
+            .change_context(builder::Error::msg("Failed to init label font"))?
+            .map(|name| res.get_font_style(&name, font_style))
+            .transpose()
+            .change_context(builder::Error::msg("Failed to find required font"))?;
+        let style = Style {
+            font,
+            font_size: cfg
+                .take_opt("font_size")
                 .change_context(builder::Error::msg("Failed to init label font size"))?,
-            font: res
-                .get_font(
-                    &cfg.take::<String>("font")
-                        .change_context(builder::Error::msg("Failed to init label font"))?,
-                )
-                .change_context(builder::Error::msg("Failed to find required font"))?,
-            color: cfg.take("color").change_context(builder::Error::msg("Failed to init color"))?,
-            rect_color: cfg
-                .take("rect_color")
-                .change_context(builder::Error::msg("Failed to init Textbox border color"))?,
-            base: Base::new(cfg)?,
+            color: cfg
+                .take_opt("color")
+                .change_context(builder::Error::msg("Failed to init color"))?,
+            border_color: cfg
+                .take_opt("rect_color")
+                .change_context(builder::Error::msg("Failed to init label border color"))?,
+            ..Style::default()
+        };
+        let mut base = Base::new(cfg)?;
+        let inline = base.inline_style_mut(StyleState::Normal);
+        *inline = inline.merge(&style);
+        let label = Self {
+            text: RefCell::new(text.chars().collect()),
+            localized: RefCell::new(localized),
             draw_truncate: TextTruncateMode::Back,
             need_to_truncate_text: false,
-        })
+            options,
+            markup,
+            layout: RefCell::new(TextLayout::default()),
+        };
+        Ok((base, label))
     }
 
     /// Set label text.
     pub fn set_text(&self, txt: &str) {
+        *self.localized.borrow_mut() = None;
         *self.text.borrow_mut() = txt.chars().collect();
     }
 
+    /// Set localized label text by string key and format arguments.
+    /// The text is updated on locale switching by [`Manager::set_locale`].
+    ///
+    /// # Errors
+    /// Return error if the string is not found.
+    ///
+    /// [`Manager::set_locale`]: crate::manager::Manager::set_locale
+    pub fn set_text_key(
+        &self,
+        key: &str,
+        args: &[(&str, &str)],
+        res: &dyn resources::Manager,
+    ) -> Result<(), resources::Error> {
+        let localized = Localized {
+            key: key.to_owned(),
+            args: args.iter().map(|(k, v)| ((*k).to_owned(), (*v).to_owned())).collect(),
+        };
+        *self.text.borrow_mut() = localized.get(res)?.chars().collect();
+        *self.localized.borrow_mut() = Some(localized);
+        Ok(())
+    }
+
+    /// Update localized text to current locale. Literal text is not changed.
+    ///
+    /// # Errors
+    /// Return error if the string is not found.
+    pub fn localize(&self, res: &dyn resources::Manager) -> Result<(), resources::Error> {
+        if let Some(ref l) = *self.localized.borrow() {
+            *self.text.borrow_mut() = l.get(res)?.chars().collect();
+        }
+        Ok(())
+    }
+
+    /// Check if label text is localized.
+    pub fn is_localized(&self) -> bool {
+        self.localized.borrow().is_some()
+    }
+
     /// Set label text.
     pub fn get_text(&self) -> String {
         self.text.borrow().iter().collect()
@@ -96,97 +234,49 @@ impl Label {
     }
 
     /// Set to true to clip the text to fit it into the rectangle.
+    /// Text with markup is never clipped.
     pub fn set_text_truncating(&mut self, mode: bool) {
         self.need_to_truncate_text = mode;
     }
-}
-
-impl Widget for Label {
-    fn handle_event(
-        &mut self,
-        _self_rc: WRef,
-        _event: Event,
-        _state: &mut State,
-    ) -> Result<(), Error> {
-        Ok(())
-    }
-
-    fn get_hovered(&self, pos: Vec2f) -> Option<WRef> {
-        self.base.get_hovered(pos)
-    }
 
-    fn check_bounds(&self, pos: Vec2f) -> bool {
-        self.base.check_bounds(pos)
+    /// Set text layout options. Truncate mode is set by [`Label::set_draw_truncate_mode`].
+    pub const fn set_layout_options(&mut self, options: LayoutOptions) {
+        self.options = options;
     }
 
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef) {
-        self.base.add_widget(self_ref, widget, widget_ref);
-    }
-
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>) {
-        self.base.set_parent(parent);
-    }
-
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>> {
-        self.base.get_parent()
-    }
-
-    fn detach(&mut self, self_rc: &WRef) {
-        self.base.detach(self_rc);
-    }
-
-    fn erase_widget(&mut self, widget: &WRef) {
-        self.base.erase_widget(widget);
-    }
-
-    fn set_position(&mut self, pos: Vec2f) {
-        self.base.set_position(pos);
-    }
-
-    fn get_position(&self) -> Vec2f {
-        self.base.get_position()
-    }
-
-    fn set_global_position(&mut self, pos: Vec2f) {
-        self.base.set_global_position(pos);
-    }
-
-    fn get_global_position(&self) -> Vec2f {
-        self.base.get_global_position()
-    }
-
-    fn get_rect(&self) -> &Rectf {
-        self.base.get_rect()
-    }
-
-    fn find(&self, id: &str) -> Option<WRef> {
-        self.base.find(id)
-    }
-
-    fn get_id(&self) -> String {
-        self.base.get_id()
+    /// Get layout of the last drawn text. It is used for caret and selection hit-testing.
+    /// Char indices are indices in the text without markup tags.
+    pub fn text_layout(&self) -> Ref<'_, TextLayout> {
+        self.layout.borrow()
     }
+}
 
-    fn set_visible_flag(&mut self, is_visible: bool) {
-        self.base.set_visible_flag(is_visible);
+impl Widget for Label {
+    fn type_name(&self) -> &'static str {
+        "label"
     }
 
-    fn is_visible(&self) -> bool {
-        self.base.is_visible()
-    }
-}
-
-impl Drawable for Label {
-    fn draw(&self, renderer: &mut dyn Renderer) {
-        let rc = renderer.draw_text(
-            self.text.borrow().as_slice(),
-            self.size,
-            self.base.get_rect(),
-            self.font,
-            &self.color,
-            self.draw_truncate,
-        );
-        if self.need_to_truncate_text && rc != 0 {
+    fn draw(&self, base: &Base, renderer: &mut dyn Renderer) {
+        let style = base.style();
+        let Some(font) = style.font else {
+            return;
+        };
+        let style = TextStyle {
+            font,
+            size: style.font_size.unwrap_or(DEFAULT_FONT_SIZE),
+            color: style.color.unwrap_or(DEFAULT_COLOR),
+        };
+        let spans = if self.markup {
+            text::parse_markup(&self.text.borrow(), &style)
+        } else {
+            vec![Span { text: self.text.borrow().clone(), style }]
+        };
+        let options = LayoutOptions { truncate: self.draw_truncate, ..self.options };
+        let layout = text::layout(&spans, base.get_rect(), &options, &mut *renderer);
+        renderer.draw_layout(&layout);
+        let rc = layout.truncated;
+        *self.layout.borrow_mut() = layout;
+        if self.need_to_truncate_text && !self.markup && rc != 0 {
             match self.draw_truncate {
                 TextTruncateMode::Front => {
                     self.text.borrow_mut().drain(..rc);
@@ -197,13 +287,16 @@ impl Drawable for Label {
                 }
             }
         }
-        renderer.draw_rect(self.base.get_rect(), &self.rect_color);
-        self.base.draw(renderer);
+    }
+
+    fn set_bound_value(&mut self, value: &Value) {
+        self.set_text(&value.to_string());
     }
 }
 
-impl BuildFromCfg<WRef> for Label {
-    fn build(cfg: Config, res: &mut dyn resources::Manager) -> Result<WRef, builder::Error> {
-        Ok(WRef::new(Self::new(cfg, res)?))
+impl BuildFromCfg<Node> for Label {
+    fn build(cfg: Config, res: &mut dyn resources::Manager) -> Result<Node, builder::Error> {
+        let (base, label) = Self::new(cfg, res)?;
+        Ok(Node::new(base, label))
     }
 }
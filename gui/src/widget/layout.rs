@@ -0,0 +1,459 @@
+//! Widget layout.
+//!
+//! Layout places child widgets inside the parent content area (parent bounds without padding).
+//! Each widget has [`Item`] params: size, size limits, margin and anchors. Parent [`Layout`]
+//! defines how childs are arranged: by anchors, in horizontal or vertical stack or in grid.
+//! Layout is recalculated by [`Manager`] before drawing and on resize.
+//!
+//! [`Manager`]: crate::manager::Manager
+
+use error_stack::{bail, Result, ResultExt};
+use std::str::FromStr;
+
+use builder::{self, config::Config};
+use renderer::slice::Insets;
+use utils::{rect::Rectf, vec2::Vec2f};
+
+/// Layout parse error.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct Error(String);
+impl Error {
+    /// Make error from message.
+    fn msg<T: Into<String>>(msg: T) -> Self {
+        Self(msg.into())
+    }
+}
+
+/// Widget size along one axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+    /// Size in pixels.
+    Px(f64),
+    /// Percentage of parent content area size.
+    Percent(f64),
+}
+
+impl Length {
+    /// Get size in pixels for parent content area with specified size.
+    #[must_use]
+    pub fn resolve(self, area: f64) -> f64 {
+        match self {
+            Self::Px(px) => px,
+            Self::Percent(p) => area * p / 100.0,
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = Error;
+
+    /// Parse length: `"20"` is 20 pixels, `"50%"` is half of parent size.
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let err = || Error::msg(format!("Unexpected length: {s:?}"));
+        match s.trim().strip_suffix('%') {
+            Some(p) => Ok(Self::Percent(p.trim().parse().map_err(|_| err())?)),
+            None => Ok(Self::Px(s.trim().parse().map_err(|_| err())?)),
+        }
+    }
+}
+
+/// Widget alignment along one axis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    /// Keep position from widget rectangle. In stacks and grids it is same as `Start`.
+    #[default]
+    Position,
+    /// Dock to the left or top edge.
+    Start,
+    /// Center in the parent.
+    Center,
+    /// Dock to the right or bottom edge.
+    End,
+    /// Stretch to both edges.
+    Stretch,
+}
+
+impl Align {
+    /// Make alignment from anchored edges.
+    const fn from_edges(start: bool, end: bool, center: bool) -> Self {
+        match (start, end) {
+            (true, true) => Self::Stretch,
+            (true, false) => Self::Start,
+            (false, true) => Self::End,
+            (false, false) if center => Self::Center,
+            (false, false) => Self::Position,
+        }
+    }
+
+    /// Place segment with specified position and length into area.
+    /// Margins are added on both sides of the segment. Return new position and length.
+    fn place(self, pos: f64, len: f64, area: (f64, f64), margin: (f64, f64)) -> (f64, f64) {
+        let (start, size) = area;
+        let (m_start, m_end) = margin;
+        match self {
+            Self::Position => (pos, len),
+            Self::Start => (start + m_start, len),
+            Self::Center => (start + m_start + (size - m_start - m_end - len) / 2.0, len),
+            Self::End => (start + size - m_end - len, len),
+            Self::Stretch => (start + m_start, (size - m_start - m_end).max(0.0)),
+        }
+    }
+}
+
+/// Widget placement params in parent layout.
+#[derive(Clone, Debug)]
+pub struct Item {
+    /// Widget width and height.
+    pub size: [Length; 2],
+    /// Minimum widget size.
+    pub min_size: Vec2f,
+    /// Maximum widget size.
+    pub max_size: Vec2f,
+    /// Space around widget.
+    pub margin: Insets,
+    /// Horizontal and vertical alignment.
+    pub align: [Align; 2],
+}
+
+impl Item {
+    /// Create layout item, that keeps widget rectangle.
+    #[must_use]
+    pub const fn fixed(rect: &Rectf) -> Self {
+        Self {
+            size: [Length::Px(rect.w), Length::Px(rect.h)],
+            min_size: Vec2f::new(0.0, 0.0),
+            max_size: Vec2f::new(f64::INFINITY, f64::INFINITY),
+            margin: Insets { left: 0.0, top: 0.0, right: 0.0, bottom: 0.0 },
+            align: [Align::Position; 2],
+        }
+    }
+
+    /// Create layout item from config. Size defaults to widget rectangle size.
+    /// Config fields:
+    /// - `size`: width and height, number of pixels or percentage string (`"50%"`);
+    /// - `min_size`, `max_size`: size limits;
+    /// - `margin`: space around widget: `[left, top, right, bottom]`;
+    /// - `anchor`: list of parent edges (`"left"`, `"top"`, `"right"`, `"bottom"`), that widget
+    ///   is docked to. Widget anchored to opposite edges is stretched, `"center"` centers widget
+    ///   along axes without anchored edges.
+    ///
+    /// # Errors
+    /// Return error if config is not valid.
+    pub fn new(cfg: &mut Config, rect: &Rectf) -> Result<Self, builder::Error> {
+        let mut item = Self::fixed(rect);
+        if let Some([w, h]) = cfg
+            .take_opt::<[String; 2]>("size")
+            .change_context(builder::Error::msg("Failed to init widget layout size"))?
+        {
+            let parse = |s: &str| {
+                Length::from_str(s)
+                    .change_context(builder::Error::msg("Failed to init widget layout size"))
+            };
+            item.size = [parse(&w)?, parse(&h)?];
+        }
+        if let Some(size) = cfg
+            .take_opt::<[f64; 2]>("min_size")
+            .change_context(builder::Error::msg("Failed to init widget min size"))?
+        {
+            item.min_size = size.into();
+        }
+        if let Some(size) = cfg
+            .take_opt::<[f64; 2]>("max_size")
+            .change_context(builder::Error::msg("Failed to init widget max size"))?
+        {
+            item.max_size = size.into();
+        }
+        if let Some(margin) = cfg
+            .take_opt::<[f64; 4]>("margin")
+            .change_context(builder::Error::msg("Failed to init widget margin"))?
+        {
+            item.margin = margin.into();
+        }
+        if let Some(anchor) = cfg
+            .take_opt::<Vec<String>>("anchor")
+            .change_context(builder::Error::msg("Failed to init widget anchor"))?
+        {
+            if let Some(edge) = anchor
+                .iter()
+                .find(|e| !["left", "top", "right", "bottom", "center"].contains(&e.as_str()))
+            {
+                bail!(builder::Error::msg(format!("Unexpected anchor edge: {edge:?}")));
+            }
+            let has = |edge: &str| anchor.iter().any(|e| e == edge);
+            let center = has("center");
+            item.align = [
+                Align::from_edges(has("left"), has("right"), center),
+                Align::from_edges(has("top"), has("bottom"), center),
+            ];
+        }
+        Ok(item)
+    }
+
+    /// Get widget size in parent content area with specified size. Margins are not included.
+    #[must_use]
+    pub fn measure(&self, area: Vec2f) -> Vec2f {
+        Vec2f::new(
+            self.clamp_w(self.size[0].resolve(area.x)),
+            self.clamp_h(self.size[1].resolve(area.y)),
+        )
+    }
+
+    /// Clamp width by size limits.
+    fn clamp_w(&self, w: f64) -> f64 {
+        w.min(self.max_size.x).max(self.min_size.x)
+    }
+
+    /// Clamp height by size limits.
+    fn clamp_h(&self, h: f64) -> f64 {
+        h.min(self.max_size.y).max(self.min_size.y)
+    }
+
+    /// Place widget into area. Positioned widget keeps its position from `rect`.
+    /// `aligns` overrides widget alignment, it is used by stacks and grids.
+    fn place(&self, rect: &Rectf, area: &Rectf, parent: Vec2f, aligns: [Align; 2]) -> Rectf {
+        let size = self.measure(parent);
+        let m = &self.margin;
+        let (x, w) = aligns[0].place(rect.x, size.x, (area.x, area.w), (m.left, m.right));
+        let (y, h) = aligns[1].place(rect.y, size.y, (area.y, area.h), (m.top, m.bottom));
+        Rectf { x, y, w: self.clamp_w(w), h: self.clamp_h(h) }
+    }
+
+    /// Get alignment used in stacks and grids. Positioned widgets are docked to start edges.
+    fn cell_align(&self) -> [Align; 2] {
+        self.align.map(|a| if a == Align::Position { Align::Start } else { a })
+    }
+}
+
+/// Child widgets arrangement kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Kind {
+    /// Childs are placed by their positions and anchors.
+    #[default]
+    Anchor,
+    /// Childs are placed left to right.
+    HStack,
+    /// Childs are placed top to bottom.
+    VStack,
+    /// Childs are placed by rows into grid with specified number of columns.
+    Grid(usize),
+}
+
+/// Child widgets layout.
+#[derive(Clone, Debug, Default)]
+pub struct Layout {
+    /// Arrangement kind.
+    pub kind: Kind,
+    /// Space between widget bounds and content area.
+    pub padding: Insets,
+    /// Space between childs in stacks and grid.
+    pub spacing: f64,
+}
+
+impl Layout {
+    /// Create layout from config.
+    /// Config fields:
+    /// - `type`: `"anchor"` (default), `"hstack"`, `"vstack"` or `"grid"`;
+    /// - `columns`: number of grid columns;
+    /// - `padding`: `[left, top, right, bottom]`;
+    /// - `spacing`: space between childs.
+    ///
+    /// # Errors
+    /// Return error if config is not valid.
+    pub fn new(mut cfg: Config) -> Result<Self, builder::Error> {
+        let kind = cfg
+            .take_opt::<String>("type")
+            .change_context(builder::Error::msg("Failed to init layout type"))?
+            .unwrap_or_else(|| "anchor".into());
+        let kind = match kind.as_str() {
+            "anchor" => Kind::Anchor,
+            "hstack" => Kind::HStack,
+            "vstack" => Kind::VStack,
+            "grid" => Kind::Grid(
+                cfg.take::<usize>("columns")
+                    .change_context(builder::Error::msg("Failed to init grid columns"))?
+                    .max(1),
+            ),
+            _ => bail!(builder::Error::msg(format!("Unexpected layout type: {kind:?}"))),
+        };
+        let padding = cfg
+            .take_opt::<[f64; 4]>("padding")
+            .change_context(builder::Error::msg("Failed to init layout padding"))?
+            .map(Insets::from)
+            .unwrap_or_default();
+        let spacing = cfg
+            .take_opt::<f64>("spacing")
+            .change_context(builder::Error::msg("Failed to init layout spacing"))?
+            .unwrap_or(0.0);
+        Ok(Self { kind, padding, spacing })
+    }
+
+    /// Calculate child rectangles in container with specified size.
+    /// Childs are specified by layout items and current rectangles.
+    #[must_use]
+    pub fn arrange(&self, size: Vec2f, childs: &[(&Item, Rectf)]) -> Vec<Rectf> {
+        let p = &self.padding;
+        let area = Rectf {
+            x: p.left,
+            y: p.top,
+            w: (size.x - p.left - p.right).max(0.0),
+            h: (size.y - p.top - p.bottom).max(0.0),
+        };
+        let content = Vec2f::new(area.w, area.h);
+        match self.kind {
+            Kind::Anchor => childs
+                .iter()
+                .map(|(item, rect)| item.place(rect, &area, content, item.align))
+                .collect(),
+            Kind::HStack => {
+                let mut x = area.x;
+                childs
+                    .iter()
+                    .map(|(item, rect)| {
+                        let [_, v] = item.cell_align();
+                        let w = item.measure(content).x + item.margin.left + item.margin.right;
+                        let cell = Rectf { x, y: area.y, w, h: area.h };
+                        x += w + self.spacing;
+                        item.place(rect, &cell, content, [Align::Start, v])
+                    })
+                    .collect()
+            }
+            Kind::VStack => {
+                let mut y = area.y;
+                childs
+                    .iter()
+                    .map(|(item, rect)| {
+                        let [h, _] = item.cell_align();
+                        let ch = item.measure(content).y + item.margin.top + item.margin.bottom;
+                        let cell = Rectf { x: area.x, y, w: area.w, h: ch };
+                        y += ch + self.spacing;
+                        item.place(rect, &cell, content, [h, Align::Start])
+                    })
+                    .collect()
+            }
+            Kind::Grid(columns) => {
+                #[allow(clippy::cast_precision_loss)]
+                let n = columns as f64;
+                let w = ((area.w - self.spacing * (n - 1.0)) / n).max(0.0);
+                let cell_size = Vec2f::new(w, area.h);
+                let mut rects = Vec::with_capacity(childs.len());
+                let mut y = area.y;
+                for (row, row_childs) in childs.chunks(columns).enumerate() {
+                    if row > 0 {
+                        y += self.spacing;
+                    }
+                    let h = row_childs
+                        .iter()
+                        .map(|(item, _)| {
+                            item.measure(cell_size).y + item.margin.top + item.margin.bottom
+                        })
+                        .fold(0.0, f64::max);
+                    for (column, (item, rect)) in row_childs.iter().enumerate() {
+                        #[allow(clippy::cast_precision_loss)]
+                        let x = (w + self.spacing).mul_add(column as f64, area.x);
+                        let cell = Rectf { x, y, w, h };
+                        rects.push(item.place(rect, &cell, cell_size, item.cell_align()));
+                    }
+                    y += h;
+                }
+                rects
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Align, Item, Kind, Layout, Length};
+    use builder::config::Config;
+    use renderer::slice::Insets;
+    use std::str::FromStr;
+    use utils::{rect::Rectf, vec2::Vec2f};
+
+    /// Check that rectangles are equal to expected ones with some precision.
+    fn assert_rects(rects: &[Rectf], expected: &[[f64; 4]]) {
+        assert_eq!(rects.len(), expected.len());
+        for (r, e) in rects.iter().zip(expected) {
+            let actual = [r.x, r.y, r.w, r.h];
+            assert!(actual.iter().zip(e).all(|(a, b)| (a - b).abs() < 1e-9), "{r:?} != {e:?}");
+        }
+    }
+
+    #[test]
+    fn length() {
+        assert_eq!(Length::from_str("20").unwrap(), Length::Px(20.0));
+        assert_eq!(Length::from_str("50%").unwrap(), Length::Percent(50.0));
+        assert!(Length::from_str("abc").is_err());
+        assert!((Length::Percent(25.0).resolve(200.0) - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn item_from_config() {
+        let mut cfg = Config::from_json(
+            r#"{ "size": ["50%", 30], "anchor": ["left", "right", "bottom"], "max_size": [80, 80] }"#,
+        )
+        .unwrap();
+        let item = Item::new(&mut cfg, &Rectf::from([0.0; 4])).unwrap();
+        assert_eq!(item.size, [Length::Percent(50.0), Length::Px(30.0)]);
+        assert_eq!(item.align, [Align::Stretch, Align::End]);
+        let size = item.measure(Vec2f::new(400.0, 100.0));
+        assert!((size.x - 80.0).abs() < 1e-9 && (size.y - 30.0).abs() < 1e-9);
+
+        let mut cfg = Config::from_json(r#"{ "anchor": ["middle"] }"#).unwrap();
+        assert!(Item::new(&mut cfg, &Rectf::from([0.0; 4])).is_err());
+    }
+
+    #[test]
+    fn anchor() {
+        let layout = Layout { padding: Insets::from([10.0; 4]), ..Layout::default() };
+        let fixed = Item::fixed(&Rectf::from([5.0, 5.0, 20.0, 20.0]));
+        let mut docked = fixed.clone();
+        docked.align = [Align::End, Align::Center];
+        docked.margin = Insets::from([0.0, 0.0, 5.0, 0.0]);
+        let mut stretched = fixed.clone();
+        stretched.align = [Align::Stretch, Align::Start];
+        let rect = Rectf::from([5.0, 5.0, 20.0, 20.0]);
+        let rects = layout.arrange(
+            Vec2f::new(120.0, 100.0),
+            &[(&fixed, rect), (&docked, rect), (&stretched, rect)],
+        );
+        assert_rects(
+            &rects,
+            &[[5.0, 5.0, 20.0, 20.0], [85.0, 40.0, 20.0, 20.0], [10.0, 10.0, 100.0, 20.0]],
+        );
+    }
+
+    #[test]
+    fn stacks() {
+        let rect = Rectf::from([0.0, 0.0, 20.0, 10.0]);
+        let item = Item::fixed(&rect);
+        let mut stretched = item.clone();
+        stretched.align = [Align::Stretch, Align::Stretch];
+        let childs = [(&item, rect), (&stretched, rect)];
+
+        let layout = Layout { kind: Kind::VStack, spacing: 5.0, ..Layout::default() };
+        let rects = layout.arrange(Vec2f::new(100.0, 100.0), &childs);
+        assert_rects(&rects, &[[0.0, 0.0, 20.0, 10.0], [0.0, 15.0, 100.0, 10.0]]);
+
+        let layout = Layout { kind: Kind::HStack, spacing: 5.0, ..Layout::default() };
+        let rects = layout.arrange(Vec2f::new(100.0, 100.0), &childs);
+        assert_rects(&rects, &[[0.0, 0.0, 20.0, 10.0], [25.0, 0.0, 20.0, 100.0]]);
+    }
+
+    #[test]
+    fn grid() {
+        let rect = Rectf::from([0.0, 0.0, 10.0, 10.0]);
+        let item = Item::fixed(&rect);
+        let mut tall = item.clone();
+        tall.size[1] = Length::Px(30.0);
+        let mut stretched = item.clone();
+        stretched.align = [Align::Stretch, Align::Stretch];
+        let layout = Layout { kind: Kind::Grid(2), spacing: 10.0, ..Layout::default() };
+        let rects = layout
+            .arrange(Vec2f::new(110.0, 100.0), &[(&item, rect), (&tall, rect), (&stretched, rect)]);
+        assert_rects(
+            &rects,
+            &[[0.0, 0.0, 10.0, 10.0], [60.0, 0.0, 10.0, 30.0], [0.0, 40.0, 50.0, 10.0]],
+        );
+    }
+}
@@ -5,13 +5,15 @@ mod button;
 mod flag;
 mod graph;
 mod label;
+pub mod layout;
 mod panel;
 mod slider;
+pub mod style;
 mod textbox;
 
 use std::ops::{Deref, DerefMut};
 
-pub use base::Base;
+pub use base::{Base, Container};
 pub use button::Button;
 pub use flag::Flag;
 pub use graph::Graph;
@@ -20,17 +22,17 @@ pub use panel::Panel;
 pub use slider::Slider;
 pub use textbox::Textbox;
 
-use crate::manager::widget::WRef;
+use crate::manager::widget::Node;
 use builder::{BuildFromCfg, Builder as BaseBuilder};
 
 /// Widget builder.
-pub struct Builder(BaseBuilder<WRef>);
+pub struct Builder(BaseBuilder<Node>);
 
 impl Default for Builder {
     /// Default builder, that can build all default widgets.
     fn default() -> Self {
-        let mut builder = BaseBuilder::<WRef>::new();
-        builder.reg_builder("base", Base::build);
+        let mut builder = BaseBuilder::<Node>::new();
+        builder.reg_builder("base", Container::build);
         builder.reg_builder("button", Button::build);
         builder.reg_builder("flag", Flag::build);
         builder.reg_builder("label", Label::build);
@@ -43,7 +45,7 @@ impl Default for Builder {
 }
 
 impl Deref for Builder {
-    type Target = BaseBuilder<WRef>;
+    type Target = BaseBuilder<Node>;
     fn deref(&self) -> &Self::Target {
         &self.0
     }
@@ -3,163 +3,93 @@
 //! Simple widget. It used for groups other widgets.
 
 use error_stack::{Result, ResultExt};
-use std::{cell::RefCell, rc::Weak};
 
-use super::Base;
+use super::{
+    style::{Style, StyleState},
+    Base,
+};
 use crate::manager::{
+    drag::{Payload, Preview, Stage},
     widget::{
-        event::{Event, MouseButton},
-        Error, WRef, Widget,
+        event::{Event, MouseButton, Phase},
+        Error, Node, Widget, WidgetId,
     },
     State,
 };
 use builder::{self, config::Config, BuildFromCfg};
-use renderer::{Drawable, Renderer};
-use resources::TextureId;
-use utils::{rect::Rectf, vec2::Vec2f};
+use renderer::{slice::Insets, Renderer};
+use utils::vec2::Vec2f;
 
-/// Panel widget.
+/// Panel widget. It is moved by dragging, payload is the panel identifier.
 pub struct Panel {
-    /// Base widget.
-    base: Base,
-    /// Background texture.
-    texture: TextureId,
-    /// Background texture rectangle.
-    texture_rect: Rectf,
-    /// Offset, used when widget cached.
-    offset: Vec2f,
+    /// Global position of the panel, where drag is started.
+    origin: Vec2f,
 }
 
 impl Widget for Panel {
+    fn type_name(&self) -> &'static str {
+        "panel"
+    }
+
     fn handle_event(
         &mut self,
-        self_rc: WRef,
+        base: &mut Base,
+        self_id: WidgetId,
         event: Event,
         state: &mut State,
     ) -> Result<(), Error> {
+        if state.get_phase() != Phase::Target {
+            return Ok(());
+        }
         match event {
-            Event::MousePress(MouseButton::Left) => {
-                if state.get_caught().is_none() {
-                    self.get_parent()
-                        .map(|p| p.upgrade().map(|p| p.borrow_mut().erase_widget(&self_rc)));
-                    self.offset = self.get_global_position() - state.mouse;
-                    self.set_position(state.mouse + self.offset);
-                    state.catch_self(self, self_rc)?;
-                }
+            Event::MousePress(MouseButton::Left) if state.get_drag().is_none() => {
+                self.origin = base.get_global_position();
+                state.start_drag(self_id, Payload::new(self_id)).set_preview(Preview::None);
             }
-            Event::MouseRelease(MouseButton::Left) => {
-                if let Some(caught) = state.get_caught() {
-                    if caught == self_rc {
-                        self.get_parent().map(|p| {
-                            p.upgrade().map(|p| {
-                                p.clone().borrow_mut().add_widget(p.into(), self, self_rc.clone());
-                            })
-                        });
-                        state.uncatch(self, self_rc)?;
-                        self.set_global_position(self.get_position());
-                    }
+            Event::DragMove => {
+                if let Some(drag) = state.get_drag() {
+                    base.set_global_position(self.origin + drag.delta());
                 }
             }
-            Event::MouseMove => {
-                if state.is_caught(self_rc) {
-                    self.set_position(state.mouse + self.offset);
-                }
+            Event::DragEnd if state.get_drag().is_some_and(|d| d.stage() == Stage::Cancelled) => {
+                base.set_global_position(self.origin);
             }
             _ => {}
         }
         Ok(())
     }
 
-    fn get_hovered(&self, pos: Vec2f) -> Option<WRef> {
-        self.base.get_hovered(pos)
-    }
-
-    fn check_bounds(&self, pos: Vec2f) -> bool {
-        self.base.check_bounds(pos)
-    }
-
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef) {
-        self.base.add_widget(self_ref, widget, widget_ref);
-    }
-
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>) {
-        self.base.set_parent(parent);
-    }
-
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>> {
-        self.base.get_parent()
-    }
-
-    fn detach(&mut self, self_rc: &WRef) {
-        self.base.detach(self_rc);
-    }
-
-    fn erase_widget(&mut self, widget: &WRef) {
-        self.base.erase_widget(widget);
-    }
-
-    fn set_position(&mut self, pos: Vec2f) {
-        self.base.set_position(pos);
-    }
-
-    fn get_position(&self) -> Vec2f {
-        self.base.get_position()
-    }
-
-    fn set_global_position(&mut self, pos: Vec2f) {
-        self.base.set_global_position(pos);
-    }
-
-    fn get_global_position(&self) -> Vec2f {
-        self.base.get_global_position()
-    }
-
-    fn get_rect(&self) -> &Rectf {
-        self.base.get_rect()
-    }
-
-    fn find(&self, id: &str) -> Option<WRef> {
-        self.base.find(id)
-    }
-
-    fn set_visible_flag(&mut self, is_visible: bool) {
-        self.base.set_visible_flag(is_visible);
-    }
-
-    fn get_id(&self) -> String {
-        self.base.get_id()
-    }
-
-    fn is_visible(&self) -> bool {
-        self.base.is_visible()
-    }
-}
-
-impl Drawable for Panel {
-    fn draw(&self, renderer: &mut dyn Renderer) {
-        renderer.draw_img(self.base.get_rect(), self.texture, &self.texture_rect);
-        self.base.draw(renderer);
+    fn draw(&self, base: &Base, renderer: &mut dyn Renderer) {
+        let style = base.style();
+        if let (Some(texture), Some(rect)) = (style.texture, style.texture_rect) {
+            let slice = style.slice.unwrap_or_default();
+            renderer.draw_img_sliced(base.get_rect(), texture, &rect, &slice);
+        }
     }
 }
 
-impl BuildFromCfg<WRef> for Panel {
-    fn build(mut cfg: Config, res: &mut dyn resources::Manager) -> Result<WRef, builder::Error> {
-        let bg_name = cfg
-            .take::<String>("background")
-            .change_context(builder::Error::msg("Failed to init button background texture"))?;
-        let texture = res.get_texture(&bg_name).change_context(builder::Error::msg(format!(
-            "Failed to init button, texture: \"{bg_name}\" not found"
-        )))?;
-
+impl BuildFromCfg<Node> for Panel {
+    fn build(mut cfg: Config, res: &mut dyn resources::Manager) -> Result<Node, builder::Error> {
+        let texture = cfg
+            .take_opt::<String>("background")
+            .change_context(builder::Error::msg("Failed to init panel background texture"))?
+            .map(|name| {
+                res.get_texture(&name).change_context(builder::Error::msg(format!(
+                    "Failed to init panel, texture: \"{name}\" not found"
+                )))
+            })
+            .transpose()?;
         let texture_rect = cfg
-            .take("background_rect")
-            .change_context(builder::Error::msg("Failed to init button"))?;
-
-        Ok(WRef::new(Self {
-            base: Base::new(cfg)?,
-            texture,
-            texture_rect,
-            offset: Vec2f::new(0.0, 0.0),
-        }))
+            .take_opt("background_rect")
+            .change_context(builder::Error::msg("Failed to init panel background rectangle"))?;
+        let slice = cfg
+            .take_opt::<[f64; 4]>("slice")
+            .change_context(builder::Error::msg("Failed to init panel texture slice insets"))?
+            .map(Insets::from);
+
+        let mut base = Base::new(cfg)?;
+        let inline = base.inline_style_mut(StyleState::Normal);
+        *inline = inline.merge(&Style { texture, texture_rect, slice, ..Style::default() });
+        Ok(Node::new(base, Self { origin: Vec2f::new(0.0, 0.0) }))
     }
 }
@@ -1,31 +1,33 @@
 //! Slider widget.
 
-use super::Base;
+use super::{
+    style::{Style, StyleState},
+    Base,
+};
 use crate::manager::{
     widget::{
-        event::{Event, MouseButton},
-        Error, WRef, Widget,
+        event::{Event, MouseButton, Phase},
+        signal::{Signal, Value},
+        Error, Node, Widget, WidgetId,
     },
     State,
 };
 use builder::{self, config::Config, BuildFromCfg};
 use core::f64;
 use error_stack::{Result, ResultExt};
-use renderer::{Drawable, Renderer};
-use resources::{Manager, TextureId};
-use std::{cell::RefCell, rc::Weak};
-use utils::{rect::Rectf, vec2::Vec2f};
+use renderer::Renderer;
+use resources::Manager;
+use scene::event::KeyCode;
+use utils::rect::Rectf;
+
+/// Number of keyboard steps of slider without value step.
+const KEY_STEP_NUMBER: f64 = 20.0;
 
-/// Slider.
+/// Slider. Emits `value_changed` signal, when value is changed by user.
+/// Slider is drawn by style: `texture_rect` of background and `cursor_texture_rect` of cursor.
 pub struct Slider {
-    /// Base widget.
-    base: Base,
-    /// Slider texture.
-    texture: TextureId,
-    /// Slider background texture rectangle.
-    texture_background_rect: Rectf,
-    /// Slider cursor texture rectangle.
-    texture_cursor_rect: Rectf,
+    /// Is widget hovered.
+    hovered: bool,
     /// Slider cursor rectangle.
     cursor_rect: Rectf,
     /// Slider minimum value.
@@ -39,22 +41,24 @@ pub struct Slider {
 }
 
 impl Slider {
-    /// Create new slider.
+    /// Create new slider and its common properties.
     ///
     /// # Errors
     /// Return error if config is not valid.
-    pub fn new(mut cfg: Config, res: &mut dyn Manager) -> Result<Self, builder::Error> {
-        let texture_name = cfg
-            .take::<String>("texture")
-            .change_context(builder::Error::msg("Failed to init slide texture"))?;
-        let texture = res
-            .get_texture(&texture_name)
-            .change_context(builder::Error::msg("Failed to find slider texture"))?;
-        let texture_background_rect = cfg
-            .take("texture_background_rect")
+    pub fn new(mut cfg: Config, res: &mut dyn Manager) -> Result<(Base, Self), builder::Error> {
+        let texture = cfg
+            .take_opt::<String>("texture")
+            .change_context(builder::Error::msg("Failed to init slide texture"))?
+            .map(|name| {
+                res.get_texture(&name)
+                    .change_context(builder::Error::msg("Failed to find slider texture"))
+            })
+            .transpose()?;
+        let texture_rect = cfg
+            .take_opt("texture_background_rect")
             .change_context(builder::Error::msg("Failed to init slide texture_background_rect"))?;
-        let texture_cursor_rect = cfg
-            .take("texture_cursor_rect")
+        let cursor_texture_rect = cfg
+            .take_opt("texture_cursor_rect")
             .change_context(builder::Error::msg("Failed to init slide texture_cursor_rect"))?;
         let cursor_rect: Rectf = cfg
             .take("cursor_rect")
@@ -71,25 +75,18 @@ impl Slider {
         let step_number = cfg
             .take::<f64>("step_number")
             .change_context(builder::Error::msg("Failed to init slide value_step"))?;
-        let base = Base::new(cfg)
+        let mut base = Base::new(cfg)
             .change_context(builder::Error::msg("Failed to init base widget for slider"))?;
+        let inline = base.inline_style_mut(StyleState::Normal);
+        *inline =
+            inline.merge(&Style { texture, texture_rect, cursor_texture_rect, ..Style::default() });
         let max_x = base.get_rect().w - cursor_rect.w;
         let value_step = if step_number == 0.0 { 0.0 } else { max_x / step_number };
 
-        let mut s = Self {
-            base,
-            texture,
-            texture_background_rect,
-            texture_cursor_rect,
-            cursor_rect,
-            value_min,
-            value_max,
-            value_step,
-            max_x,
-        };
-        s.cursor_rect.y = (s.base.get_rect().h - cursor_rect.h) / 2.0;
+        let mut s = Self { hovered: false, cursor_rect, value_min, value_max, value_step, max_x };
+        s.cursor_rect.y = (base.get_rect().h - cursor_rect.h) / 2.0;
         s.set_value(value);
-        Ok(s)
+        Ok((base, s))
     }
 
     /// Convert slider value to cursor `x` coordinate.
@@ -121,130 +118,122 @@ impl Slider {
 }
 
 impl Widget for Slider {
+    fn type_name(&self) -> &'static str {
+        "slider"
+    }
+
     fn handle_event(
         &mut self,
-        self_rc: WRef,
+        base: &mut Base,
+        self_id: WidgetId,
         event: Event,
         state: &mut State,
     ) -> Result<(), Error> {
+        if state.get_phase() != Phase::Target {
+            return Ok(());
+        }
+        let value = self.get_value();
         match event {
-            Event::MousePress(mouse_button) => {
-                if matches!(mouse_button, MouseButton::Left) && state.get_caught().is_none() {
-                    self.set_position(self.get_global_position());
-                    self.get_parent()
-                        .map(|p| p.upgrade().map(|p| p.borrow_mut().erase_widget(&self_rc)));
-                    state.catch_self(self, self_rc)?;
-                    self.update_cursor_pos(
-                        state.mouse.x
-                            - self.base.get_global_position().x
-                            - self.cursor_rect.w / 2.0,
-                    );
-                }
+            Event::MousePress(MouseButton::Left) if state.get_caught().is_none() => {
+                state.catch_self(self, base, self_id)?;
+                self.update_cursor_pos(
+                    state.mouse.x - base.get_global_position().x - self.cursor_rect.w / 2.0,
+                );
             }
-            Event::MouseRelease(mouse_button) => {
-                if matches!(mouse_button, MouseButton::Left) && state.is_caught(self_rc.clone()) {
-                    state.uncatch(self, self_rc.clone())?;
-                    self.get_parent().map(|p| {
-                        p.upgrade().map(|p| {
-                            p.clone().borrow_mut().add_widget(p.into(), self, self_rc);
-                        })
-                    });
-                    self.set_global_position(self.get_position());
-                }
+            Event::MouseRelease(MouseButton::Left) if state.is_caught(self_id) => {
+                state.uncatch(self, base, self_id)?;
+                self.hovered = base.check_global_bounds(state.mouse);
+            }
+            Event::MouseEnter => self.hovered = true,
+            Event::MouseLeave => self.hovered = state.is_caught(self_id),
+            Event::MouseMove if state.is_caught(self_id) => {
+                self.update_cursor_pos(
+                    state.mouse.x - base.get_global_position().x - self.cursor_rect.w / 2.0,
+                );
             }
-            Event::MouseMove => {
-                if state.is_caught(self_rc) {
-                    self.update_cursor_pos(
-                        state.mouse.x
-                            - self.base.get_global_position().x
-                            - self.cursor_rect.w / 2.0,
-                    );
+            Event::KeyPress(key) if state.is_focused(self_id) => {
+                let step = if self.value_step == 0.0 {
+                    self.max_x / KEY_STEP_NUMBER
+                } else {
+                    self.value_step
+                };
+                match key {
+                    KeyCode::ArrowLeft => self.update_cursor_pos(self.cursor_rect.x - step),
+                    KeyCode::ArrowRight => self.update_cursor_pos(self.cursor_rect.x + step),
+                    KeyCode::Home => self.update_cursor_pos(0.0),
+                    KeyCode::End => self.update_cursor_pos(self.max_x),
+                    _ => {}
                 }
             }
             _ => {}
         }
+        let new_value = self.get_value();
+        if new_value != value {
+            state.emit(self_id, Signal::ValueChanged(Value::Number(new_value)));
+        }
+        base.set_style_state(if state.is_caught(self_id) {
+            StyleState::Pressed
+        } else if self.hovered {
+            StyleState::Hovered
+        } else if state.is_focused(self_id) {
+            StyleState::Focused
+        } else {
+            StyleState::Normal
+        });
         Ok(())
     }
 
-    fn get_hovered(&self, pos: Vec2f) -> Option<WRef> {
-        self.base.get_hovered(pos)
-    }
-
-    fn check_bounds(&self, pos: Vec2f) -> bool {
-        self.base.check_bounds(pos)
-    }
-
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef) {
-        self.base.add_widget(self_ref, widget, widget_ref);
-    }
-
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>) {
-        self.base.set_parent(parent);
-    }
-
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>> {
-        self.base.get_parent()
-    }
-
-    fn detach(&mut self, self_rc: &WRef) {
-        self.base.detach(self_rc);
-    }
-
-    fn erase_widget(&mut self, widget: &WRef) {
-        self.base.erase_widget(widget);
-    }
-
-    fn set_position(&mut self, pos: Vec2f) {
-        self.base.set_position(pos);
-    }
-
-    fn get_position(&self) -> Vec2f {
-        self.base.get_position()
-    }
-
-    fn set_global_position(&mut self, pos: Vec2f) {
-        self.base.set_global_position(pos);
-    }
-
-    fn get_global_position(&self) -> Vec2f {
-        self.base.get_global_position()
-    }
-
-    fn get_rect(&self) -> &Rectf {
-        self.base.get_rect()
-    }
-
-    fn find(&self, id: &str) -> Option<WRef> {
-        self.base.find(id)
+    fn draw(&self, base: &Base, renderer: &mut dyn Renderer) {
+        let style = base.style();
+        let Some(texture) = style.texture else {
+            return;
+        };
+        let rect = base.get_rect();
+        if let Some(ref texture_rect) = style.texture_rect {
+            renderer.draw_img(rect, texture, texture_rect);
+        }
+        if let Some(ref cursor_texture_rect) = style.cursor_texture_rect {
+            renderer.push_state();
+            renderer.translate(rect.x, rect.y);
+            renderer.draw_img(&self.cursor_rect, texture, cursor_texture_rect);
+            renderer.pop_state();
+        }
     }
 
-    fn set_visible_flag(&mut self, is_visible: bool) {
-        self.base.set_visible_flag(is_visible);
+    fn arrange(&mut self, rect: &Rectf) {
+        let max_x = rect.w - self.cursor_rect.w;
+        let cursor_y = (rect.h - self.cursor_rect.h) / 2.0;
+        // Too narrow slider keeps cursor range, so value is not lost.
+        if max_x > 0.0 && (max_x != self.max_x || cursor_y != self.cursor_rect.y) {
+            // Cursor range depends on slider size, so value is kept and cursor is moved.
+            let value = self.get_value();
+            if self.max_x != 0.0 {
+                self.value_step *= max_x / self.max_x;
+            }
+            self.max_x = max_x;
+            self.cursor_rect.y = cursor_y;
+            self.set_value(value);
+        }
     }
 
-    fn get_id(&self) -> String {
-        self.base.get_id()
+    fn is_focusable(&self) -> bool {
+        true
     }
 
-    fn is_visible(&self) -> bool {
-        self.base.is_visible()
+    fn uses_key(&self, key: KeyCode) -> bool {
+        matches!(key, KeyCode::ArrowLeft | KeyCode::ArrowRight)
     }
-}
 
-impl Drawable for Slider {
-    fn draw(&self, renderer: &mut dyn Renderer) {
-        let rect = self.base.get_rect();
-        renderer.draw_img(rect, self.texture, &self.texture_background_rect);
-        renderer.push_state();
-        renderer.translate(rect.x, rect.y);
-        renderer.draw_img(&self.cursor_rect, self.texture, &self.texture_cursor_rect);
-        renderer.pop_state();
-        self.base.draw(renderer);
+    fn set_bound_value(&mut self, value: &Value) {
+        if let Some(value) = value.as_number() {
+            self.set_value(value);
+        }
     }
 }
 
-impl BuildFromCfg<WRef> for Slider {
-    fn build(cfg: Config, res: &mut dyn Manager) -> Result<WRef, builder::Error> {
-        Ok(WRef::new(Self::new(cfg, res)?))
+impl BuildFromCfg<Node> for Slider {
+    fn build(cfg: Config, res: &mut dyn Manager) -> Result<Node, builder::Error> {
+        let (base, slider) = Self::new(cfg, res)?;
+        Ok(Node::new(base, slider))
     }
 }
@@ -0,0 +1,308 @@
+//! Widget styles and themes.
+//!
+//! Theme contains named styles (classes) per widget type. Widget class is set by `"style"`
+//! config field and it is inherited from parent widget. Style of `"default"` class is applied to
+//! all widgets of the type, styles of other classes override it.
+//!
+//! Style contains properties of the normal widget state and overrides for other states:
+//! ```json
+//! {
+//!     "styles": {
+//!         "button": {
+//!             "default": {
+//!                 "texture": "button_tiles",
+//!                 "texture_rect": [ 0, 0, 160, 16 ],
+//!                 "hovered": { "texture_rect": [ 0, 16, 160, 16 ] },
+//!                 "pressed": { "texture_rect": [ 0, 32, 160, 16 ] }
+//!             }
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Properties specified in widget config (inline style) override theme properties of the same
+//! state.
+
+use error_stack::{Result, ResultExt};
+use std::{collections::HashMap, str::FromStr};
+
+use builder::{self, config::Config};
+use renderer::slice::Insets;
+use resources::{FontId, FontStyle, Manager, TextureId};
+use utils::{color::Color, rect::Rectf};
+
+/// Class, that is applied to all widgets of the type.
+pub const DEFAULT_CLASS: &str = "default";
+
+/// Widget state, that selects style.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StyleState {
+    /// Normal state.
+    #[default]
+    Normal,
+    /// Widget is under cursor.
+    Hovered,
+    /// Widget is pressed.
+    Pressed,
+    /// Widget is disabled.
+    Disabled,
+    /// Widget is focused.
+    Focused,
+}
+
+impl StyleState {
+    /// States, that override normal state.
+    const OVERRIDES: [(Self, &'static str); 4] = [
+        (Self::Hovered, "hovered"),
+        (Self::Pressed, "pressed"),
+        (Self::Disabled, "disabled"),
+        (Self::Focused, "focused"),
+    ];
+}
+
+/// Style properties. Unset properties are taken from less specific style.
+#[derive(Clone, Copy, Default)]
+pub struct Style {
+    /// Background color.
+    pub background_color: Option<Color>,
+    /// Border color.
+    pub border_color: Option<Color>,
+    /// Border width.
+    pub border_width: Option<f64>,
+    /// Radius of background and border corners.
+    pub corner_radius: Option<f64>,
+    /// Text color.
+    pub color: Option<Color>,
+    /// Text font.
+    pub font: Option<FontId>,
+    /// Text font size.
+    pub font_size: Option<f64>,
+    /// Background texture.
+    pub texture: Option<TextureId>,
+    /// Background texture rectangle.
+    pub texture_rect: Option<Rectf>,
+    /// Background texture rectangle of widget in on state, e.g. checked flag.
+    pub texture_rect_on: Option<Rectf>,
+    /// Cursor texture rectangle, e.g. slider cursor.
+    pub cursor_texture_rect: Option<Rectf>,
+    /// Background texture slice insets.
+    pub slice: Option<Insets>,
+}
+
+impl Style {
+    /// Create style from config.
+    /// Config fields: `background_color`, `border_color`, `border_width`, `corner_radius`,
+    /// `color`, `font`, `font_style`, `font_size`, `texture`, `texture_rect`, `texture_rect_on`,
+    /// `cursor_texture_rect` and `slice`.
+    ///
+    /// # Errors
+    /// Return error if config is not valid or the required resource is not found.
+    pub fn new(cfg: &mut Config, res: &dyn Manager) -> Result<Self, builder::Error> {
+        let err = |field: &str| builder::Error::msg(format!("Failed to init style {field}"));
+        let font = cfg.take_opt::<String>("font").change_context_lazy(|| err("font"))?;
+        let font_style = cfg
+            .take_opt::<String>("font_style")
+            .change_context_lazy(|| err("font style"))?
+            .map(|s| FontStyle::from_str(&s))
+            .transpose()
+            .change_context_lazy(|| err("font style"))?
+            .unwrap_or_default();
+        let texture = cfg.take_opt::<String>("texture").change_context_lazy(|| err("texture"))?;
+        Ok(Self {
+            background_color: cfg
+                .take_opt("background_color")
+                .change_context_lazy(|| err("background color"))?,
+            border_color: cfg
+                .take_opt("border_color")
+                .change_context_lazy(|| err("border color"))?,
+            border_width: cfg
+                .take_opt("border_width")
+                .change_context_lazy(|| err("border width"))?,
+            corner_radius: cfg
+                .take_opt("corner_radius")
+                .change_context_lazy(|| err("corner radius"))?,
+            color: cfg.take_opt("color").change_context_lazy(|| err("color"))?,
+            font: font
+                .map(|name| res.get_font_style(&name, font_style))
+                .transpose()
+                .change_context_lazy(|| err("font"))?,
+            font_size: cfg.take_opt("font_size").change_context_lazy(|| err("font size"))?,
+            texture: texture
+                .map(|name| res.get_texture(&name))
+                .transpose()
+                .change_context_lazy(|| err("texture"))?,
+            texture_rect: cfg
+                .take_opt("texture_rect")
+                .change_context_lazy(|| err("texture rectangle"))?,
+            texture_rect_on: cfg
+                .take_opt("texture_rect_on")
+                .change_context_lazy(|| err("on state texture rectangle"))?,
+            cursor_texture_rect: cfg
+                .take_opt("cursor_texture_rect")
+                .change_context_lazy(|| err("cursor texture rectangle"))?,
+            slice: cfg
+                .take_opt::<[f64; 4]>("slice")
+                .change_context_lazy(|| err("texture slice insets"))?
+                .map(Insets::from),
+        })
+    }
+
+    /// Override properties by properties, that are set in other style.
+    #[must_use]
+    pub fn merge(self, other: &Self) -> Self {
+        Self {
+            background_color: other.background_color.or(self.background_color),
+            border_color: other.border_color.or(self.border_color),
+            border_width: other.border_width.or(self.border_width),
+            corner_radius: other.corner_radius.or(self.corner_radius),
+            color: other.color.or(self.color),
+            font: other.font.or(self.font),
+            font_size: other.font_size.or(self.font_size),
+            texture: other.texture.or(self.texture),
+            texture_rect: other.texture_rect.or(self.texture_rect),
+            texture_rect_on: other.texture_rect_on.or(self.texture_rect_on),
+            cursor_texture_rect: other.cursor_texture_rect.or(self.cursor_texture_rect),
+            slice: other.slice.or(self.slice),
+        }
+    }
+}
+
+/// Styles of all widget states.
+#[derive(Clone, Default)]
+pub struct StyleSet {
+    /// Styles by state.
+    states: HashMap<StyleState, Style>,
+}
+
+impl StyleSet {
+    /// Create style set from config. Normal state properties are specified in the config root,
+    /// other states are specified by `"hovered"`, `"pressed"`, `"disabled"` and `"focused"`
+    /// fields.
+    ///
+    /// # Errors
+    /// Return error if config is not valid or the required resource is not found.
+    pub fn new(mut cfg: Config, res: &dyn Manager) -> Result<Self, builder::Error> {
+        let mut set = Self::default();
+        for (state, name) in StyleState::OVERRIDES {
+            if let Some(mut cfg) = cfg
+                .take_opt::<Config>(name)
+                .change_context(builder::Error::msg(format!("Failed to init {name} style")))?
+            {
+                *set.get_mut(state) = Style::new(&mut cfg, res)?;
+            }
+        }
+        *set.get_mut(StyleState::Normal) = Style::new(&mut cfg, res)?;
+        Ok(set)
+    }
+
+    /// Get style of the state. Properties of the normal state are not included.
+    #[must_use]
+    pub fn get(&self, state: StyleState) -> Style {
+        self.states.get(&state).copied().unwrap_or_default()
+    }
+
+    /// Get mutable style of the state.
+    pub fn get_mut(&mut self, state: StyleState) -> &mut Style {
+        self.states.entry(state).or_default()
+    }
+
+    /// Override styles by styles of other set.
+    #[must_use]
+    pub fn merge(mut self, other: &Self) -> Self {
+        for (state, style) in &other.states {
+            let merged = self.get(*state).merge(style);
+            self.states.insert(*state, merged);
+        }
+        self
+    }
+}
+
+/// Theme: style sets by widget type and class.
+#[derive(Default)]
+pub struct Theme {
+    /// Style sets by widget type and class.
+    styles: HashMap<String, HashMap<String, StyleSet>>,
+}
+
+impl Theme {
+    /// Create theme from config. Styles are specified by `"styles"` field: map of widget types
+    /// to map of classes.
+    ///
+    /// # Errors
+    /// Return error if config is not valid or the required resource is not found.
+    pub fn new(mut cfg: Config, res: &dyn Manager) -> Result<Self, builder::Error> {
+        let cfg = cfg
+            .take::<HashMap<String, HashMap<String, Config>>>("styles")
+            .change_context(builder::Error::msg("Failed to init theme styles"))?;
+        let mut styles = HashMap::new();
+        for (kind, classes) in cfg {
+            let mut sets = HashMap::new();
+            for (class, cfg) in classes {
+                let set = StyleSet::new(cfg, res).change_context(builder::Error::msg(format!(
+                    "Failed to init theme style: type: {kind:?}, class: {class:?}"
+                )))?;
+                sets.insert(class, set);
+            }
+            styles.insert(kind, sets);
+        }
+        Ok(Self { styles })
+    }
+
+    /// Add style set of the widget type and class. Previous style set is replaced.
+    pub fn insert(&mut self, kind: &str, class: &str, set: StyleSet) {
+        self.styles.entry(kind.to_owned()).or_default().insert(class.to_owned(), set);
+    }
+
+    /// Get style set of the widget type and class. Default class style is overridden by the
+    /// class style.
+    #[must_use]
+    pub fn get(&self, kind: &str, class: &str) -> StyleSet {
+        let Some(classes) = self.styles.get(kind) else {
+            return StyleSet::default();
+        };
+        let set = classes.get(DEFAULT_CLASS).cloned().unwrap_or_default();
+        match classes.get(class) {
+            Some(style) if class != DEFAULT_CLASS => set.merge(style),
+            _ => set,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Style, StyleSet, StyleState, Theme, DEFAULT_CLASS};
+    use std::collections::HashMap;
+
+    /// Make style set with specified normal and hovered border widths.
+    fn set(normal: Option<f64>, hovered: Option<f64>) -> StyleSet {
+        let mut set = StyleSet::default();
+        set.get_mut(StyleState::Normal).border_width = normal;
+        set.get_mut(StyleState::Hovered).border_width = hovered;
+        set
+    }
+
+    #[test]
+    fn merge() {
+        let a = Style { border_width: Some(1.0), corner_radius: Some(2.0), ..Style::default() };
+        let b = Style { border_width: Some(3.0), ..Style::default() };
+        let m = a.merge(&b);
+        assert_eq!(m.border_width, Some(3.0));
+        assert_eq!(m.corner_radius, Some(2.0));
+    }
+
+    #[test]
+    fn theme_classes() {
+        let classes = HashMap::from([
+            (DEFAULT_CLASS.to_owned(), set(Some(1.0), Some(2.0))),
+            ("primary".to_owned(), set(None, Some(5.0))),
+        ]);
+        let theme = Theme { styles: HashMap::from([("button".to_owned(), classes)]) };
+
+        let primary = theme.get("button", "primary");
+        assert_eq!(primary.get(StyleState::Normal).border_width, Some(1.0));
+        assert_eq!(primary.get(StyleState::Hovered).border_width, Some(5.0));
+        let unknown = theme.get("button", "unknown");
+        assert_eq!(unknown.get(StyleState::Hovered).border_width, Some(2.0));
+        assert!(theme.get("label", DEFAULT_CLASS).get(StyleState::Normal).border_width.is_none());
+    }
+}
@@ -4,24 +4,24 @@
 
 use crate::manager::{
     widget::{
-        event::{Event, MouseButton},
-        Error, WRef, Widget,
+        event::{Event, MouseButton, Phase},
+        signal::{Signal, Value},
+        Error, Node, Widget, WidgetId,
     },
     State,
 };
 use builder::{self, config::Config, BuildFromCfg};
 use error_stack::{Result, ResultExt};
-use renderer::{Drawable, Renderer, TextTruncateMode};
+use renderer::{Renderer, TextTruncateMode};
 use scene::event::KeyCode;
-use std::{cell::RefCell, rc::Weak};
-use utils::{rect::Rectf, vec2::Vec2f};
+use utils::vec2::Vec2f;
 
-use super::Label;
+use super::{style::StyleState, Base, Label};
 
-/// Textbox widget.
+/// Textbox widget. Emits `value_changed` signal on text edit and `text_submitted` on `Enter` key.
 pub struct Textbox {
-    /// Base widget.
-    base: Label,
+    /// Label, that displays text.
+    label: Label,
     /// Last pressed key.
     last_key: Option<KeyCode>,
     /// Is textbox focused.
@@ -33,44 +33,97 @@ pub struct Textbox {
 }
 
 impl Textbox {
-    /// Create new textbox.
+    /// Set textbox text. If textbox is focused, cursor is moved to the end of the text.
+    pub fn set_text(&mut self, txt: &str) {
+        self.label.set_text(txt);
+        if self.is_focused {
+            self.cursor_offset = self.label.chars().len();
+            self.label.chars_mut().push(self.cursor);
+        }
+    }
+
+    /// Get textbox text without cursor.
+    #[must_use]
+    pub fn get_text(&self) -> String {
+        self.label
+            .chars()
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.is_focused || *i != self.cursor_offset)
+            .map(|(_, c)| *c)
+            .collect()
+    }
+
+    /// Create new textbox and its common properties.
     ///
     /// # Errors
     /// Return error if the config is incorrect or the required resource is not found.
-    pub fn new(mut cfg: Config, res: &mut dyn resources::Manager) -> Result<Self, builder::Error> {
+    pub fn new(
+        mut cfg: Config,
+        res: &mut dyn resources::Manager,
+    ) -> Result<(Base, Self), builder::Error> {
         let cursor = cfg
             .take_opt("cursor")
             .change_context(builder::Error::msg("Failed to init textbox cursor"))?
             .unwrap_or('\u{033F}');
-        let mut base = Label::new(cfg, res)?;
-        base.set_text_truncating(false);
-        Ok(Self { base, last_key: None, is_focused: false, cursor, cursor_offset: 0 })
+        let (base, mut label) = Label::new(cfg, res)?;
+        label.set_text_truncating(false);
+        Ok((base, Self { label, last_key: None, is_focused: false, cursor, cursor_offset: 0 }))
+    }
+
+    /// Move cursor to the char nearest to the global position.
+    fn set_cursor_at(&mut self, base: &Base, pos: Vec2f) {
+        let zoom = base.get_global_zoom() / base.get_zoom();
+        let origin = base.get_global_position();
+        let rect = base.get_rect();
+        let pos =
+            Vec2f::new(rect.x + (pos.x - origin.x) / zoom, rect.y + (pos.y - origin.y) / zoom);
+        let mut index = self.label.text_layout().hit_test(pos);
+        // Layout contains cursor char, that is moved.
+        if index > self.cursor_offset {
+            index -= 1;
+        }
+        let mut chars = self.label.chars_mut();
+        chars.remove(self.cursor_offset);
+        index = index.min(chars.len());
+        chars.insert(index, self.cursor);
+        self.cursor_offset = index;
     }
 }
 
 impl Widget for Textbox {
+    fn type_name(&self) -> &'static str {
+        "textbox"
+    }
+
     fn handle_event(
         &mut self,
-        self_rc: WRef,
+        base: &mut Base,
+        self_id: WidgetId,
         event: Event,
         state: &mut State,
     ) -> Result<(), Error> {
+        if state.get_phase() != Phase::Target {
+            return Ok(());
+        }
+        let text = self.get_text();
         match event {
             Event::MousePress(mouse_button) => {
                 if matches!(mouse_button, MouseButton::Left) {
-                    if state.is_hovered(&self_rc) {
-                        if !state.is_focused(self_rc.clone()) {
-                            state.focus_self(self, self_rc)?;
+                    if state.is_hovered(self_id) {
+                        if !state.is_focused(self_id) {
+                            state.focus_self(self, base, self_id)?;
                         }
-                    } else if state.is_focused(self_rc.clone()) {
-                        state.unfocus(self, self_rc)?;
+                        self.set_cursor_at(base, state.mouse);
+                    } else if state.is_focused(self_id) {
+                        state.unfocus(self, base, self_id)?;
                     }
                 }
             }
             Event::TextInput(txt) if !txt.is_empty() => {
-                if self.is_focused && state.get_focused() == Some(self_rc) {
+                if self.is_focused && state.is_focused(self_id) {
                     for c in txt.chars() {
-                        self.base.chars_mut().insert(self.cursor_offset, c);
+                        self.label.chars_mut().insert(self.cursor_offset, c);
                         self.cursor_offset += 1;
                     }
                 }
@@ -78,55 +131,54 @@ impl Widget for Textbox {
             Event::TextInput(_) => {
                 if self.is_focused {
                     if Some(KeyCode::Backspace) == self.last_key && self.cursor_offset > 0 {
-                        self.base.chars_mut().remove(self.cursor_offset - 1);
+                        self.label.chars_mut().remove(self.cursor_offset - 1);
                         self.cursor_offset -= 1;
                     } else if Some(KeyCode::Delete) == self.last_key
-                        && self.cursor_offset < self.base.chars().len() - 1
+                        && self.cursor_offset < self.label.chars().len() - 1
                     {
-                        self.base.chars_mut().remove(self.cursor_offset + 1);
+                        self.label.chars_mut().remove(self.cursor_offset + 1);
                     }
                 }
             }
             Event::Focused => {
                 self.is_focused = true;
-                self.cursor_offset = self.base.chars().len();
-                self.base.chars_mut().push(self.cursor);
-                self.base.set_draw_truncate_mode(TextTruncateMode::Front);
+                self.cursor_offset = self.label.chars().len();
+                self.label.chars_mut().push(self.cursor);
+                self.label.set_draw_truncate_mode(TextTruncateMode::Front);
+                base.set_style_state(StyleState::Focused);
             }
             Event::Unfocused => {
                 self.is_focused = false;
-                self.base.chars_mut().remove(self.cursor_offset);
+                self.label.chars_mut().remove(self.cursor_offset);
                 self.last_key = None;
-                self.base.set_draw_truncate_mode(TextTruncateMode::Back);
+                self.label.set_draw_truncate_mode(TextTruncateMode::Back);
+                base.set_style_state(StyleState::Normal);
             }
             Event::KeyPress(k) => {
                 if self.is_focused && self.last_key.is_none() {
                     match k {
-                        KeyCode::ArrowLeft => {
-                            if self.cursor_offset > 0 {
-                                self.base
-                                    .chars_mut()
-                                    .swap(self.cursor_offset, self.cursor_offset - 1);
-                                self.cursor_offset -= 1;
-                            }
+                        KeyCode::ArrowLeft if self.cursor_offset > 0 => {
+                            self.label.chars_mut().swap(self.cursor_offset, self.cursor_offset - 1);
+                            self.cursor_offset -= 1;
                         }
-                        KeyCode::ArrowRight => {
-                            if self.cursor_offset < self.base.chars().len() - 1 {
-                                self.base
-                                    .chars_mut()
-                                    .swap(self.cursor_offset, self.cursor_offset + 1);
-                                self.cursor_offset += 1;
-                            }
+                        KeyCode::ArrowRight
+                            if self.cursor_offset < self.label.chars().len() - 1 =>
+                        {
+                            self.label.chars_mut().swap(self.cursor_offset, self.cursor_offset + 1);
+                            self.cursor_offset += 1;
                         }
                         KeyCode::Home => {
-                            self.base.chars_mut().remove(self.cursor_offset);
+                            self.label.chars_mut().remove(self.cursor_offset);
                             self.cursor_offset = 0;
-                            self.base.chars_mut().insert(0, self.cursor);
+                            self.label.chars_mut().insert(0, self.cursor);
                         }
                         KeyCode::End => {
-                            self.base.chars_mut().remove(self.cursor_offset);
-                            self.cursor_offset = self.base.chars().len();
-                            self.base.chars_mut().push(self.cursor);
+                            self.label.chars_mut().remove(self.cursor_offset);
+                            self.cursor_offset = self.label.chars().len();
+                            self.label.chars_mut().push(self.cursor);
+                        }
+                        KeyCode::Enter => {
+                            state.emit(self_id, Signal::TextSubmitted(text.clone()));
                         }
                         _ => {}
                     }
@@ -143,84 +195,41 @@ impl Widget for Textbox {
             | Event::MouseEnter
             | Event::MouseLeave
             | Event::Caught
-            | Event::Released => {}
+            | Event::Released
+            | Event::DragStart
+            | Event::DragMove
+            | Event::DragEnd
+            | Event::DragEnter
+            | Event::DragLeave
+            | Event::Drop => {}
+        }
+        let new_text = self.get_text();
+        if new_text != text {
+            state.emit(self_id, Signal::ValueChanged(Value::Text(new_text)));
         }
         Ok(())
     }
 
-    fn get_hovered(&self, pos: Vec2f) -> Option<WRef> {
-        self.base.get_hovered(pos)
-    }
-
-    fn check_bounds(&self, pos: Vec2f) -> bool {
-        self.base.check_bounds(pos)
-    }
-
-    fn add_widget(&mut self, self_ref: WRef, widget: &mut dyn Widget, widget_ref: WRef) {
-        self.base.add_widget(self_ref, widget, widget_ref);
-    }
-
-    fn set_parent(&mut self, parent: Option<Weak<RefCell<dyn Widget>>>) {
-        self.base.set_parent(parent);
-    }
-
-    fn get_parent(&mut self) -> Option<Weak<RefCell<dyn Widget>>> {
-        self.base.get_parent()
+    fn draw(&self, base: &Base, renderer: &mut dyn Renderer) {
+        self.label.draw(base, renderer);
     }
 
-    fn detach(&mut self, self_rc: &WRef) {
-        self.base.detach(self_rc);
+    fn is_focusable(&self) -> bool {
+        true
     }
 
-    fn erase_widget(&mut self, widget: &WRef) {
-        self.base.erase_widget(widget);
+    fn uses_key(&self, key: KeyCode) -> bool {
+        matches!(key, KeyCode::ArrowLeft | KeyCode::ArrowRight)
     }
 
-    fn set_position(&mut self, pos: Vec2f) {
-        self.base.set_position(pos);
-    }
-
-    fn get_position(&self) -> Vec2f {
-        self.base.get_position()
-    }
-
-    fn set_global_position(&mut self, pos: Vec2f) {
-        self.base.set_global_position(pos);
-    }
-
-    fn get_global_position(&self) -> Vec2f {
-        self.base.get_global_position()
-    }
-
-    fn get_rect(&self) -> &Rectf {
-        self.base.get_rect()
-    }
-
-    fn find(&self, id: &str) -> Option<WRef> {
-        self.base.find(id)
-    }
-
-    fn get_id(&self) -> String {
-        self.base.get_id()
-    }
-
-    fn set_visible_flag(&mut self, is_visible: bool) {
-        self.base.set_visible_flag(is_visible);
-    }
-
-    fn is_visible(&self) -> bool {
-        self.base.is_visible()
-    }
-}
-
-impl Drawable for Textbox {
-    fn draw(&self, renderer: &mut dyn Renderer) {
-        self.base.draw(renderer);
+    fn set_bound_value(&mut self, value: &Value) {
+        self.set_text(&value.to_string());
     }
 }
 
-impl BuildFromCfg<WRef> for Textbox {
-    fn build(cfg: Config, res: &mut dyn resources::Manager) -> Result<WRef, builder::Error> {
-        Ok(WRef::new(Self::new(cfg, res)?))
+impl BuildFromCfg<Node> for Textbox {
+    fn build(cfg: Config, res: &mut dyn resources::Manager) -> Result<Node, builder::Error> {
+        let (base, textbox) = Self::new(cfg, res)?;
+        Ok(Node::new(base, textbox))
     }
 }
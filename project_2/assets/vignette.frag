@@ -0,0 +1,11 @@
+#version 150 core
+uniform sampler2D u_texture;
+uniform float strength;
+in vec2 v_uv;
+in vec4 v_color;
+out vec4 o_color;
+void main() {
+    vec4 color = texture(u_texture, v_uv) * v_color;
+    float vignette = strength * smoothstep(0.3, 0.75, distance(v_uv, vec2(0.5)));
+    o_color = vec4(color.rgb * (1.0 - vignette), color.a);
+}
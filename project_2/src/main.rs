@@ -13,6 +13,8 @@ const WINDOW_SCALE: u32 = 50;
 const WINDOW_H: u32 = 9 * WINDOW_SCALE;
 /// Window width.
 const WINDOW_W: u32 = 16 * WINDOW_SCALE;
+/// Texture atlas page size.
+const ATLAS_PAGE_SIZE: u32 = 1024;
 
 /// `Project_2` error.
 #[derive(Debug, thiserror::Error)]
@@ -35,7 +37,8 @@ fn main() {
 /// `Project_2` main loop.
 fn run() -> Result<(), Error> {
     let runtime = Runtime::new("Project 2", (WINDOW_W, WINDOW_H))
-        .change_context(Error::msg("Failed to init runtime"))?;
+        .change_context(Error::msg("Failed to init runtime"))?
+        .with_texture_atlas(ATLAS_PAGE_SIZE);
     let mut builder = scene::Builder::new();
     builder.reg_builder("main_menu", MainMenu::build);
     builder.reg_builder("level", Level::build);
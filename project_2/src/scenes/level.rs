@@ -1,13 +1,11 @@
 //! Game level scene.
 
-use std::{cell::RefCell, rc::Rc};
-
 use anim::{make_animator_cfg, Animator};
 use builder::{config::Config, BuildFromCfg};
 use error_stack::ResultExt;
 use gui::{
-    manager::Manager as GuiManager,
-    widget::{Builder as GuiBuilder, Button},
+    manager::{binding::Property, Manager as GuiManager},
+    widget::Builder as GuiBuilder,
 };
 use renderer::Drawable;
 use scene::{
@@ -45,7 +43,7 @@ pub struct Level {
     /// Level scene GUI.
     gui: GuiManager,
     /// Is need to return to main menu.
-    menu_scene: Rc<RefCell<bool>>,
+    menu_scene: Property<bool>,
     /// Main menu config.
     cfg: Config,
     /// Player animation.
@@ -87,7 +85,7 @@ impl Scene for Level {
         }
         self.gui.handle_event(e).change_context(scene::Error::msg("Gui failed"))?;
 
-        if *self.menu_scene.borrow() {
+        if self.menu_scene.get() {
             state
                 .load_next_scene(
                     self.cfg
@@ -115,14 +113,12 @@ impl BuildFromCfg<Box<dyn Scene>> for Level {
         let gui_cfg = cfg
             .take::<Config>("gui")
             .change_context(builder::Error::msg("Failed to build scene GUI"))?;
-        let gui = GuiManager::new(&GuiBuilder::default(), res, gui_cfg)
+        let mut gui = GuiManager::new(&GuiBuilder::default(), res, gui_cfg)
             .change_context(builder::Error::msg("Failed to init GUI manager"))?;
-        let menu_scene = Rc::new(RefCell::new(false));
+        let menu_scene = Property::new(false);
         let menu_scene_clone = menu_scene.clone();
-        gui.get_by_id_cast::<Button>("change_scene")
-            .change_context(builder::Error::msg("Failed to find change scene button"))?
-            .borrow_mut()
-            .click_cb(move |_| *menu_scene_clone.borrow_mut() = true);
+        gui.connect("change_scene", "clicked", move |_, _, _| menu_scene_clone.set(true))
+            .change_context(builder::Error::msg("Failed to connect change scene button"))?;
 
         let animator_cfg = make_animator_cfg!(
             State_enum: PlayerState,
@@ -1,28 +1,29 @@
 //! Main menu scene.
 
-use std::{cell::RefCell, rc::Rc};
-
 use builder::{config::Config, BuildFromCfg};
 use error_stack::ResultExt;
 use gui::{
-    manager::{widget::Widget, Manager as GuiManager},
-    widget::{Builder as GuiBuilder, Button, Flag, Graph, Textbox},
+    manager::{binding::Property, widget::TypedId, Manager as GuiManager},
+    widget::{Builder as GuiBuilder, Graph, Textbox},
 };
 use renderer::Drawable;
-use scene::{event::Event, Scene};
+use scene::{
+    event::{Event, KeyCode},
+    Scene,
+};
 
 /// Main menu scene.
 pub struct MainMenu {
     /// Main menu GUI.
     gui: GuiManager,
     /// Is need to load next scene.
-    next_scene: Rc<RefCell<bool>>,
+    next_scene: Property<bool>,
     /// Next scene config.
     cfg: Config,
     /// Graph for cursor x.
-    cursor_x: Rc<RefCell<Graph>>,
+    cursor_x: TypedId<Graph>,
     /// Graph for cursor y.
-    cursor_y: Rc<RefCell<Graph>>,
+    cursor_y: TypedId<Graph>,
 }
 
 impl Scene for MainMenu {
@@ -33,10 +34,28 @@ impl Scene for MainMenu {
     ) -> error_stack::Result<(), scene::Error> {
         self.gui.handle_event(e.clone()).change_context(scene::Error::msg("Gui failed"))?;
         if let Event::MouseMove(x, y) = e {
-            self.cursor_x.borrow_mut().push(x);
-            self.cursor_y.borrow_mut().push(y);
+            let tree = self.gui.tree_mut();
+            if let Some(graph) = tree.cast_mut(self.cursor_x) {
+                graph.push(x);
+            }
+            if let Some(graph) = tree.cast_mut(self.cursor_y) {
+                graph.push(y);
+            }
+        }
+        if let Event::KeyPress(KeyCode::F2) = e {
+            let res = state.get_resources_manager();
+            let locale = if res.get_locale() == "en" { "ru" } else { "en" };
+            self.gui
+                .set_locale(res, locale)
+                .change_context(scene::Error::msg("Failed to switch language"))?;
         }
-        if *self.next_scene.borrow() {
+        if let Event::KeyPress(KeyCode::F3) = e {
+            let theme = if self.gui.get_theme() == Some("light") { "dark" } else { "light" };
+            self.gui
+                .set_theme(theme)
+                .change_context(scene::Error::msg("Failed to switch theme"))?;
+        }
+        if self.next_scene.get() {
             state
                 .load_next_scene(
                     self.cfg
@@ -63,35 +82,30 @@ impl BuildFromCfg<Box<dyn Scene>> for MainMenu {
         let gui_cfg = cfg
             .take::<Config>("gui")
             .change_context(builder::Error::msg("Failed to build scene GUI"))?;
-        let gui = GuiManager::new(&GuiBuilder::default(), res, gui_cfg)
+        let mut gui = GuiManager::new(&GuiBuilder::default(), res, gui_cfg)
             .change_context(builder::Error::msg("Failed to init GUI manager"))?;
-        let next_scene = Rc::new(RefCell::new(false));
+        let next_scene = Property::new(false);
         let next_scene_clone = next_scene.clone();
-        gui.get_by_id_cast::<Button>("change_scene")
-            .change_context(builder::Error::msg("Failed to find change scene button"))?
-            .borrow_mut()
-            .click_cb(move |_| *next_scene_clone.borrow_mut() = true);
+        gui.connect("change_scene", "clicked", move |_, _, _| next_scene_clone.set(true))
+            .change_context(builder::Error::msg("Failed to connect change scene button"))?;
         let cursor_x = gui
             .get_by_id_cast::<Graph>("cursor_x")
             .change_context(builder::Error::msg("Failed to find graph for cursor x"))?;
         let cursor_y = gui
             .get_by_id_cast::<Graph>("cursor_y")
             .change_context(builder::Error::msg("Failed to find graph for cursor y"))?;
-        {
-            let cursor_x = cursor_x.clone();
-            let cursor_y = cursor_y.clone();
-            let text_1 = gui
-                .get_by_id_cast::<Textbox>("input_txt_1")
-                .change_context(builder::Error::msg("Failed to find textbox 1"))?;
-            gui.get_by_id_cast::<Flag>("hello_flag")
-                .change_context(builder::Error::msg("Failed to find change scene button"))?
-                .borrow_mut()
-                .change_state_cb(move |_, state| {
-                    cursor_x.borrow_mut().set_visible_flag(state);
-                    cursor_y.borrow_mut().set_visible_flag(state);
-                    text_1.borrow_mut().set_visible_flag(state);
-                });
-        }
+        let text_1 = gui
+            .get_by_id_cast::<Textbox>("input_txt_1")
+            .change_context(builder::Error::msg("Failed to find textbox 1"))?;
+        gui.connect("hello_flag", "value_changed", move |tree, _, signal| {
+            let state = signal.as_bool().unwrap_or_default();
+            for widget in [cursor_x.id(), cursor_y.id(), text_1.id()] {
+                if let Some(widget) = tree.get_mut(widget) {
+                    widget.set_visible_flag(state);
+                }
+            }
+        })
+        .change_context(builder::Error::msg("Failed to connect hello flag"))?;
         Ok(Box::new(Self { gui, next_scene, cfg, cursor_x, cursor_y }))
     }
 }
@@ -6,8 +6,27 @@ pub trait Drawable {
     fn draw(&self, renderer: &mut dyn Renderer);
 }
 
-use resources::{FontId, TextureId};
-use utils::{color::Color, rect::Rectf, vec2::Vec2f};
+use error_stack::Result;
+use resources::{FontId, ShaderId, TextureId};
+use slice::Insets;
+use std::str::FromStr;
+use text::{LayoutOptions, Span, TextLayout, TextStyle};
+use utils::{color::Color, rect::Rectf, transform::Transform, vec2::Vec2f};
+
+pub mod shape;
+pub mod slice;
+pub mod text;
+
+/// Renderer error.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct Error(String);
+impl Error {
+    /// Make error from message.
+    pub fn msg<T: Into<String>>(msg: T) -> Self {
+        Self(msg.into())
+    }
+}
 
 /// Text truncation mode.
 #[derive(Clone, Copy)]
@@ -18,22 +37,220 @@ pub enum TextTruncateMode {
     Back,
 }
 
+/// Font vertical metrics in pixels.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FontMetrics {
+    /// Distance from baseline to the top of the highest glyph.
+    pub ascent: f64,
+    /// Distance from baseline to the bottom of the lowest glyph.
+    pub descent: f64,
+    /// Gap between lines.
+    pub line_gap: f64,
+}
+
+impl FontMetrics {
+    /// Distance between baselines of two neighboring lines.
+    #[must_use]
+    pub const fn line_height(&self) -> f64 {
+        self.ascent + self.descent + self.line_gap
+    }
+}
+
+/// Shape of the polyline corners.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Sharp corner. Too sharp corners are beveled.
+    #[default]
+    Miter,
+    /// Rounded corner.
+    Round,
+    /// Cut corner.
+    Bevel,
+}
+
+impl FromStr for LineJoin {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "miter" => Ok(Self::Miter),
+            "round" => Ok(Self::Round),
+            "bevel" => Ok(Self::Bevel),
+            _ => Err(Error::msg(format!("Unexpected line join: {s:?}"))),
+        }
+    }
+}
+
+/// Shape of the polyline ends.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// Line ends exactly at the end point.
+    #[default]
+    Butt,
+    /// Line ends with half circle.
+    Round,
+    /// Line is extended by half of its width.
+    Square,
+}
+
+impl FromStr for LineCap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "butt" => Ok(Self::Butt),
+            "round" => Ok(Self::Round),
+            "square" => Ok(Self::Square),
+            _ => Err(Error::msg(format!("Unexpected line cap: {s:?}"))),
+        }
+    }
+}
+
+/// Defines how drawn pixels are mixed with the pixels, that are already drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Drawn pixels are mixed by their alpha.
+    #[default]
+    Alpha,
+    /// Colors are added. Used for glowing effects.
+    Add,
+    /// Colors are multiplied. Result is always darker.
+    Multiply,
+    /// Inverted colors are multiplied. Result is always lighter.
+    Screen,
+}
+
+impl FromStr for BlendMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "alpha" => Ok(Self::Alpha),
+            "add" => Ok(Self::Add),
+            "multiply" => Ok(Self::Multiply),
+            "screen" => Ok(Self::Screen),
+            _ => Err(Error::msg(format!("Unexpected blend mode: {s:?}"))),
+        }
+    }
+}
+
+/// Line style.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stroke {
+    /// Line width.
+    pub width: f64,
+    /// Corners shape.
+    pub join: LineJoin,
+    /// Ends shape.
+    pub cap: LineCap,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self { width: 1.0, join: LineJoin::default(), cap: LineCap::default() }
+    }
+}
+
+impl Stroke {
+    /// Create solid line style with specified width.
+    #[must_use]
+    pub fn new(width: f64) -> Self {
+        Self { width, ..Self::default() }
+    }
+}
+
 /// GUI rendered interface.
 pub trait Renderer {
-    /// Save current renderer state (offset, rotation and scale).
+    /// Save current renderer state (transform, opacity and blend mode).
     fn push_state(&mut self);
-    /// Restore preview renderer state (offset, rotation and scale).
+    /// Restore preview renderer state (transform, opacity and blend mode).
     fn pop_state(&mut self);
     /// Translate the origin of coordinates.
     fn translate(&mut self, x: f64, y: f64);
+    /// Rotate coordinates around the origin. Angle is in radians, positive angle rotates clockwise.
+    fn rotate(&mut self, angle: f64) {
+        let t = self.get_transform().rotate(angle);
+        self.set_transform(&t);
+    }
+    /// Scale coordinates relative to the origin. Negative scale flips coordinates.
+    fn scale(&mut self, sx: f64, sy: f64) {
+        let t = self.get_transform().scale(sx, sy);
+        self.set_transform(&t);
+    }
+    /// Replace current transform. Transform maps local coordinates to window ones.
+    fn set_transform(&mut self, transform: &Transform);
+    /// Get current transform.
+    fn get_transform(&self) -> Transform;
+    /// Set opacity, that multiplies alpha of all drawn colors. Must be in range `0.0..=1.0`.
+    fn set_opacity(&mut self, opacity: f64);
+    /// Get current opacity.
+    fn get_opacity(&self) -> f64;
+    /// Multiply current opacity. Used to fade nested objects relative to their parent.
+    fn fade(&mut self, opacity: f64) {
+        let o = self.get_opacity() * opacity;
+        self.set_opacity(o);
+    }
+    /// Set blend mode for next draws.
+    fn set_blend_mode(&mut self, mode: BlendMode);
+    /// Get current blend mode.
+    fn get_blend_mode(&self) -> BlendMode;
+    /// Set shader for next textured draws (images and text). `None` restores default drawing.
+    /// Shader is saved with state. Backends without shaders support ignore it.
+    fn set_shader(&mut self, _shader: Option<ShaderId>) {}
+    /// Set shader parameter (float, vec2, vec3 or vec4 uniform). Parameters keep their values
+    /// until changed.
+    fn set_shader_param(&mut self, _shader: ShaderId, _name: &str, _value: &[f32]) {}
+    /// Start drawing of the frame part, that is processed by post-process shaders.
+    /// Backends without shaders support draw it without effects.
+    fn begin_post_process(&mut self, _chain: &[ShaderId]) {}
+    /// Apply post-process shaders in order and draw the result.
+    fn end_post_process(&mut self) {}
+    /// Restrict drawing to the rectangle in local coordinates until [`Renderer::pop_clip`].
+    /// Nested clip rectangles are intersected. Rotated rectangle is replaced by its bounding box.
+    fn push_clip(&mut self, rect: &Rectf);
+    /// Remove the last clip rectangle.
+    fn pop_clip(&mut self);
+
+    /// Redirect drawing into render target, created by [`resources::Manager::create_render_target`].
+    /// Target is cleared and drawing starts with default state. Targets can be nested.
+    ///
+    /// # Errors
+    /// Return error if texture is not a render target.
+    fn bind_target(&mut self, target: TextureId) -> Result<(), Error>;
+    /// Finish drawing into the last bound target and restore previous drawing state.
+    fn unbind_target(&mut self);
 
     /// Draw specified rectangle with specified color.
     fn draw_rect(&mut self, rect: &Rectf, color: &Color);
     /// Draw specified line with specified color.
     fn draw_line(&mut self, points: &[Vec2f], color: &Color);
     /// Draw specified part of texture in some area.
-    fn draw_img(&mut self, rect: &Rectf, texture: TextureId, texture_rect: &Rectf);
-    /// Draw specified text.
+    fn draw_img(&mut self, rect: &Rectf, texture: TextureId, texture_rect: &Rectf) {
+        let white = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+        self.draw_img_tinted(rect, texture, texture_rect, &white);
+    }
+    /// Draw specified part of texture in some area. Texture colors are multiplied by `tint`.
+    fn draw_img_tinted(
+        &mut self,
+        rect: &Rectf,
+        texture: TextureId,
+        texture_rect: &Rectf,
+        tint: &Color,
+    );
+    /// Draw specified part of texture sliced by insets, so its corners are not distorted.
+    /// Insets are in texture pixels, see [`slice`] module.
+    fn draw_img_sliced(
+        &mut self,
+        rect: &Rectf,
+        texture: TextureId,
+        texture_rect: &Rectf,
+        insets: &Insets,
+    ) {
+        for (rect, texture_rect) in slice::nine_slice(rect, texture_rect, insets) {
+            self.draw_img(&rect, texture, &texture_rect);
+        }
+    }
+    /// Draw specified text. Text is wrapped by words to fit the rectangle width.
     /// Returns number of truncated chars.
     fn draw_text(
         &mut self,
@@ -43,5 +260,67 @@ pub trait Renderer {
         font: FontId,
         color: &Color,
         mode: TextTruncateMode,
-    ) -> usize;
+    ) -> usize {
+        let spans = [Span { text: text.to_vec(), style: TextStyle { font, size, color: *color } }];
+        let options = LayoutOptions { truncate: mode, ..LayoutOptions::default() };
+        let layout = text::layout(&spans, rect, &options, self);
+        self.draw_layout(&layout);
+        layout.truncated
+    }
+    /// Draw glyphs of text layout, created by [`text::layout`].
+    fn draw_layout(&mut self, layout: &TextLayout);
+
+    /// Fill triangles with specified color.
+    fn fill_triangles(&mut self, triangles: &[shape::Triangle], color: &Color);
+    /// Fill specified rectangle with specified color.
+    fn fill_rect(&mut self, rect: &Rectf, color: &Color) {
+        self.fill_triangles(&shape::fan(&shape::rounded_rect(rect, 0.0)), color);
+    }
+    /// Fill rectangle with rounded corners.
+    fn fill_rounded_rect(&mut self, rect: &Rectf, radius: f64, color: &Color) {
+        self.fill_triangles(&shape::fan(&shape::rounded_rect(rect, radius)), color);
+    }
+    /// Stroke outline of rectangle with rounded corners.
+    fn stroke_rounded_rect(&mut self, rect: &Rectf, radius: f64, color: &Color, stroke: &Stroke) {
+        self.stroke_polyline(&shape::rounded_rect(rect, radius), true, color, stroke);
+    }
+    /// Fill ellipse inscribed in the rectangle.
+    fn fill_ellipse(&mut self, rect: &Rectf, color: &Color) {
+        self.fill_triangles(&shape::fan(&shape::ellipse(rect)), color);
+    }
+    /// Stroke outline of ellipse inscribed in the rectangle.
+    fn stroke_ellipse(&mut self, rect: &Rectf, color: &Color, stroke: &Stroke) {
+        self.stroke_polyline(&shape::ellipse(rect), true, color, stroke);
+    }
+    /// Stroke arc of ellipse inscribed in the rectangle.
+    /// Angles are in radians and grow clockwise, zero angle points right.
+    fn stroke_arc(&mut self, rect: &Rectf, start: f64, end: f64, color: &Color, stroke: &Stroke) {
+        self.stroke_polyline(&shape::ellipse_arc(rect, start, end), false, color, stroke);
+    }
+    /// Fill simple (convex or concave) polygon.
+    fn fill_polygon(&mut self, points: &[Vec2f], color: &Color) {
+        self.fill_triangles(&shape::triangulate(points), color);
+    }
+    /// Stroke polyline with specified line style.
+    /// If `closed` is `true` the last point is connected with the first one.
+    fn stroke_polyline(&mut self, points: &[Vec2f], closed: bool, color: &Color, stroke: &Stroke) {
+        self.fill_triangles(&shape::stroke(points, closed, stroke), color);
+    }
+
+    /// Get font metrics for specified font size.
+    fn font_metrics(&mut self, font: FontId, size: f64) -> FontMetrics;
+    /// Get horizontal advance of char. Fallback fonts are used for glyphs missed in font.
+    fn glyph_advance(&mut self, font: FontId, size: f64, ch: char) -> f64;
+    /// Get kerning between two neighboring chars. Backends without kerning support return zero.
+    fn kerning(&mut self, _font: FontId, _size: f64, _prev: char, _ch: char) -> f64 {
+        0.0
+    }
+    /// Measure text without drawing it.
+    /// Returns width of the longest line and height of all lines.
+    fn measure_text(&mut self, text: &[char], size: f64, font: FontId) -> Vec2f {
+        let white = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+        let spans = [Span { text: text.to_vec(), style: TextStyle { font, size, color: white } }];
+        let layout = text::layout(&spans, &[0.0; 4].into(), &LayoutOptions::default(), self);
+        Vec2f::new(layout.bounds.w, layout.bounds.h)
+    }
 }
@@ -0,0 +1,319 @@
+//! Shape geometry.
+//!
+//! Converts shapes into triangles, so a backend has to be able to fill triangles only.
+//! Angles are in radians and grow clockwise on screen (`y` axis points down).
+
+use super::{LineCap, LineJoin, Stroke};
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use utils::{rect::Rectf, vec2::Vec2f};
+
+/// Triangle.
+pub type Triangle = [Vec2f; 3];
+
+/// Maximum distance between curve and its polyline approximation in pixels.
+const TOLERANCE: f64 = 0.25;
+
+/// Miter join is replaced by bevel one if miter length exceeds `MITER_LIMIT * width / 2`.
+const MITER_LIMIT: f64 = 4.0;
+
+/// Get number of segments to approximate arc with specified radius and sweep angle.
+#[must_use]
+pub fn arc_segments(radius: f64, sweep: f64) -> usize {
+    if radius <= TOLERANCE {
+        return 1;
+    }
+    let step = 2.0 * (1.0 - TOLERANCE / radius).acos();
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let n = (sweep.abs() / step).ceil() as usize;
+    n.max(1)
+}
+
+/// Get points of the ellipse arc. The ellipse is inscribed in the rectangle.
+#[must_use]
+pub fn ellipse_arc(rect: &Rectf, start: f64, end: f64) -> Vec<Vec2f> {
+    let (rx, ry) = (rect.w / 2.0, rect.h / 2.0);
+    let (cx, cy) = (rect.x + rx, rect.y + ry);
+    let n = arc_segments(rx.max(ry), end - start);
+    #[allow(clippy::cast_precision_loss)]
+    let step = (end - start) / n as f64;
+    (0..=n)
+        .map(|i| {
+            #[allow(clippy::cast_precision_loss)]
+            let a = step.mul_add(i as f64, start);
+            Vec2f::new(a.cos().mul_add(rx, cx), a.sin().mul_add(ry, cy))
+        })
+        .collect()
+}
+
+/// Get points of the ellipse inscribed in the rectangle. The first point is not repeated.
+#[must_use]
+pub fn ellipse(rect: &Rectf) -> Vec<Vec2f> {
+    let mut points = ellipse_arc(rect, 0.0, TAU);
+    points.pop();
+    points
+}
+
+/// Get points of the rectangle with rounded corners. Radius is clamped to fit the rectangle.
+#[must_use]
+pub fn rounded_rect(rect: &Rectf, radius: f64) -> Vec<Vec2f> {
+    let r = radius.min(rect.w / 2.0).min(rect.h / 2.0).max(0.0);
+    if r == 0.0 {
+        return vec![
+            Vec2f::new(rect.x, rect.y),
+            Vec2f::new(rect.x + rect.w, rect.y),
+            Vec2f::new(rect.x + rect.w, rect.y + rect.h),
+            Vec2f::new(rect.x, rect.y + rect.h),
+        ];
+    }
+    let d = 2.0 * r;
+    let corners = [
+        (rect.x, rect.y, PI),
+        (rect.x + rect.w - d, rect.y, PI + FRAC_PI_2),
+        (rect.x + rect.w - d, rect.y + rect.h - d, 0.0),
+        (rect.x, rect.y + rect.h - d, FRAC_PI_2),
+    ];
+    corners
+        .into_iter()
+        .flat_map(|(x, y, a)| ellipse_arc(&Rectf::from([x, y, d, d]), a, a + FRAC_PI_2))
+        .collect()
+}
+
+/// Triangulate convex polygon.
+#[must_use]
+pub fn fan(points: &[Vec2f]) -> Vec<Triangle> {
+    let Some((first, rest)) = points.split_first() else {
+        return Vec::new();
+    };
+    rest.windows(2).map(|w| [*first, w[0], w[1]]).collect()
+}
+
+/// Cross product of vectors `b - a` and `c - b`.
+fn cross(a: Vec2f, b: Vec2f, c: Vec2f) -> f64 {
+    (b.x - a.x).mul_add(c.y - b.y, -(b.y - a.y) * (c.x - b.x))
+}
+
+/// Check if point is inside triangle or on its edge.
+fn in_triangle(p: Vec2f, [a, b, c]: Triangle) -> bool {
+    let (d1, d2, d3) = (cross(a, b, p), cross(b, c, p), cross(c, a, p));
+    let has_neg = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_pos = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_neg && has_pos)
+}
+
+/// Triangulate simple (convex or concave) polygon by ear clipping.
+/// Self-intersecting polygons are triangulated partially.
+#[must_use]
+pub fn triangulate(points: &[Vec2f]) -> Vec<Triangle> {
+    let area: f64 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.x.mul_add(b.y, -b.x * a.y))
+        .sum();
+    let orientation = area.signum();
+    let mut idx: Vec<usize> = (0..points.len()).collect();
+    let mut triangles = Vec::with_capacity(points.len().saturating_sub(2));
+    let mut i = 0;
+    let mut fails = 0;
+    while idx.len() > 2 && fails < idx.len() {
+        let n = idx.len();
+        let (prev, cur, next) = (idx[(i + n - 1) % n], idx[i % n], idx[(i + 1) % n]);
+        let tri = [points[prev], points[cur], points[next]];
+        let is_convex = cross(tri[0], tri[1], tri[2]) * orientation > 0.0;
+        let is_ear = is_convex
+            && idx
+                .iter()
+                .filter(|j| ![prev, cur, next].contains(*j))
+                .all(|j| !in_triangle(points[*j], tri));
+        if is_ear {
+            triangles.push(tri);
+            idx.remove(i % n);
+            fails = 0;
+        } else {
+            i += 1;
+            fails += 1;
+        }
+        i %= idx.len().max(1);
+    }
+    triangles
+}
+
+/// Normalize vector. Returns `None` for zero vector.
+fn normalize(v: Vec2f) -> Option<Vec2f> {
+    let len = v.x.hypot(v.y);
+    (len > f64::EPSILON).then(|| Vec2f::new(v.x / len, v.y / len))
+}
+
+/// Scale vector.
+fn scale(v: Vec2f, k: f64) -> Vec2f {
+    Vec2f::new(v.x * k, v.y * k)
+}
+
+/// Add triangles of the circle sector with center `c`, radius `r`,
+/// from angle `start` with sweep `sweep`.
+fn sector(triangles: &mut Vec<Triangle>, c: Vec2f, r: f64, start: f64, sweep: f64) {
+    let n = arc_segments(r, sweep);
+    #[allow(clippy::cast_precision_loss)]
+    let step = sweep / n as f64;
+    let point = |i: usize| {
+        #[allow(clippy::cast_precision_loss)]
+        let a = step.mul_add(i as f64, start);
+        Vec2f::new(a.cos().mul_add(r, c.x), a.sin().mul_add(r, c.y))
+    };
+    triangles.extend((0..n).map(|i| [c, point(i), point(i + 1)]));
+}
+
+/// Add join triangles at vertex `v` between segments with directions `d0` and `d1`.
+fn join(triangles: &mut Vec<Triangle>, v: Vec2f, d0: Vec2f, d1: Vec2f, hw: f64, style: LineJoin) {
+    let turn = d0.x.mul_add(d1.y, -d0.y * d1.x);
+    if turn.abs() < f64::EPSILON {
+        return;
+    }
+    // Gap to fill is on the outer side of the turn.
+    let side = -turn.signum() * hw;
+    let n0 = Vec2f::new(-d0.y * side, d0.x * side);
+    let n1 = Vec2f::new(-d1.y * side, d1.x * side);
+    let miter = normalize(n0 + n1).map(|m| {
+        let len = hw * hw / m.x.mul_add(n0.x, m.y * n0.y);
+        scale(m, len)
+    });
+    match (style, miter) {
+        (LineJoin::Miter, Some(m)) if m.x.hypot(m.y) <= MITER_LIMIT * hw => {
+            triangles.push([v, v + n0, v + m]);
+            triangles.push([v, v + m, v + n1]);
+        }
+        (LineJoin::Round, _) => {
+            let start = n0.y.atan2(n0.x);
+            let mut sweep = n1.y.atan2(n1.x) - start;
+            if sweep > PI {
+                sweep -= TAU;
+            } else if sweep < -PI {
+                sweep += TAU;
+            }
+            sector(triangles, v, hw, start, sweep);
+        }
+        _ => triangles.push([v, v + n0, v + n1]),
+    }
+}
+
+/// Add cap triangles at line end `p` with outward direction `d`.
+fn cap(triangles: &mut Vec<Triangle>, p: Vec2f, d: Vec2f, hw: f64, style: LineCap) {
+    let n = Vec2f::new(-d.y * hw, d.x * hw);
+    match style {
+        LineCap::Butt => {}
+        LineCap::Square => {
+            let ext = scale(d, hw);
+            triangles.push([p + n, p - n, p - n + ext]);
+            triangles.push([p + n, p - n + ext, p + n + ext]);
+        }
+        LineCap::Round => sector(triangles, p, hw, (-n.y).atan2(-n.x), PI),
+    }
+}
+
+/// Convert polyline into triangles with specified stroke style.
+/// If `closed` is `true` the last point is connected with the first one.
+#[must_use]
+pub fn stroke(points: &[Vec2f], closed: bool, style: &Stroke) -> Vec<Triangle> {
+    let hw = style.width / 2.0;
+    let mut pts: Vec<Vec2f> = Vec::with_capacity(points.len());
+    for p in points {
+        if pts.last().is_none_or(|l| (l.x - p.x).hypot(l.y - p.y) > f64::EPSILON) {
+            pts.push(*p);
+        }
+    }
+    if closed && pts.len() > 2 {
+        let (first, last) = (pts[0], pts[pts.len() - 1]);
+        if (first.x - last.x).hypot(first.y - last.y) <= f64::EPSILON {
+            pts.pop();
+        }
+    }
+    let mut triangles = Vec::new();
+    if pts.len() < 2 || hw <= 0.0 {
+        return triangles;
+    }
+    let segments: Vec<(Vec2f, Vec2f, Vec2f)> = pts
+        .iter()
+        .zip(pts.iter().cycle().skip(1))
+        .take(if closed { pts.len() } else { pts.len() - 1 })
+        .filter_map(|(a, b)| Some((*a, *b, normalize(*b - *a)?)))
+        .collect();
+
+    for (a, b, d) in &segments {
+        let n = Vec2f::new(-d.y * hw, d.x * hw);
+        triangles.push([*a + n, *a - n, *b - n]);
+        triangles.push([*a + n, *b - n, *b + n]);
+    }
+    for (s0, s1) in segments.iter().zip(segments.iter().skip(1)) {
+        join(&mut triangles, s0.1, s0.2, s1.2, hw, style.join);
+    }
+    if let (Some(first), Some(last)) = (segments.first(), segments.last()) {
+        if closed {
+            join(&mut triangles, last.1, last.2, first.2, hw, style.join);
+        } else {
+            cap(&mut triangles, first.0, scale(first.2, -1.0), hw, style.cap);
+            cap(&mut triangles, last.1, last.2, hw, style.cap);
+        }
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fan, rounded_rect, stroke, triangulate, Triangle};
+    use crate::{LineCap, LineJoin, Stroke};
+    use utils::{rect::Rectf, vec2::Vec2f};
+
+    /// Get summary area of triangles.
+    fn area(triangles: &[Triangle]) -> f64 {
+        triangles
+            .iter()
+            .map(|[a, b, c]| (b.x - a.x).mul_add(c.y - a.y, -(b.y - a.y) * (c.x - a.x)).abs() / 2.0)
+            .sum()
+    }
+
+    /// Make points from coordinates.
+    fn points(coords: &[(f64, f64)]) -> Vec<Vec2f> {
+        coords.iter().map(|p| (*p).into()).collect()
+    }
+
+    #[test]
+    fn triangulate_concave() {
+        // "L" shape with area 3.
+        let l = points(&[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (0.0, 2.0)]);
+        let t = triangulate(&l);
+        assert_eq!(t.len(), 4);
+        assert!((area(&t) - 3.0).abs() < 1e-9);
+
+        let mut reversed = l;
+        reversed.reverse();
+        assert!((area(&triangulate(&reversed)) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fan_rect() {
+        let rect = rounded_rect(&Rectf::from([0.0, 0.0, 4.0, 2.0]), 0.0);
+        assert_eq!(rect.len(), 4);
+        assert!((area(&fan(&rect)) - 8.0).abs() < 1e-9);
+        let round = rounded_rect(&Rectf::from([0.0, 0.0, 4.0, 2.0]), 10.0);
+        let expected = 2.0f64.mul_add(2.0, std::f64::consts::PI);
+        assert!((area(&fan(&round)) - expected).abs() < 0.5);
+    }
+
+    #[test]
+    fn stroke_line() {
+        let line = points(&[(0.0, 0.0), (10.0, 0.0)]);
+        let butt = Stroke { width: 2.0, join: LineJoin::Miter, cap: LineCap::Butt };
+        assert!((area(&stroke(&line, false, &butt)) - 20.0).abs() < 1e-9);
+        let square = Stroke { cap: LineCap::Square, ..butt };
+        assert!((area(&stroke(&line, false, &square)) - 24.0).abs() < 1e-9);
+
+        // Segments overlap at inner corners, so 4 corner squares are counted twice.
+        // Miter join fills outer corner square, bevel join fills half of it.
+        let sq = points(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        assert!((area(&stroke(&sq, true, &butt)) - 84.0).abs() < 1e-9);
+        let bevel = Stroke { join: LineJoin::Bevel, ..butt };
+        assert!((area(&stroke(&sq, true, &bevel)) - 82.0).abs() < 1e-9);
+        let round = Stroke { join: LineJoin::Round, ..butt };
+        let round_area = area(&stroke(&sq, true, &round));
+        assert!(round_area > 82.0 && round_area < 84.0);
+    }
+}
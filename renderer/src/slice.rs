@@ -0,0 +1,114 @@
+//! Sliced images.
+//!
+//! Nine-slice splits texture region into 3x3 parts by insets: corners keep their size, edges are
+//! stretched along one axis and the center is stretched along both ones. Three-slice is the
+//! nine-slice with zero insets on one axis.
+
+use utils::rect::Rectf;
+
+/// Insets from the rectangle sides.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Insets {
+    /// Left inset.
+    pub left: f64,
+    /// Top inset.
+    pub top: f64,
+    /// Right inset.
+    pub right: f64,
+    /// Bottom inset.
+    pub bottom: f64,
+}
+
+impl Insets {
+    /// Create insets for horizontal three-slice.
+    #[must_use]
+    pub const fn horizontal(left: f64, right: f64) -> Self {
+        Self { left, top: 0.0, right, bottom: 0.0 }
+    }
+
+    /// Create insets for vertical three-slice.
+    #[must_use]
+    pub const fn vertical(top: f64, bottom: f64) -> Self {
+        Self { left: 0.0, top, right: 0.0, bottom }
+    }
+}
+
+impl From<[f64; 4]> for Insets {
+    fn from(value: [f64; 4]) -> Self {
+        let [left, top, right, bottom] = value;
+        Self { left, top, right, bottom }
+    }
+}
+
+/// Split segment by insets. Insets are shrunk proportionally if they don't fit into segment.
+fn split(pos: f64, len: f64, start: f64, end: f64) -> [(f64, f64); 3] {
+    let k = if start + end > len && start + end > 0.0 { len / (start + end) } else { 1.0 };
+    let (start, end) = (start * k, end * k);
+    [(pos, start), (pos + start, len - start - end), (pos + len - end, end)]
+}
+
+/// Get pairs of destination and texture rectangles for each slice.
+/// Empty slices are skipped, so zero insets give one stretched image.
+#[must_use]
+pub fn nine_slice(rect: &Rectf, texture_rect: &Rectf, insets: &Insets) -> Vec<(Rectf, Rectf)> {
+    let (l, t, r, b) = (insets.left, insets.top, insets.right, insets.bottom);
+    let columns = split(rect.x, rect.w, l, r);
+    let rows = split(rect.y, rect.h, t, b);
+    let texture_columns = split(texture_rect.x, texture_rect.w, l, r);
+    let texture_rows = split(texture_rect.y, texture_rect.h, t, b);
+    let mut slices = Vec::with_capacity(9);
+    for ((y, h), (src_y, src_h)) in rows.into_iter().zip(texture_rows) {
+        for ((x, w), (src_x, src_w)) in columns.into_iter().zip(texture_columns) {
+            if w > 0.0 && h > 0.0 && src_w > 0.0 && src_h > 0.0 {
+                slices.push(([x, y, w, h].into(), [src_x, src_y, src_w, src_h].into()));
+            }
+        }
+    }
+    slices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{nine_slice, Insets};
+    use utils::rect::Rectf;
+
+    /// Check that rectangle is equal to expected one with some precision.
+    fn assert_near(r: &Rectf, expected: [f64; 4]) {
+        let actual = [r.x, r.y, r.w, r.h];
+        assert!(actual.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-9), "{r:?}");
+    }
+
+    #[test]
+    fn nine() {
+        let rect = Rectf::from([10.0, 20.0, 100.0, 50.0]);
+        let src = Rectf::from([0.0, 0.0, 30.0, 30.0]);
+        let slices = nine_slice(&rect, &src, &[5.0, 5.0, 10.0, 10.0].into());
+        assert_eq!(slices.len(), 9);
+        assert_near(&slices[0].0, [10.0, 20.0, 5.0, 5.0]);
+        assert_near(&slices[0].1, [0.0, 0.0, 5.0, 5.0]);
+        assert_near(&slices[4].0, [15.0, 25.0, 85.0, 35.0]);
+        assert_near(&slices[4].1, [5.0, 5.0, 15.0, 15.0]);
+        assert_near(&slices[8].0, [100.0, 60.0, 10.0, 10.0]);
+        assert_near(&slices[8].1, [20.0, 20.0, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn three() {
+        let rect = Rectf::from([0.0, 0.0, 100.0, 20.0]);
+        let src = Rectf::from([0.0, 0.0, 30.0, 10.0]);
+        let slices = nine_slice(&rect, &src, &Insets::horizontal(10.0, 10.0));
+        assert_eq!(slices.len(), 3);
+        assert_near(&slices[1].0, [10.0, 0.0, 80.0, 20.0]);
+        assert_near(&slices[1].1, [10.0, 0.0, 10.0, 10.0]);
+    }
+
+    #[test]
+    fn shrink() {
+        let rect = Rectf::from([0.0, 0.0, 10.0, 10.0]);
+        let src = Rectf::from([0.0, 0.0, 30.0, 30.0]);
+        let slices = nine_slice(&rect, &src, &[10.0, 0.0, 10.0, 0.0].into());
+        assert_eq!(slices.len(), 2);
+        assert_near(&slices[0].0, [0.0, 0.0, 5.0, 10.0]);
+        assert_near(&slices[1].0, [5.0, 0.0, 5.0, 10.0]);
+    }
+}
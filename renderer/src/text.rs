@@ -0,0 +1,735 @@
+//! Text layout.
+//!
+//! Splits styled text into lines, aligns them in a rectangle and places glyphs. The layout is
+//! used for text drawing and by widgets, that need glyph positions for caret and selection.
+//! Char indices of the layout are indices in the concatenated text of all spans.
+
+use super::{FontMetrics, Renderer, TextTruncateMode};
+use resources::FontId;
+use std::{ops::Range, str::FromStr};
+use utils::{color::Color, rect::Rectf, vec2::Vec2f};
+
+/// Horizontal text alignment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HAlign {
+    /// Lines are aligned by the left side.
+    #[default]
+    Left,
+    /// Lines are centered.
+    Center,
+    /// Lines are aligned by the right side.
+    Right,
+}
+
+impl HAlign {
+    /// Get part of free space before the line.
+    const fn factor(self) -> f64 {
+        match self {
+            Self::Left => 0.0,
+            Self::Center => 0.5,
+            Self::Right => 1.0,
+        }
+    }
+}
+
+impl FromStr for HAlign {
+    type Err = super::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Self::Left),
+            "center" => Ok(Self::Center),
+            "right" => Ok(Self::Right),
+            _ => Err(super::Error::msg(format!("Unexpected horizontal align: {s:?}"))),
+        }
+    }
+}
+
+/// Vertical text alignment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VAlign {
+    /// Text is aligned by the top side.
+    #[default]
+    Top,
+    /// Text is centered.
+    Center,
+    /// Text is aligned by the bottom side.
+    Bottom,
+}
+
+impl VAlign {
+    /// Get part of free space before the text.
+    const fn factor(self) -> f64 {
+        match self {
+            Self::Top => 0.0,
+            Self::Center => 0.5,
+            Self::Bottom => 1.0,
+        }
+    }
+}
+
+impl FromStr for VAlign {
+    type Err = super::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(Self::Top),
+            "center" => Ok(Self::Center),
+            "bottom" => Ok(Self::Bottom),
+            _ => Err(super::Error::msg(format!("Unexpected vertical align: {s:?}"))),
+        }
+    }
+}
+
+/// Text style.
+#[derive(Clone, Copy)]
+pub struct TextStyle {
+    /// Font.
+    pub font: FontId,
+    /// Font size.
+    pub size: f64,
+    /// Text color.
+    pub color: Color,
+}
+
+/// Chars with the same style.
+#[derive(Clone)]
+pub struct Span {
+    /// Span text.
+    pub text: Vec<char>,
+    /// Span style.
+    pub style: TextStyle,
+}
+
+/// Text layout options.
+#[derive(Clone, Copy)]
+pub struct LayoutOptions {
+    /// Horizontal alignment.
+    pub h_align: HAlign,
+    /// Vertical alignment.
+    pub v_align: VAlign,
+    /// Wrap lines by words to fit the rectangle width. Too long words are wrapped by chars.
+    pub wrap: bool,
+    /// Mark truncated text by ellipsis.
+    pub ellipsis: bool,
+    /// Line height multiplier.
+    pub line_spacing: f64,
+    /// Part of text, that is truncated if text does not fit the rectangle height.
+    pub truncate: TextTruncateMode,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            h_align: HAlign::default(),
+            v_align: VAlign::default(),
+            wrap: true,
+            ellipsis: false,
+            line_spacing: 1.0,
+            truncate: TextTruncateMode::Back,
+        }
+    }
+}
+
+/// Font measurements, that are required for text layout.
+pub trait FontMeasure {
+    /// Get font metrics for specified font size.
+    fn metrics(&mut self, font: FontId, size: f64) -> FontMetrics;
+    /// Get horizontal advance of char.
+    fn advance(&mut self, font: FontId, size: f64, ch: char) -> f64;
+    /// Get kerning between two neighboring chars.
+    fn kern(&mut self, font: FontId, size: f64, prev: char, ch: char) -> f64;
+}
+
+impl<R: Renderer + ?Sized> FontMeasure for R {
+    fn metrics(&mut self, font: FontId, size: f64) -> FontMetrics {
+        self.font_metrics(font, size)
+    }
+
+    fn advance(&mut self, font: FontId, size: f64, ch: char) -> f64 {
+        self.glyph_advance(font, size, ch)
+    }
+
+    fn kern(&mut self, font: FontId, size: f64, prev: char, ch: char) -> f64 {
+        self.kerning(font, size, prev, ch)
+    }
+}
+
+/// Placed glyph.
+#[derive(Clone, Copy)]
+pub struct PositionedGlyph {
+    /// Index of the source char. `None` for ellipsis.
+    pub index: Option<usize>,
+    /// Glyph char.
+    pub ch: char,
+    /// Pen position on the baseline.
+    pub pos: Vec2f,
+    /// Horizontal advance.
+    pub advance: f64,
+    /// Glyph style.
+    pub style: TextStyle,
+}
+
+/// Placed line.
+#[derive(Clone)]
+pub struct Line {
+    /// Range of the source chars without the line break.
+    pub chars: Range<usize>,
+    /// Range of the line glyphs in [`TextLayout::glyphs`].
+    pub glyphs: Range<usize>,
+    /// Line bounds. Height includes line spacing, so lines have no gaps between them.
+    pub rect: Rectf,
+    /// Baseline position.
+    pub baseline: f64,
+}
+
+/// Text layout.
+#[derive(Clone)]
+pub struct TextLayout {
+    /// Placed glyphs.
+    pub glyphs: Vec<PositionedGlyph>,
+    /// Placed lines.
+    pub lines: Vec<Line>,
+    /// Bounds of all lines.
+    pub bounds: Rectf,
+    /// Number of chars, that are truncated from the text end for [`TextTruncateMode::Back`]
+    /// or from the text start for [`TextTruncateMode::Front`].
+    pub truncated: usize,
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self { glyphs: Vec::new(), lines: Vec::new(), bounds: [0.0; 4].into(), truncated: 0 }
+    }
+}
+
+impl TextLayout {
+    /// Get caret index (index of the char before that caret stands) nearest to position.
+    #[must_use]
+    pub fn hit_test(&self, pos: Vec2f) -> usize {
+        let Some(line) =
+            self.lines.iter().find(|l| pos.y < l.rect.y + l.rect.h).or_else(|| self.lines.last())
+        else {
+            return 0;
+        };
+        for g in &self.glyphs[line.glyphs.clone()] {
+            if let Some(index) = g.index {
+                if pos.x < g.pos.x + g.advance / 2.0 {
+                    return index;
+                }
+            }
+        }
+        line.chars.end
+    }
+
+    /// Get caret rectangle with zero width before char with specified index.
+    /// Returns `None` if the char is not placed.
+    #[must_use]
+    pub fn caret_rect(&self, index: usize) -> Option<Rectf> {
+        let line = self
+            .lines
+            .iter()
+            .find(|l| l.chars.contains(&index))
+            .or_else(|| self.lines.iter().find(|l| l.chars.end == index))?;
+        Some([self.caret_x(line, index), line.rect.y, 0.0, line.rect.h].into())
+    }
+
+    /// Get rectangles, that cover chars in the range. One rectangle per line.
+    #[must_use]
+    pub fn selection_rects(&self, range: Range<usize>) -> Vec<Rectf> {
+        self.lines
+            .iter()
+            .filter(|l| l.chars.start < range.end && range.start <= l.chars.end)
+            .map(|l| {
+                let start = self.caret_x(l, range.start.max(l.chars.start));
+                let end = self.caret_x(l, range.end.min(l.chars.end));
+                [start, l.rect.y, end - start, l.rect.h].into()
+            })
+            .collect()
+    }
+
+    /// Get caret position in the line before char with specified index.
+    fn caret_x(&self, line: &Line, index: usize) -> f64 {
+        let glyphs = &self.glyphs[line.glyphs.clone()];
+        glyphs.iter().find(|g| g.index == Some(index)).map_or_else(
+            || {
+                glyphs
+                    .iter()
+                    .rfind(|g| g.index.is_some())
+                    .map_or(line.rect.x, |g| g.pos.x + g.advance)
+            },
+            |g| g.pos.x,
+        )
+    }
+}
+
+/// Char prepared for layout.
+#[derive(Clone, Copy)]
+struct Item {
+    /// Source char index.
+    index: usize,
+    /// Char.
+    ch: char,
+    /// Index of the char span.
+    span: usize,
+    /// Kerning with the previous char.
+    kern: f64,
+    /// Horizontal advance.
+    advance: f64,
+}
+
+/// Line, that is not placed yet.
+struct RawLine {
+    /// Line chars.
+    items: Vec<Item>,
+    /// Index of the first char.
+    start: usize,
+    /// Span of the first char. It defines height of empty line.
+    span: usize,
+    /// Line is truncated by ellipsis.
+    ellipsis: bool,
+}
+
+impl RawLine {
+    /// Get index after the last char.
+    fn end(&self) -> usize {
+        self.items.last().map_or(self.start, |i| i.index + 1)
+    }
+
+    /// Get line metrics, that are defined by the biggest font in the line.
+    fn metrics(&self, span_metrics: &[FontMetrics]) -> FontMetrics {
+        let spans =
+            self.items.iter().map(|i| i.span).chain(self.items.is_empty().then_some(self.span));
+        spans.map(|s| span_metrics[s]).fold(FontMetrics::default(), |a, m| FontMetrics {
+            ascent: a.ascent.max(m.ascent),
+            descent: a.descent.max(m.descent),
+            line_gap: a.line_gap.max(m.line_gap),
+        })
+    }
+}
+
+/// Get width of chars.
+fn line_width(items: &[Item]) -> f64 {
+    items.iter().enumerate().map(|(n, i)| if n == 0 { i.advance } else { i.kern + i.advance }).sum()
+}
+
+/// Get width of chars without trailing whitespaces.
+fn content_width(items: &[Item]) -> f64 {
+    let len = items.iter().rposition(|i| !i.ch.is_whitespace()).map_or(0, |p| p + 1);
+    line_width(&items[..len])
+}
+
+/// Split chars into lines, that fit the width. Lines are broken after whitespaces, too long
+/// words are broken by chars. Zero width disables wrapping.
+fn wrap(items: &[Item], max_width: f64) -> Vec<Range<usize>> {
+    let mut lines = Vec::new();
+    let (mut start, mut x, mut line_break) = (0, 0.0, None);
+    let mut i = 0;
+    while i < items.len() {
+        let item = items[i];
+        let w = if i > start { item.kern + item.advance } else { item.advance };
+        if max_width > 0.0 && i > start && x + w > max_width && !item.ch.is_whitespace() {
+            let end = line_break.take().unwrap_or(i);
+            lines.push(start..end);
+            start = end;
+            x = line_width(&items[start..i]);
+            continue;
+        }
+        x += w;
+        if item.ch.is_whitespace() {
+            line_break = Some(i + 1);
+        }
+        i += 1;
+    }
+    lines.push(start..items.len());
+    lines
+}
+
+/// Get ellipsis chars with their advances: `…` or `...` if font has no `…` glyph.
+fn ellipsis<M: FontMeasure + ?Sized>(style: &TextStyle, measure: &mut M) -> Vec<(char, f64)> {
+    let advance = measure.advance(style.font, style.size, '…');
+    if advance > 0.0 {
+        vec![('…', advance)]
+    } else {
+        vec![('.', measure.advance(style.font, style.size, '.')); 3]
+    }
+}
+
+/// Parse markup tag and update style stacks. Returns `false` if tag is not recognized.
+fn parse_tag(tag: &str, colors: &mut Vec<Color>, sizes: &mut Vec<f64>) -> bool {
+    match tag.split_once('=') {
+        Some(("color", value)) => Color::from_str(value).map(|c| colors.push(c)).is_ok(),
+        Some(("size", value)) => value.parse().map(|s| sizes.push(s)).is_ok(),
+        None if tag == "/color" => colors.pop().is_some(),
+        None if tag == "/size" => sizes.pop().is_some(),
+        _ => false,
+    }
+}
+
+/// Parse text with simple markup into spans.
+///
+/// Supported tags: `[color=#RRGGBB]`, `[color=#RRGGBBAA]`, `[size=N]` and closing `[/color]`,
+/// `[/size]`. `[[` is literal `[`. Not recognized tags are kept as text.
+#[must_use]
+pub fn parse_markup(text: &[char], style: &TextStyle) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let (mut colors, mut sizes) = (Vec::new(), Vec::new());
+    let mut current = Span { text: Vec::new(), style: *style };
+    let mut i = 0;
+    while i < text.len() {
+        let tag_len = (text[i] == '[' && text.get(i + 1) != Some(&'['))
+            .then(|| text[i..].iter().position(|c| *c == ']'))
+            .flatten();
+        let tag = tag_len.map(|len| text[i + 1..i + len].iter().collect::<String>());
+        match (tag_len, tag) {
+            (Some(len), Some(tag)) if parse_tag(&tag, &mut colors, &mut sizes) => {
+                let style = TextStyle {
+                    size: sizes.last().copied().unwrap_or(style.size),
+                    color: colors.last().copied().unwrap_or(style.color),
+                    ..*style
+                };
+                let prev = std::mem::replace(&mut current, Span { text: Vec::new(), style });
+                if !prev.text.is_empty() {
+                    spans.push(prev);
+                }
+                i += len + 1;
+            }
+            _ => {
+                current.text.push(text[i]);
+                i += if text[i] == '[' && text.get(i + 1) == Some(&'[') { 2 } else { 1 };
+            }
+        }
+    }
+    if !current.text.is_empty() || spans.is_empty() {
+        spans.push(current);
+    }
+    spans
+}
+
+/// Split text into paragraphs by line breaks. Returns paragraphs and text length.
+fn paragraphs<M: FontMeasure + ?Sized>(spans: &[Span], measure: &mut M) -> (Vec<RawLine>, usize) {
+    let mut paragraphs = vec![RawLine { items: Vec::new(), start: 0, span: 0, ellipsis: false }];
+    let mut index = 0;
+    let mut prev: Option<(&TextStyle, char)> = None;
+    for (span_index, span) in spans.iter().enumerate() {
+        let style = &span.style;
+        for &ch in &span.text {
+            index += 1;
+            if ch == '\n' {
+                let p =
+                    RawLine { items: Vec::new(), start: index, span: span_index, ellipsis: false };
+                paragraphs.push(p);
+                prev = None;
+                continue;
+            }
+            let kern = match prev {
+                Some((prev_style, prev_ch))
+                    if prev_style.font.0 == style.font.0
+                        && (prev_style.size - style.size).abs() < f64::EPSILON =>
+                {
+                    measure.kern(style.font, style.size, prev_ch, ch)
+                }
+                _ => 0.0,
+            };
+            let advance = measure.advance(style.font, style.size, ch);
+            let item = Item { index: index - 1, ch, span: span_index, kern, advance };
+            if let Some(p) = paragraphs.last_mut() {
+                p.items.push(item);
+            }
+            prev = Some((style, ch));
+        }
+    }
+    (paragraphs, index)
+}
+
+/// Get range of lines, that fit the rectangle height.
+///
+/// The first kept line is always visible. Lines heights are pairs of line height without gap
+/// and step to the next line.
+fn visible_lines(heights: &[(f64, f64)], max_height: f64, front: bool) -> Range<usize> {
+    let mut y = 0.0;
+    let mut count = 0;
+    let ordered: Box<dyn Iterator<Item = &(f64, f64)>> =
+        if front { Box::new(heights.iter().rev()) } else { Box::new(heights.iter()) };
+    for (height, step) in ordered {
+        if count > 0 && max_height > 0.0 && y + height > max_height {
+            break;
+        }
+        y += step;
+        count += 1;
+    }
+    if front {
+        heights.len() - count..heights.len()
+    } else {
+        0..count
+    }
+}
+
+/// Remove chars, that don't fit the width with ellipsis, from the line end (or start if `front`)
+/// and mark line by ellipsis.
+fn add_ellipsis<M: FontMeasure + ?Sized>(
+    line: &mut RawLine,
+    spans: &[Span],
+    max_width: f64,
+    front: bool,
+    measure: &mut M,
+) {
+    let span =
+        if front { line.items.first() } else { line.items.last() }.map_or(line.span, |i| i.span);
+    let ellipsis_width: f64 = ellipsis(&spans[span].style, measure).iter().map(|e| e.1).sum();
+    while !line.items.is_empty()
+        && ((!front && line.items.last().is_some_and(|i| i.ch.is_whitespace()))
+            || (max_width > 0.0 && content_width(&line.items) + ellipsis_width > max_width))
+    {
+        if front {
+            line.items.remove(0);
+        } else {
+            line.items.pop();
+        }
+    }
+    if let Some(first) = line.items.first_mut() {
+        first.kern = 0.0;
+        line.start = first.index;
+    }
+    line.span = span;
+    line.ellipsis = true;
+}
+
+/// Get alignment offset of content with specified size in the available space.
+/// Zero space disables alignment.
+fn align_offset(space: f64, size: f64, align: f64) -> f64 {
+    if space > 0.0 {
+        (space - size) * align
+    } else {
+        0.0
+    }
+}
+
+/// Layout styled text in the rectangle. Zero rectangle width disables wrapping and horizontal
+/// alignment, zero height disables truncation and vertical alignment.
+#[must_use]
+pub fn layout<M: FontMeasure + ?Sized>(
+    spans: &[Span],
+    rect: &Rectf,
+    options: &LayoutOptions,
+    measure: &mut M,
+) -> TextLayout {
+    if spans.is_empty() {
+        return TextLayout::default();
+    }
+    let metrics: Vec<_> =
+        spans.iter().map(|s| measure.metrics(s.style.font, s.style.size)).collect();
+    let front = matches!(options.truncate, TextTruncateMode::Front);
+
+    let (paragraphs, total) = paragraphs(spans, measure);
+    let max_width = if options.wrap { rect.w } else { 0.0 };
+    let mut lines = Vec::new();
+    for p in paragraphs {
+        for range in wrap(&p.items, max_width) {
+            let items = p.items[range].to_vec();
+            let (start, span) = items.first().map_or((p.start, p.span), |i| (i.index, i.span));
+            lines.push(RawLine { items, start, span, ellipsis: false });
+        }
+    }
+
+    let line_metrics: Vec<_> = lines.iter().map(|l| l.metrics(&metrics)).collect();
+    let heights: Vec<_> = line_metrics
+        .iter()
+        .map(|m| (m.ascent + m.descent, m.line_height() * options.line_spacing))
+        .collect();
+
+    let visible = visible_lines(&heights, rect.h, front);
+    let is_truncated = visible.len() < lines.len();
+    let line_metrics = &line_metrics[visible.clone()];
+    let heights = &heights[visible.clone()];
+    let mut lines: Vec<_> = lines.drain(visible).collect();
+
+    if options.ellipsis {
+        let edge = if front { 0 } else { lines.len() - 1 };
+        for (n, line) in lines.iter_mut().enumerate() {
+            let overflow = !options.wrap && rect.w > 0.0 && content_width(&line.items) > rect.w;
+            if overflow || (is_truncated && n == edge) {
+                add_ellipsis(line, spans, rect.w, front, measure);
+            }
+        }
+    }
+    let truncated = if front {
+        lines.first().map_or(0, |l| l.start)
+    } else {
+        lines.last().map_or(total, |l| total - l.end())
+    };
+
+    let content_h: f64 = heights.iter().map(|h| h.1).sum();
+    let mut y = rect.y + align_offset(rect.h, content_h, options.v_align.factor());
+    let mut result = TextLayout { truncated, ..TextLayout::default() };
+    for ((line, m), (_, step)) in lines.iter().zip(line_metrics).zip(heights) {
+        let mut items = line.items.clone();
+        if line.ellipsis {
+            let style = &spans[line.span].style;
+            let e = ellipsis(style, measure).into_iter().map(|(ch, advance)| Item {
+                index: usize::MAX,
+                ch,
+                span: line.span,
+                kern: 0.0,
+                advance,
+            });
+            if front {
+                items.splice(0..0, e);
+            } else {
+                items.extend(e);
+            }
+        }
+        let width = content_width(&items);
+        let mut x = rect.x + align_offset(rect.w, width, options.h_align.factor());
+        let line_rect = Rectf::from([x, y, width, *step]);
+        let baseline = y + m.ascent;
+        let first = result.glyphs.len();
+        for (n, item) in items.iter().enumerate() {
+            if n > 0 {
+                x += item.kern;
+            }
+            result.glyphs.push(PositionedGlyph {
+                index: (item.index != usize::MAX).then_some(item.index),
+                ch: item.ch,
+                pos: Vec2f::new(x, baseline),
+                advance: item.advance,
+                style: spans[item.span].style,
+            });
+            x += item.advance;
+        }
+        result.lines.push(Line {
+            chars: line.start..line.end(),
+            glyphs: first..result.glyphs.len(),
+            rect: line_rect,
+            baseline,
+        });
+        y += step;
+    }
+    let left = result.lines.iter().map(|l| l.rect.x).fold(f64::INFINITY, f64::min);
+    let right = result.lines.iter().map(|l| l.rect.x + l.rect.w).fold(f64::NEG_INFINITY, f64::max);
+    if let (Some(first), Some(last)) = (result.lines.first(), result.lines.last()) {
+        let top = first.rect.y;
+        result.bounds = [left, top, right - left, last.rect.y + last.rect.h - top].into();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{layout, parse_markup, FontMeasure, HAlign, LayoutOptions, Span, TextStyle};
+    use crate::{FontMetrics, TextTruncateMode};
+    use resources::FontId;
+    use utils::{color::Color, rect::Rectf, vec2::Vec2f};
+
+    /// Monospace font: advance is half of size, ellipsis is not supported.
+    struct Mono;
+
+    impl FontMeasure for Mono {
+        fn metrics(&mut self, _font: FontId, size: f64) -> FontMetrics {
+            FontMetrics { ascent: size * 0.8, descent: size * 0.2, line_gap: 0.0 }
+        }
+
+        fn advance(&mut self, _font: FontId, size: f64, ch: char) -> f64 {
+            if ch == '…' {
+                0.0
+            } else {
+                size / 2.0
+            }
+        }
+
+        fn kern(&mut self, _font: FontId, _size: f64, _prev: char, _ch: char) -> f64 {
+            0.0
+        }
+    }
+
+    /// Make single span text with size 10.
+    fn text(s: &str) -> Vec<Span> {
+        vec![Span { text: s.chars().collect(), style: style() }]
+    }
+
+    /// Default test style.
+    const fn style() -> TextStyle {
+        TextStyle { font: FontId(0), size: 10.0, color: Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 } }
+    }
+
+    /// Check that values are equal with some precision.
+    fn assert_near(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    #[test]
+    fn word_wrap() {
+        let rect = Rectf::from([0.0, 0.0, 40.0, 0.0]);
+        let l = layout(&text("hello world"), &rect, &LayoutOptions::default(), &mut Mono);
+        let lines: Vec<_> = l.lines.iter().map(|l| l.chars.clone()).collect();
+        assert_eq!(lines, [0..6, 6..11]);
+        assert_near(l.lines[1].baseline, 18.0);
+
+        let rect = Rectf::from([0.0, 0.0, 20.0, 0.0]);
+        let l = layout(&text("abcdefghij"), &rect, &LayoutOptions::default(), &mut Mono);
+        let lines: Vec<_> = l.lines.iter().map(|l| l.chars.clone()).collect();
+        assert_eq!(lines, [0..4, 4..8, 8..10]);
+    }
+
+    #[test]
+    fn align() {
+        let rect = Rectf::from([10.0, 0.0, 100.0, 0.0]);
+        let options = LayoutOptions { h_align: HAlign::Center, ..LayoutOptions::default() };
+        let l = layout(&text("ab "), &rect, &options, &mut Mono);
+        assert_near(l.glyphs[0].pos.x, 55.0);
+        let options = LayoutOptions { h_align: HAlign::Right, ..LayoutOptions::default() };
+        let l = layout(&text("ab"), &rect, &options, &mut Mono);
+        assert_near(l.glyphs[1].pos.x, 105.0);
+    }
+
+    #[test]
+    fn line_spacing() {
+        let options = LayoutOptions { line_spacing: 2.0, ..LayoutOptions::default() };
+        let l = layout(&text("a\n\nb"), &[0.0; 4].into(), &options, &mut Mono);
+        let baselines: Vec<_> = l.lines.iter().map(|l| l.baseline).collect();
+        assert_eq!(l.lines.len(), 3);
+        assert_near(baselines[1], 28.0);
+        assert_near(baselines[2], 48.0);
+        assert_eq!(l.lines[1].chars, 2..2);
+    }
+
+    #[test]
+    fn truncate() {
+        let rect = Rectf::from([0.0, 0.0, 25.0, 25.0]);
+        let options = LayoutOptions { ellipsis: true, ..LayoutOptions::default() };
+        let l = layout(&text("aaaa bbbb cccc"), &rect, &options, &mut Mono);
+        assert_eq!(l.lines.len(), 2);
+        assert_eq!(l.truncated, 7);
+        let last: String = l.glyphs[l.lines[1].glyphs.clone()].iter().map(|g| g.ch).collect();
+        assert_eq!(last, "bb...");
+
+        let options = LayoutOptions { truncate: TextTruncateMode::Front, ..options };
+        let l = layout(&text("aaaa bbbb cccc"), &rect, &options, &mut Mono);
+        assert_eq!(l.lines[0].chars, 7..10);
+        assert_eq!(l.truncated, 7);
+    }
+
+    #[test]
+    fn markup() {
+        let chars: Vec<_> = "a[color=#ff0000]b[size=20]c[/size][/color][[d[e".chars().collect();
+        let spans = parse_markup(&chars, &style());
+        let texts: Vec<String> = spans.iter().map(|s| s.text.iter().collect()).collect();
+        assert_eq!(texts, ["a", "b", "c", "[d[e"]);
+        assert_near(f64::from(spans[1].style.color.r), 1.0);
+        assert_near(spans[2].style.size, 20.0);
+        assert_near(spans[3].style.size, 10.0);
+    }
+
+    #[test]
+    fn hit_test() {
+        let l = layout(&text("abcd\nef"), &[0.0; 4].into(), &LayoutOptions::default(), &mut Mono);
+        assert_eq!(l.hit_test(Vec2f::new(12.0, 5.0)), 2);
+        assert_eq!(l.hit_test(Vec2f::new(100.0, 5.0)), 4);
+        assert_eq!(l.hit_test(Vec2f::new(6.0, 15.0)), 6);
+        assert_near(l.caret_rect(4).map_or(0.0, |r| r.x), 20.0);
+        let rects = l.selection_rects(2..6);
+        assert_eq!(rects.len(), 2);
+        assert_near(rects[0].w, 10.0);
+        assert_near(rects[1].w, 5.0);
+    }
+}
@@ -3,7 +3,9 @@
 //! Resource Manager manages the resources needed for GUI operation (textures, sounds, etc.).
 
 use error_stack::Result;
-use std::path::Path;
+use std::{path::Path, str::FromStr};
+
+pub mod strings;
 
 /// Resource manger error.
 #[derive(Debug, thiserror::Error)]
@@ -24,13 +26,119 @@ pub struct TextureId(pub usize);
 #[derive(Clone, Copy)]
 pub struct FontId(pub usize);
 
+/// Shader identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShaderId(pub usize);
+
+/// Texture filtering.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Filter {
+    /// Nearest pixel, used for pixel art.
+    #[default]
+    Nearest,
+    /// Linear interpolation.
+    Linear,
+}
+
+impl FromStr for Filter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(Self::Nearest),
+            "linear" => Ok(Self::Linear),
+            _ => Err(Error::msg(format!("Unexpected texture filter: {s:?}"))),
+        }
+    }
+}
+
+/// Texture wrap mode. Used when texture coordinates are out of texture bounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Wrap {
+    /// Repeat texture edge pixels.
+    #[default]
+    ClampToEdge,
+    /// Repeat texture.
+    Repeat,
+    /// Repeat texture, mirroring it on each repeat.
+    MirroredRepeat,
+}
+
+impl FromStr for Wrap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(Self::ClampToEdge),
+            "repeat" => Ok(Self::Repeat),
+            "mirrored_repeat" => Ok(Self::MirroredRepeat),
+            _ => Err(Error::msg(format!("Unexpected texture wrap mode: {s:?}"))),
+        }
+    }
+}
+
+/// Font style.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    /// Regular font.
+    #[default]
+    Regular,
+    /// Bold font.
+    Bold,
+    /// Italic font.
+    Italic,
+    /// Bold and italic font.
+    BoldItalic,
+}
+
+impl FromStr for FontStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "regular" => Ok(Self::Regular),
+            "bold" => Ok(Self::Bold),
+            "italic" => Ok(Self::Italic),
+            "bold_italic" => Ok(Self::BoldItalic),
+            _ => Err(Error::msg(format!("Unexpected font style: {s:?}"))),
+        }
+    }
+}
+
+/// Resource loading options.
+/// Texture options are also applied to font glyph textures.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct LoadOptions {
+    /// Texture filtering.
+    pub filter: Filter,
+    /// Texture wrap mode.
+    pub wrap: Wrap,
+    /// Generate mipmaps.
+    pub mipmaps: bool,
+    /// Texture stores sRGB colors, that should be converted to linear on sampling.
+    pub srgb: bool,
+    /// Texture colors are premultiplied by alpha. Such textures are converted on loading.
+    pub premultiplied_alpha: bool,
+    /// Font style. Fonts loaded with the same name and different styles make a font family.
+    pub style: FontStyle,
+    /// Names of fonts, that used to draw glyphs missed in the font.
+    pub fallback: Vec<String>,
+}
+
 /// Resource manager.
 pub trait Manager {
     /// Load specified resource.
     ///
     /// # Errors
     /// Return error if failed to load specified resource.
-    fn load(&mut self, kind: &str, name: &str, path: &Path) -> Result<(), Error>;
+    fn load(
+        &mut self,
+        kind: &str,
+        name: &str,
+        path: &Path,
+        opts: &LoadOptions,
+    ) -> Result<(), Error>;
 
     /// Get texture identifier by name.
     ///
@@ -40,11 +148,60 @@ pub trait Manager {
     /// Return error if the specified texture was not loaded.
     fn get_texture(&self, name: &str) -> Result<TextureId, Error>;
 
-    /// Get font identifier by name.
+    /// Create transparent texture with specified size, that can be used as render target.
+    /// Created texture is accessible by name like loaded one.
+    ///
+    /// # Errors
+    /// Return error if render target can not be created.
+    fn create_render_target(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<TextureId, Error>;
+
+    /// Get identifier of regular font by font family name.
+    /// Any other loaded style is returned if the family has no regular font.
     ///
-    /// Before use, font must be loaded with kind: "font".
+    /// Before use, font must be loaded with kind: "font" or "bitmap_font".
     ///
     /// # Errors
     /// Return error if the specified font was not loaded.
     fn get_font(&self, name: &str) -> Result<FontId, Error>;
+
+    /// Get identifier of font with specified style by font family name.
+    /// If the family has no font with requested style, regular font is returned,
+    /// then any other loaded style.
+    ///
+    /// Before use, font must be loaded with kind: "font" or "bitmap_font".
+    ///
+    /// # Errors
+    /// Return error if the specified font was not loaded.
+    fn get_font_style(&self, name: &str, style: FontStyle) -> Result<FontId, Error>;
+
+    /// Get shader identifier by name.
+    ///
+    /// Before use, fragment shader source must be loaded with kind: "shader".
+    ///
+    /// # Errors
+    /// Return error if the specified shader was not loaded.
+    fn get_shader(&self, name: &str) -> Result<ShaderId, Error>;
+
+    /// Set current locale of localized strings.
+    ///
+    /// Before use, string table must be loaded with kind: "strings" and locale as name.
+    ///
+    /// # Errors
+    /// Return error if no string table loaded for the locale.
+    fn set_locale(&mut self, locale: &str) -> Result<(), Error>;
+
+    /// Get current locale of localized strings.
+    fn get_locale(&self) -> &str;
+
+    /// Get localized string by key and substitute arguments (`{name}` placeholders).
+    /// Plural form is selected by `count` argument, see [`strings`].
+    ///
+    /// # Errors
+    /// Return error if the specified string was not loaded.
+    fn get_string(&self, key: &str, args: &[(&str, &str)]) -> Result<String, Error>;
 }
@@ -0,0 +1,237 @@
+//! Localized string tables.
+//!
+//! String table file contains one `key = value` entry per line.
+//! Empty lines and lines starting with `#` are ignored.
+//! `\n` in value is replaced with line break.
+//!
+//! Value may contain argument placeholders: `{name}`. Use `{{` and `}}` for literal braces.
+//!
+//! Plural forms are stored as separate keys with plural category suffix:
+//! ```text
+//! files.one = {count} file
+//! files.other = {count} files
+//! ```
+//! Category is selected by the `count` argument according to the locale plural rules.
+
+use super::Error;
+use error_stack::{bail, report, Result};
+use std::collections::HashMap;
+
+/// Argument, that used to select plural form.
+const COUNT_ARG: &str = "count";
+
+/// String table of the one locale.
+#[derive(Default)]
+pub struct StringTable {
+    /// Strings by key.
+    entries: HashMap<String, String>,
+}
+
+impl StringTable {
+    /// Parse string table and add its entries. Existing entries are replaced.
+    ///
+    /// # Errors
+    /// Return error if source contains invalid line.
+    pub fn parse(&mut self, src: &str) -> Result<(), Error> {
+        for (i, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, val)) = line.split_once('=') else {
+                bail!(Error::msg(format!("Failed to parse string table line {}: {line:?}", i + 1)));
+            };
+            self.entries.insert(key.trim().to_owned(), val.trim().replace("\\n", "\n"));
+        }
+        Ok(())
+    }
+
+    /// Get string by key.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+}
+
+/// String tables of all locales.
+#[derive(Default)]
+pub struct Strings {
+    /// Current locale.
+    locale: String,
+    /// Locale, that used for keys missed in current locale. It is the first loaded locale.
+    fallback: String,
+    /// Tables by locale.
+    tables: HashMap<String, StringTable>,
+}
+
+impl Strings {
+    /// Load string table for locale. Tables loaded for the same locale are merged.
+    /// The first loaded locale becomes current and fallback one.
+    ///
+    /// # Errors
+    /// Return error if failed to parse table.
+    pub fn load(&mut self, locale: &str, src: &str) -> Result<(), Error> {
+        self.tables.entry(locale.to_owned()).or_default().parse(src)?;
+        if self.fallback.is_empty() {
+            self.fallback = locale.to_owned();
+            self.locale = locale.to_owned();
+        }
+        Ok(())
+    }
+
+    /// Set current locale.
+    ///
+    /// # Errors
+    /// Return error if no string table loaded for the locale.
+    pub fn set_locale(&mut self, locale: &str) -> Result<(), Error> {
+        if !self.tables.contains_key(locale) {
+            bail!(Error::msg(format!("No string table for locale {locale:?}")));
+        }
+        locale.clone_into(&mut self.locale);
+        Ok(())
+    }
+
+    /// Get current locale.
+    #[must_use]
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Get string by key in current locale and substitute arguments.
+    ///
+    /// # Errors
+    /// Return error if key not found in current and fallback locales.
+    pub fn get(&self, key: &str, args: &[(&str, &str)]) -> Result<String, Error> {
+        let src = self
+            .lookup(&self.locale, key, args)
+            .or_else(|| self.lookup(&self.fallback, key, args))
+            .ok_or_else(|| report!(Error::msg(format!("String {key:?} not found"))))?;
+        Ok(format(src, args))
+    }
+
+    /// Find string in specified locale table. Plural form is chosen by `count` argument.
+    fn lookup(&self, locale: &str, key: &str, args: &[(&str, &str)]) -> Option<&str> {
+        let table = self.tables.get(locale)?;
+        let count = args.iter().find(|(name, _)| *name == COUNT_ARG);
+        if let Some(n) = count.and_then(|(_, val)| val.parse::<f64>().ok()) {
+            let category = plural_category(locale, n);
+            if let Some(s) = table
+                .get(&format!("{key}.{category}"))
+                .or_else(|| table.get(&format!("{key}.other")))
+            {
+                return Some(s);
+            }
+        }
+        table.get(key)
+    }
+}
+
+/// Substitute arguments into placeholders. Unknown placeholders are kept as is.
+fn format(src: &str, args: &[(&str, &str)]) -> String {
+    let mut res = String::with_capacity(src.len());
+    let mut rest = src;
+    while let Some(pos) = rest.find(['{', '}']) {
+        res.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        if let Some(r) = rest.strip_prefix("{{").or_else(|| rest.strip_prefix("}}")) {
+            res.push_str(&rest[..1]);
+            rest = r;
+            continue;
+        }
+        let arg = rest.strip_prefix('{').and_then(|r| {
+            let end = r.find('}')?;
+            let val = args.iter().find(|(name, _)| *name == &r[..end])?.1;
+            Some((val, &r[end + 1..]))
+        });
+        if let Some((val, r)) = arg {
+            res.push_str(val);
+            rest = r;
+        } else {
+            res.push_str(&rest[..1]);
+            rest = &rest[1..];
+        }
+    }
+    res.push_str(rest);
+    res
+}
+
+/// Get plural category for number in specified locale.
+/// Rules cover common languages, other languages use English rules.
+fn plural_category(locale: &str, n: f64) -> &'static str {
+    let lang = locale.split(['-', '_']).next().unwrap_or_default();
+    if n.fract() != 0.0 || n < 0.0 {
+        return "other";
+    }
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let i = n as u64;
+    let (m10, m100) = (i % 10, i % 100);
+    let few = (2..=4).contains(&m10) && !(12..=14).contains(&m100);
+    match lang {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" => "other",
+        "fr" if i <= 1 => "one",
+        "ru" | "uk" | "be" | "sr" | "hr" | "bs" => {
+            if m10 == 1 && m100 != 11 {
+                "one"
+            } else if few {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "pl" => {
+            if i == 1 {
+                "one"
+            } else if few {
+                "few"
+            } else {
+                "many"
+            }
+        }
+        "cs" | "sk" => match i {
+            1 => "one",
+            2..=4 => "few",
+            _ => "other",
+        },
+        _ if i == 1 => "one",
+        _ => "other",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, plural_category, Strings};
+
+    #[test]
+    fn format_args() {
+        assert_eq!(format("Hello, {name}!", &[("name", "Bob")]), "Hello, Bob!");
+        assert_eq!(format("{{name}} {x}", &[("name", "Bob")]), "{name} {x}");
+    }
+
+    #[test]
+    fn plural() {
+        assert_eq!(plural_category("en", 1.0), "one");
+        assert_eq!(plural_category("en", 2.0), "other");
+        assert_eq!(plural_category("ru_RU", 21.0), "one");
+        assert_eq!(plural_category("ru", 13.0), "many");
+        assert_eq!(plural_category("ru", 24.0), "few");
+        assert_eq!(plural_category("fr", 0.0), "one");
+    }
+
+    #[test]
+    fn locales() {
+        let mut s = Strings::default();
+        s.load("en", "# comment\n\nmenu.start = Start\nmenu.exit = Exit").unwrap();
+        s.load("en", "files.one = {count} file\nfiles.other = {count} files").unwrap();
+        s.load("ru", "menu.start = Старт\nfiles.one = {count} файл").unwrap();
+        s.load("ru", "files.few = {count} файла\nfiles.many = {count} файлов").unwrap();
+        assert_eq!(s.get("menu.start", &[]).unwrap(), "Start");
+        assert_eq!(s.get("files", &[("count", "2")]).unwrap(), "2 files");
+        s.set_locale("ru").unwrap();
+        assert_eq!(s.get("menu.start", &[]).unwrap(), "Старт");
+        assert_eq!(s.get("files", &[("count", "3")]).unwrap(), "3 файла");
+        assert_eq!(s.get("menu.exit", &[]).unwrap(), "Exit");
+        assert!(s.get("missed", &[]).is_err());
+        assert!(s.set_locale("de").is_err());
+        assert!(s.load("en", "invalid line").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! Texture atlas.
+//!
+//! Packs small textures into shared pages, so many sprites use one GL texture.
+
+use image::{GenericImage, RgbaImage};
+use resources::{Filter, LoadOptions, Wrap};
+
+/// Gap between packed images in pixels. The gap is transparent, so only images with nearest
+/// filtering, that never sample it, are packed.
+const PADDING: u32 = 1;
+
+/// Shelf (row) of the packer.
+struct Shelf {
+    /// Shelf top.
+    y: u32,
+    /// Shelf hight.
+    h: u32,
+    /// First free `x` coordinate.
+    x: u32,
+}
+
+/// Simple shelf bin packer.
+pub struct Packer {
+    /// Page size.
+    size: u32,
+    /// Allocated shelves.
+    shelves: Vec<Shelf>,
+}
+
+impl Packer {
+    /// Create packer for square page with specified size.
+    #[must_use]
+    pub const fn new(size: u32) -> Self {
+        Self { size, shelves: Vec::new() }
+    }
+
+    /// Allocate area with specified size.
+    /// Returns position of the allocated area or `None` if the page is full.
+    pub fn insert(&mut self, w: u32, h: u32) -> Option<(u32, u32)> {
+        let (pw, ph) = (w + PADDING, h + PADDING);
+        if pw > self.size || ph > self.size {
+            return None;
+        }
+        // Choose the lowest shelf, that fits the image, to waste less space.
+        let best = self
+            .shelves
+            .iter_mut()
+            .filter(|s| s.h >= ph && s.x + pw <= self.size)
+            .min_by_key(|s| s.h - ph);
+        if let Some(shelf) = best {
+            let pos = (shelf.x, shelf.y);
+            shelf.x += pw;
+            return Some(pos);
+        }
+        let y = self.shelves.last().map_or(0, |s| s.y + s.h);
+        if y + ph > self.size {
+            return None;
+        }
+        self.shelves.push(Shelf { y, h: ph, x: pw });
+        Some((0, y))
+    }
+}
+
+/// Atlas page.
+pub struct Page {
+    /// Page image, kept in memory to rebuild texture after packing new images.
+    pub image: RgbaImage,
+    /// Page texture index in resource manager.
+    pub texture: usize,
+    /// Load options of all textures packed into page.
+    pub opts: LoadOptions,
+    /// Page packer.
+    packer: Packer,
+    /// Is page texture need to be updated.
+    pub dirty: bool,
+}
+
+impl Page {
+    /// Create new empty page.
+    #[must_use]
+    pub fn new(size: u32, texture: usize, opts: LoadOptions) -> Self {
+        Self {
+            image: RgbaImage::new(size, size),
+            texture,
+            opts,
+            packer: Packer::new(size),
+            dirty: true,
+        }
+    }
+
+    /// Copy image into page.
+    /// Returns image position or `None` if page is full.
+    pub fn pack(&mut self, img: &RgbaImage) -> Option<(u32, u32)> {
+        let (x, y) = self.packer.insert(img.width(), img.height())?;
+        self.image.copy_from(img, x, y).ok()?;
+        self.dirty = true;
+        Some((x, y))
+    }
+}
+
+/// Texture atlas.
+pub struct Atlas {
+    /// Page size.
+    pub page_size: u32,
+    /// Allocated pages.
+    pub pages: Vec<Page>,
+}
+
+impl Atlas {
+    /// Create new atlas with specified page size.
+    #[must_use]
+    pub const fn new(page_size: u32) -> Self {
+        Self { page_size, pages: Vec::new() }
+    }
+
+    /// Check if image with specified size and options should be packed.
+    /// Big images and images, that sample outside own bounds (repeated, mipmapped or linearly
+    /// filtered), are kept as standalone textures.
+    #[must_use]
+    pub fn accepts(&self, w: u32, h: u32, opts: &LoadOptions) -> bool {
+        w <= self.page_size / 2
+            && h <= self.page_size / 2
+            && opts.filter == Filter::Nearest
+            && opts.wrap == Wrap::ClampToEdge
+            && !opts.mipmaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resources::{Filter, LoadOptions, Wrap};
+
+    use super::{Atlas, Packer};
+
+    #[test]
+    fn pack_shelves() {
+        let mut p = Packer::new(64);
+        assert_eq!(p.insert(31, 15), Some((0, 0)));
+        assert_eq!(p.insert(31, 15), Some((32, 0)));
+        assert_eq!(p.insert(31, 15), Some((0, 16)));
+        assert_eq!(p.insert(10, 5), Some((32, 16)));
+        assert_eq!(p.insert(63, 31), Some((0, 32)));
+        assert_eq!(p.insert(1, 1), Some((43, 16)));
+        assert_eq!(p.insert(64, 1), None);
+        assert_eq!(p.insert(20, 20), None);
+    }
+
+    #[test]
+    fn accepts_options() {
+        let atlas = Atlas::new(64);
+        let opts = LoadOptions::default();
+        assert!(atlas.accepts(32, 32, &opts));
+        assert!(!atlas.accepts(33, 32, &opts));
+        assert!(!atlas.accepts(16, 16, &LoadOptions { filter: Filter::Linear, ..opts.clone() }));
+        assert!(!atlas.accepts(16, 16, &LoadOptions { wrap: Wrap::Repeat, ..opts.clone() }));
+        assert!(!atlas.accepts(16, 16, &LoadOptions { mipmaps: true, ..opts }));
+    }
+}
@@ -0,0 +1,220 @@
+//! Bitmap font in `BMFont` (`AngelCode`) text format.
+
+use error_stack::{bail, report, Result, ResultExt};
+use std::{collections::HashMap, str::FromStr};
+
+/// Bitmap font char.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Char {
+    /// Char rectangle `x` on page texture.
+    pub x: f64,
+    /// Char rectangle `y` on page texture.
+    pub y: f64,
+    /// Char rectangle width.
+    pub width: f64,
+    /// Char rectangle hight.
+    pub height: f64,
+    /// Offset from pen position to the char left edge.
+    pub xoffset: f64,
+    /// Offset from line top to the char top edge.
+    pub yoffset: f64,
+    /// Horizontal advance.
+    pub xadvance: f64,
+    /// Page index.
+    pub page: usize,
+}
+
+/// Bitmap font.
+pub struct BitmapFont {
+    /// Font size, the font was generated with.
+    pub size: f64,
+    /// Distance between lines.
+    pub line_height: f64,
+    /// Distance from line top to baseline.
+    pub base: f64,
+    /// Page texture files, relative to font file.
+    pub page_files: Vec<String>,
+    /// Page texture indexes in resource manager.
+    pub pages: Vec<usize>,
+    /// Font chars.
+    pub chars: HashMap<char, Char>,
+    /// Kerning pairs.
+    pub kernings: HashMap<(char, char), f64>,
+}
+
+impl BitmapFont {
+    /// Parse font description.
+    /// Page textures are not loaded, fill [`BitmapFont::pages`] after loading
+    /// [`BitmapFont::page_files`].
+    ///
+    /// # Errors
+    /// Return error if description is not valid.
+    pub fn parse(src: &str) -> Result<Self, resources::Error> {
+        let mut font = Self {
+            size: 0.0,
+            line_height: 0.0,
+            base: 0.0,
+            page_files: Vec::new(),
+            pages: Vec::new(),
+            chars: HashMap::new(),
+            kernings: HashMap::new(),
+        };
+        for (i, line) in src.lines().enumerate() {
+            let err = || resources::Error::msg(format!("Failed to parse bitmap font line {i}"));
+            let mut tokens = tokenize(line);
+            let Some((tag, _)) = tokens.next() else {
+                continue;
+            };
+            let attrs: HashMap<_, _> = tokens.collect();
+            let get = |key: &str| -> Result<f64, resources::Error> {
+                let val = attrs.get(key).ok_or_else(|| {
+                    report!(resources::Error::msg(format!("Attribute {key:?} not found")))
+                })?;
+                f64::from_str(val).change_context(resources::Error::msg(format!(
+                    "Failed to parse attribute {key:?}: {val:?}"
+                )))
+            };
+            match tag {
+                "info" => font.size = get("size").change_context_lazy(err)?.abs(),
+                "common" => {
+                    font.line_height = get("lineHeight").change_context_lazy(err)?;
+                    font.base = get("base").change_context_lazy(err)?;
+                }
+                "page" => {
+                    let file = attrs
+                        .get("file")
+                        .ok_or_else(|| report!(err()).attach_printable("Page file not found"))?;
+                    font.page_files.push((*file).to_string());
+                }
+                "char" => {
+                    let ch =
+                        to_char(get("id").change_context_lazy(err)?).change_context_lazy(err)?;
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let page = get("page").change_context_lazy(err)? as usize;
+                    let c = Char {
+                        x: get("x").change_context_lazy(err)?,
+                        y: get("y").change_context_lazy(err)?,
+                        width: get("width").change_context_lazy(err)?,
+                        height: get("height").change_context_lazy(err)?,
+                        xoffset: get("xoffset").change_context_lazy(err)?,
+                        yoffset: get("yoffset").change_context_lazy(err)?,
+                        xadvance: get("xadvance").change_context_lazy(err)?,
+                        page,
+                    };
+                    font.chars.insert(ch, c);
+                }
+                "kerning" => {
+                    let first =
+                        to_char(get("first").change_context_lazy(err)?).change_context_lazy(err)?;
+                    let second = to_char(get("second").change_context_lazy(err)?)
+                        .change_context_lazy(err)?;
+                    font.kernings.insert((first, second), get("amount").change_context_lazy(err)?);
+                }
+                _ => {}
+            }
+        }
+        if let Some((ch, c)) = font.chars.iter().find(|(_, c)| c.page >= font.page_files.len()) {
+            bail!(resources::Error::msg(format!(
+                "Char {ch:?} refers to page {}, but font has {} pages",
+                c.page,
+                font.page_files.len()
+            )));
+        }
+        if font.size == 0.0 {
+            font.size = font.line_height;
+        }
+        Ok(font)
+    }
+}
+
+/// Convert char code to char.
+fn to_char(code: f64) -> Result<char, resources::Error> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    char::from_u32(code as u32)
+        .ok_or_else(|| report!(resources::Error::msg(format!("Invalid char code: {code}"))))
+}
+
+/// Split description line into tag and attributes.
+/// The first item is the line tag with empty value, other items are `key=value` pairs.
+/// Quotes around values are removed.
+fn tokenize(line: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = line.trim_start();
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let key_end = rest.find(|c: char| c == '=' || c.is_whitespace()).unwrap_or(rest.len());
+        let key = &rest[..key_end];
+        rest = &rest[key_end..];
+        let mut val = "";
+        if let Some(r) = rest.strip_prefix('=') {
+            if let Some(r) = r.strip_prefix('"') {
+                let end = r.find('"').unwrap_or(r.len());
+                val = &r[..end];
+                rest = r.get(end + 1..).unwrap_or("");
+            } else {
+                let end = r.find(char::is_whitespace).unwrap_or(r.len());
+                val = &r[..end];
+                rest = &r[end..];
+            }
+        }
+        rest = rest.trim_start();
+        Some((key, val))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitmapFont, Char};
+
+    #[test]
+    fn parse_font() {
+        let font = BitmapFont::parse(
+            r#"info face="Pixel Font" size=-16 bold=0 italic=0
+common lineHeight=18 base=14 scaleW=128 scaleH=128 pages=1 packed=0
+page id=0 file="pixel font_0.png"
+chars count=2
+char id=65   x=0     y=0     width=7     height=9     xoffset=0     yoffset=5     xadvance=8     page=0  chnl=15
+char id=86   x=8     y=0     width=7     height=9     xoffset=0     yoffset=5     xadvance=8     page=0  chnl=15
+kernings count=1
+kerning first=65  second=86  amount=-1
+"#,
+        )
+        .unwrap();
+        assert_eq!(font.size, 16.0);
+        assert_eq!(font.line_height, 18.0);
+        assert_eq!(font.base, 14.0);
+        assert_eq!(font.page_files, vec!["pixel font_0.png".to_string()]);
+        assert_eq!(
+            font.chars.get(&'V'),
+            Some(&Char {
+                x: 8.0,
+                y: 0.0,
+                width: 7.0,
+                height: 9.0,
+                xoffset: 0.0,
+                yoffset: 5.0,
+                xadvance: 8.0,
+                page: 0
+            })
+        );
+        assert_eq!(font.kernings.get(&('A', 'V')), Some(&-1.0));
+        assert!(BitmapFont::parse("char id=65 x=0").is_err());
+    }
+
+    #[test]
+    fn parse_page_out_of_range() {
+        let src = r#"common lineHeight=18 base=14 pages=1
+page id=0 file="font_0.png"
+char id=65 x=0 y=0 width=7 height=9 xoffset=0 yoffset=5 xadvance=8 page=0
+char id=66 x=8 y=0 width=7 height=9 xoffset=0 yoffset=5 xadvance=8 page=1
+"#;
+        assert!(BitmapFont::parse(src).is_err());
+        let src = src.replace("page=1", "page=0");
+        assert!(BitmapFont::parse(&src).is_ok());
+        assert!(BitmapFont::parse(
+            "char id=65 x=0 y=0 width=7 height=9 xoffset=0 yoffset=5 xadvance=8 page=0"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,75 @@
+//! Frame buffer capture: screenshots and frame sequences.
+
+use error_stack::{Result, ResultExt};
+use image::RgbaImage;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Frame sequence capture settings and progress.
+pub struct FrameCapture {
+    /// Directory for numbered frames.
+    pub dir: PathBuf,
+    /// Captured frames per second of scene time.
+    pub fps: u32,
+    /// Number of the next frame.
+    pub frame: u32,
+}
+
+impl FrameCapture {
+    /// Path of the next frame.
+    pub fn next_path(&mut self) -> PathBuf {
+        let path = self.dir.join(format!("frame_{:06}.png", self.frame));
+        self.frame += 1;
+        path
+    }
+}
+
+/// Read pixels of currently bound frame buffer with specified size.
+/// All pending draws must be flushed before.
+pub fn read_frame(size: [u32; 2]) -> RgbaImage {
+    let [w, h] = size;
+    let mut pixels = vec![0_u8; w as usize * h as usize * 4];
+    // SAFETY: GL context is current and buffer has size of `w * h` RGBA pixels.
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        #[allow(clippy::cast_possible_wrap)]
+        gl::ReadPixels(
+            0,
+            0,
+            w as i32,
+            h as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr().cast(),
+        );
+    }
+    let mut img = RgbaImage::from_raw(w, h, pixels).unwrap_or_else(|| RgbaImage::new(w, h));
+    // Frame buffer rows go from bottom to top, and its alpha is not meaningful for a screenshot.
+    image::imageops::flip_vertical_in_place(&mut img);
+    for p in img.pixels_mut() {
+        p.0[3] = u8::MAX;
+    }
+    img
+}
+
+/// Save image as PNG file. Parent directories are created if needed.
+///
+/// # Errors
+/// Return error if failed to create directory or write file.
+pub fn save(img: &RgbaImage, path: &Path) -> Result<(), crate::Error> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).change_context(crate::Error::msg(format!(
+            "Failed to create capture directory {dir:?}"
+        )))?;
+    }
+    img.save(path).change_context(crate::Error::msg(format!("Failed to save capture {path:?}")))
+}
+
+/// Make unique screenshot path in specified directory.
+pub fn screenshot_path(dir: &Path) -> PathBuf {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    dir.join(format!("screenshot_{}_{:03}.png", time.as_secs(), time.subsec_millis()))
+}
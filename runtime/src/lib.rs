@@ -2,28 +2,40 @@
 //! dependent on it, in the future it will be rewritten using a low-level OpenGL API,
 //! so now it is full of crutches and questionable code.
 
+mod atlas;
+mod bmfont;
+mod capture;
 mod renderer;
 mod resmgr;
+mod shader;
 
-use ::renderer::Drawable;
+use ::renderer::{Drawable, Renderer as _};
 use builder::config::Config;
+use capture::FrameCapture;
 use error_stack::{ensure, Result, ResultExt};
 use glutin_window::GlutinWindow as Window;
 use graphics::clear;
-use gui::widget::{Graph, Label, Slider};
+use gui::widget::{Graph, Label};
 use gui::{manager::Manager as GuiMngr, widget::Builder as GuiBuilder};
-use opengl_graphics::{GlGraphics, GlyphCache, OpenGL, Texture, TextureSettings};
+use opengl_graphics::{GlGraphics, GlyphCache, OpenGL, Texture};
 use piston::event_loop::{EventSettings, Events};
 use piston::input::RenderEvent;
 use piston::window::WindowSettings;
-use piston::{Button, EventLoop, Key, Motion, UpdateEvent};
-use renderer::Renderer;
-use resmgr::ResMngr;
-use resources::{FontId, TextureId};
+use piston::{Button, EventLoop, Key, Motion, UpdateEvent, Window as _};
+use renderer::{Renderer, Stats};
+use resmgr::{texture_settings, Font, FontKind, ResMngr};
+use resources::{FontStyle, LoadOptions, Manager as _, ShaderId};
 use scene::event::{self, Event, KeyCode, MouseButton};
 use scene::TimeTick;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+/// Model property of ticks per second, that is bound to runtime GUI slider and label.
+const TPS_PROPERTY: &str = "runtime.tps";
+/// Default ticks per second.
+const DEFAULT_TICK_PER_SEC: f64 = 1000.0;
+
 /// Runtime error.
 #[derive(Debug, thiserror::Error)]
 #[error("{0}")]
@@ -45,6 +57,14 @@ pub struct Runtime {
     gui: GuiMngr,
     /// Runtime gui recourses.
     gui_res: ResMngr,
+    /// Scene texture atlas page size. If `None` textures are not packed.
+    atlas_page_size: Option<u32>,
+    /// Directory for screenshots taken by hotkey.
+    screenshot_dir: PathBuf,
+    /// Path of requested screenshot of the next frame.
+    screenshot: Option<PathBuf>,
+    /// Frame sequence capture. `None` if frames are not captured.
+    capture: Option<FrameCapture>,
 }
 
 impl Runtime {
@@ -61,41 +81,89 @@ impl Runtime {
         let mut gui_res = ResMngr::new();
         {
             // load default font
-            let id = FontId(gui_res.fonts.len());
-            let mut settings = TextureSettings::new();
-            settings.set_filter(opengl_graphics::Filter::Nearest);
             let cache = GlyphCache::from_bytes(
                 include_bytes!("./ubuntu.mono.ttf"),
                 (),
-                TextureSettings::new(),
+                texture_settings(&LoadOptions::default()),
             )
             .map_err(|e| Error::msg(format!("Failed to load font: {e:?}")))?;
-            gui_res.fonts.push(cache);
-            gui_res.fonts_map.insert("default".into(), id);
+            let font = Font {
+                kind: FontKind::Ttf(cache),
+                style: FontStyle::Regular,
+                fallback: Vec::new(),
+            };
+            gui_res.add_font("default", font);
         }
         {
             // load slider texture
-            let id = TextureId(gui_res.textures.len());
-            let mut settings = TextureSettings::new();
-            settings.set_filter(opengl_graphics::Filter::Nearest);
-            gui_res.textures.push(
-                Texture::from_bytes(include_bytes!("./slider.png"), &settings)
-                    .map_err(|e| Error::msg(format!("Failed to load texture: {e}")))?,
+            gui_res.add_texture(
+                "slider_texture",
+                Texture::from_bytes(
+                    include_bytes!("./slider.png"),
+                    &texture_settings(&LoadOptions::default()),
+                )
+                .map_err(|e| Error::msg(format!("Failed to load texture: {e}")))?,
             );
-            gui_res.textures_map.insert("slider_texture".into(), id);
         }
         let cfg = Config::from_json(include_str!("./gui_cfg.json"))
             .change_context(Error::msg("Failed to create runtime gui config"))?;
         let gui = GuiMngr::new(&GuiBuilder::default(), &mut gui_res, cfg)
             .change_context(Error::msg("Failed to load runtime gui"))?;
-        Ok(Self { window, gl, gui, gui_res })
+        Ok(Self {
+            window,
+            gl,
+            gui,
+            gui_res,
+            atlas_page_size: None,
+            screenshot_dir: PathBuf::from("screenshots"),
+            screenshot: None,
+            capture: None,
+        })
+    }
+
+    /// Enable packing of scene textures into shared atlas pages with specified size.
+    /// Textures bigger than half of the page are loaded as standalone textures.
+    #[must_use]
+    pub const fn with_texture_atlas(mut self, page_size: u32) -> Self {
+        self.atlas_page_size = Some(page_size);
+        self
+    }
+
+    /// Set directory for screenshots taken by F12 hotkey. Default is `"screenshots"`.
+    #[must_use]
+    pub fn with_screenshot_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.screenshot_dir = dir.into();
+        self
+    }
+
+    /// Enable frame sequence capture. Each rendered frame is saved into specified directory
+    /// as `frame_NNNNNN.png` and the scene is advanced by fixed time step of `1 / fps` second
+    /// instead of real time, so the captured sequence does not depend on the render speed.
+    /// Capture is paused and resumed by F11 hotkey. It is paused too, if a frame is not saved.
+    #[must_use]
+    pub fn with_frame_capture<P: Into<PathBuf>>(mut self, dir: P, fps: u32) -> Self {
+        self.capture = Some(FrameCapture { dir: dir.into(), fps: fps.max(1), frame: 0 });
+        self
+    }
+
+    /// Request screenshot of the next rendered frame. Screenshot contains the scene without
+    /// runtime debug gui and it is saved as PNG file.
+    pub fn screenshot<P: Into<PathBuf>>(&mut self, path: P) {
+        self.screenshot = Some(path.into());
     }
 
     /// Run runtime cycle.
+    /// Scene config may contain "post_process" chain of fragment shaders, that is applied to the
+    /// scene frame.
+    /// Hotkeys: F1 toggles debug gui, F11 pauses frame capture, F12 takes screenshot.
     ///
     /// # Errors
     /// Return error if some scene failed.
-    pub fn run(mut self, scene_builder: &scene::Builder, scene_cfg: Config) -> Result<(), Error> {
+    pub fn run(
+        mut self,
+        scene_builder: &scene::Builder,
+        mut scene_cfg: Config,
+    ) -> Result<(), Error> {
         let root = self
             .gui
             .get_by_id("root")
@@ -108,48 +176,106 @@ impl Runtime {
             .gui
             .get_by_id_cast::<Label>("fps_label")
             .change_context(Error::msg("Failed to get runtime fps label"))?;
-        let tps_label = self
-            .gui
-            .get_by_id_cast::<Label>("tps_label")
-            .change_context(Error::msg("Failed to get runtime tps label"))?;
-        let tps_slider = self
+        let stats_label = self
             .gui
-            .get_by_id_cast::<Slider>("tps_slider")
-            .change_context(Error::msg("Failed to get runtime tps slider"))?;
+            .get_by_id_cast::<Label>("stats_label")
+            .change_context(Error::msg("Failed to get runtime render stats label"))?;
+        let model = self.gui.model().clone();
+        model.set(TPS_PROPERTY, DEFAULT_TICK_PER_SEC);
 
         let mut events = Events::new(EventSettings::new());
         events.bench_mode(true);
         events.max_fps(100);
-        let mut state = State { next_scene: None, res: ResMngr::new() };
+        let res = self.atlas_page_size.map_or_else(ResMngr::new, ResMngr::with_atlas);
+        let mut state = State { next_scene: None, res };
+        let mut post_process = load_post_process(&mut scene_cfg, &mut state.res)?;
         let mut scene = scene_builder
             .build(scene_cfg, &mut state.res)
             .change_context(Error::msg("Failed to create first scene"))?;
+        let size = self.window.size();
+        let resize = Event::Resize(size.width, size.height);
+        scene
+            .handle_event(resize.clone(), &mut state)
+            .change_context(Error::msg("Scene failed to handle resize event"))?;
+        self.gui.handle_event(resize).change_context(Error::msg("Failed to resize runtime gui"))?;
 
         let mut fps_counter = 0;
         let mut fps_timer = Instant::now();
-        let mut tick_per_sec = tps_slider.borrow().get_value();
+        let mut tick_per_sec = DEFAULT_TICK_PER_SEC;
+        let mut capture_paused = false;
 
         while let Some(e) = events.next(&mut self.window) {
             if let Some(args) = e.render_args() {
-                self.gl.draw(args.viewport(), |c, g| {
+                let mut stats = Stats::default();
+                let capture = self.capture.as_mut().filter(|_| !capture_paused);
+                let screenshot = self.screenshot.take();
+                let frame = self.gl.draw(args.viewport(), |c, g| {
                     clear([1.0; 4], g);
-                    let mut renderer = Renderer { ctx: vec![c], g, res: &mut state.res };
-                    scene.draw(&mut renderer);
-                    let mut renderer = Renderer { ctx: vec![c], g, res: &mut self.gui_res };
-                    self.gui.draw(&mut renderer);
+                    {
+                        let mut renderer = Renderer::new(c, g, &mut state.res, &mut stats);
+                        renderer.begin_post_process(&post_process);
+                        clear([1.0; 4], renderer.g);
+                        scene.draw(&mut renderer);
+                        renderer.end_post_process();
+                    }
+                    let frame = (screenshot.is_some() || capture.is_some()).then(|| {
+                        g.draw_end();
+                        capture::read_frame(args.draw_size)
+                    });
+                    self.gui.draw(&mut Renderer::new(c, g, &mut self.gui_res, &mut stats));
+                    frame
                 });
+                if let Some(frame) = frame {
+                    if let Some(path) = screenshot {
+                        // Failed screenshot should not stop the game.
+                        if let Err(e) = capture::save(&frame, &path) {
+                            log::error!("Failed to save screenshot {}: {e:?}", path.display());
+                        }
+                    }
+                    if let Some(capture) = capture {
+                        // Failed frame is logged and capture is paused, so it can be resumed by
+                        // hotkey after the problem is fixed.
+                        let path = capture.next_path();
+                        if let Err(e) = capture::save(&frame, &path) {
+                            log::error!("Failed to save captured frame {}: {e:?}", path.display());
+                            capture_paused = true;
+                        } else {
+                            #[allow(clippy::cast_possible_truncation)]
+                            #[allow(clippy::cast_sign_loss)]
+                            let dt = (tick_per_sec / f64::from(capture.fps)).round() as TimeTick;
+                            scene
+                                .handle_event(event::Event::TimeTick(dt), &mut state)
+                                .change_context(Error::msg(
+                                    "Scene failed to handle update event",
+                                ))?;
+                        }
+                    }
+                }
 
                 fps_counter += 1;
                 if fps_timer.elapsed() >= Duration::from_secs_f32(0.1) {
                     let fps = f64::from(fps_counter) / fps_timer.elapsed().as_secs_f64();
-                    fps_graph.borrow_mut().push(fps);
-                    fps_label.borrow_mut().set_text(&format!("fps: {}", fps.round()));
+                    let tree = self.gui.tree_mut();
+                    if let Some(graph) = tree.cast_mut(fps_graph) {
+                        graph.push(fps);
+                    }
+                    if let Some(label) = tree.cast_mut(fps_label) {
+                        label.set_text(&format!("fps: {}", fps.round()));
+                    }
+                    if let Some(label) = tree.cast_mut(stats_label) {
+                        label.set_text(&format!(
+                            "draw calls: {}, vertices: {}, texture switches: {}",
+                            stats.draw_calls, stats.vertices, stats.texture_switches
+                        ));
+                    }
                     fps_counter = 0;
                     fps_timer = Instant::now();
                 }
             }
 
-            if let Some(e) = e.update_args() {
+            // Scene time is advanced by rendered frames while frame sequence is captured.
+            let is_capturing = self.capture.is_some() && !capture_paused;
+            if let Some(e) = e.update_args().filter(|_| !is_capturing) {
                 #[allow(clippy::cast_possible_truncation)]
                 #[allow(clippy::cast_sign_loss)]
                 let dt = (e.dt * tick_per_sec).round() as TimeTick;
@@ -161,8 +287,16 @@ impl Runtime {
             let event = convert_event(e);
             if let Some(e) = event {
                 if matches!(e, Event::KeyPress(KeyCode::F1)) {
-                    let is_visible = root.borrow().is_visible();
-                    root.borrow_mut().set_visible_flag(!is_visible);
+                    if let Some(root) = self.gui.tree_mut().get_mut(root) {
+                        let is_visible = root.is_visible();
+                        root.set_visible_flag(!is_visible);
+                    }
+                }
+                if matches!(e, Event::KeyPress(KeyCode::F11)) {
+                    capture_paused = !capture_paused;
+                }
+                if matches!(e, Event::KeyPress(KeyCode::F12)) {
+                    self.screenshot = Some(capture::screenshot_path(&self.screenshot_dir));
                 }
                 scene
                     .handle_event(e.clone(), &mut state)
@@ -170,20 +304,63 @@ impl Runtime {
                 self.gui
                     .handle_event(e)
                     .change_context(Error::msg("Failed to update runtime gui"))?;
-                tick_per_sec = tps_slider.borrow().get_value().round();
-                tps_label.borrow_mut().set_text(&format!("TPS: {tick_per_sec}"));
+                tick_per_sec = model
+                    .get(TPS_PROPERTY)
+                    .and_then(|v| v.as_number())
+                    .unwrap_or(tick_per_sec)
+                    .round();
+                model.set(TPS_PROPERTY, tick_per_sec);
             }
 
-            if let Some(cfg) = state.next_scene.take() {
+            if let Some(mut cfg) = state.next_scene.take() {
+                post_process = load_post_process(&mut cfg, &mut state.res)?;
                 scene = scene_builder
                     .build(cfg, &mut state.res)
                     .change_context(Error::msg("Failed to load next scene"))?;
+                let size = self.window.size();
+                scene
+                    .handle_event(Event::Resize(size.width, size.height), &mut state)
+                    .change_context(Error::msg("Scene failed to handle resize event"))?;
             }
         }
         Ok(())
     }
 }
 
+/// Load post-process shaders chain from the scene config "post_process" field.
+/// Each chain item contains fragment shader path ("shader") and optional shader parameters
+/// ("params"), that are float arrays with 1-4 items.
+///
+/// # Errors
+/// Return error if config is not valid or shader failed to load.
+fn load_post_process(cfg: &mut Config, res: &mut ResMngr) -> Result<Vec<ShaderId>, Error> {
+    let items = cfg
+        .take_opt::<Vec<Config>>("post_process")
+        .change_context(Error::msg("Failed to init post-process chain"))?
+        .unwrap_or_default();
+    let mut chain = Vec::new();
+    for mut item in items {
+        let path = item
+            .take::<PathBuf>("shader")
+            .change_context(Error::msg("Failed to init post-process shader"))?;
+        let params = item
+            .take_opt::<HashMap<String, Vec<f32>>>("params")
+            .change_context(Error::msg("Failed to init post-process shader params"))?
+            .unwrap_or_default();
+        let name = path.display().to_string();
+        if res.get_shader(&name).is_err() {
+            res.load("shader", &name, &path, &LoadOptions::default())
+                .change_context(Error::msg(format!("Failed to load shader {name:?}")))?;
+        }
+        let shader = res
+            .get_shader(&name)
+            .change_context(Error::msg(format!("Failed to get shader {name:?}")))?;
+        res.shaders[shader.0].params.extend(params);
+        chain.push(shader);
+    }
+    Ok(chain)
+}
+
 /// Convert piston event to scene event.
 fn convert_event(event: piston::Event) -> Option<Event> {
     match event {
@@ -194,6 +371,10 @@ fn convert_event(event: piston::Event) -> Option<Event> {
                     Button::Keyboard(Key::Backspace) => Some(KeyCode::Backspace),
                     Button::Keyboard(Key::Tab) => Some(KeyCode::Tab),
                     Button::Keyboard(Key::F1) => Some(KeyCode::F1),
+                    Button::Keyboard(Key::F2) => Some(KeyCode::F2),
+                    Button::Keyboard(Key::F3) => Some(KeyCode::F3),
+                    Button::Keyboard(Key::F11) => Some(KeyCode::F11),
+                    Button::Keyboard(Key::F12) => Some(KeyCode::F12),
                     Button::Keyboard(Key::Return) => Some(KeyCode::Enter),
                     Button::Keyboard(Key::Up) => Some(KeyCode::ArrowUp),
                     Button::Keyboard(Key::Down) => Some(KeyCode::ArrowDown),
@@ -202,6 +383,8 @@ fn convert_event(event: piston::Event) -> Option<Event> {
                     Button::Keyboard(Key::Home) => Some(KeyCode::Home),
                     Button::Keyboard(Key::End) => Some(KeyCode::End),
                     Button::Keyboard(Key::Delete) => Some(KeyCode::Delete),
+                    Button::Keyboard(Key::Space) => Some(KeyCode::Space),
+                    Button::Keyboard(Key::LShift | Key::RShift) => Some(KeyCode::Shift),
                     _ => None,
                 }
                 .map(|k| match arg.state {
@@ -233,6 +416,9 @@ fn convert_event(event: piston::Event) -> Option<Event> {
             },
             piston::Input::Move(Motion::MouseCursor([x, y])) => Some(Event::MouseMove(x, y)),
             piston::Input::Text(txt) => Some(Event::TextInput(txt)),
+            piston::Input::Resize(args) => {
+                Some(Event::Resize(args.window_size[0], args.window_size[1]))
+            }
             _ => None,
         },
         _ => None,
@@ -254,7 +440,7 @@ impl scene::State for State {
         Ok(())
     }
 
-    fn get_resources_manager(&mut self) -> &dyn resources::Manager {
+    fn get_resources_manager(&mut self) -> &mut dyn resources::Manager {
         &mut self.res
     }
 }
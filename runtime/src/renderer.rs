@@ -1,142 +1,788 @@
 //! Simple renderer implementation.
 
-use super::resmgr::ResMngr;
+use super::resmgr::{FontKind, RenderTarget, ResMngr};
+use super::shader::Shader;
+use error_stack::{bail, Result};
+use gl::types::GLuint;
+use graphics::draw_state::Blend;
 use graphics::rectangle::Border;
+use graphics::triangulation::{tx, ty};
 use graphics::{
-    line, Character, CharacterCache, Context, DrawState, Image, Rectangle, Transformed,
+    math::Matrix2d, CharacterCache, Context, DrawState, Graphics, ImageSize, Line, Rectangle,
+    Transformed, Viewport, BACK_END_MAX_VERTEX_COUNT,
 };
 use opengl_graphics::{GlGraphics, Texture};
-use renderer::TextTruncateMode;
-use resources::FontId;
-use utils::{color::Color, rect::Rectf, vec2::Vec2f};
+use renderer::{shape::Triangle, text::TextLayout, BlendMode, FontMetrics};
+use resources::{FontId, ShaderId, TextureId};
+use utils::{color::Color, rect::Rectf, transform::Transform, vec2::Vec2f};
 
 /// Simple implementation of renderer
 pub struct Renderer<'a> {
     /// Gl graphics
     pub g: &'a mut GlGraphics,
-    /// Contexts stack
-    pub ctx: Vec<Context>,
+    /// Frame buffer, that is drawn to, and frame buffers, saved on render targets binding.
+    frames: Frames,
+    /// Started post-processing chains with their render targets.
+    /// Targets are `None`, if the chain is drawn without effects.
+    post_process: Vec<(Vec<ShaderId>, Option<[TextureId; 2]>)>,
+    /// Textured quads, that are not drawn yet.
+    batch: Batch,
+    /// Frame statistics.
+    stats: &'a mut Stats,
     /// Resources
     pub res: &'a mut ResMngr,
 }
 
+/// Rendering statistics.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stats {
+    /// Number of backend draw calls.
+    pub draw_calls: usize,
+    /// Number of drawn vertices.
+    pub vertices: usize,
+    /// Number of texture changes between textured draw calls.
+    pub texture_switches: usize,
+    /// Texture of the last textured draw call.
+    last_texture: Option<GLuint>,
+}
+
+impl Stats {
+    /// Count draw call. `texture` is `None` for draws without texture.
+    fn add_draw(&mut self, vertices: usize, texture: Option<GLuint>) {
+        self.draw_calls += vertices.div_ceil(BATCH_SIZE).max(1);
+        self.vertices += vertices;
+        if let Some(texture) = texture {
+            if self.last_texture.is_some_and(|last| last != texture) {
+                self.texture_switches += 1;
+            }
+            self.last_texture = Some(texture);
+        }
+    }
+}
+
+/// Maximum number of vertices in one backend draw call. Contains whole triangles only.
+const BATCH_SIZE: usize = BACK_END_MAX_VERTEX_COUNT / 3 * 3;
+
+/// Backend blend mode, that marks screen blending in draw state. Backend has no screen blending,
+/// so its blend function is replaced by [`draw_blended`].
+const SCREEN_BLEND: Blend = Blend::Lighter;
+
+/// Draw with backend using specified draw state.
+/// Screen blending is set by GL call after the backend binds the draw state. The backend does not
+/// bind the same draw state again, so all draws of `f` use screen blending.
+fn draw_blended(g: &mut GlGraphics, draw_state: &DrawState, f: impl FnOnce(&mut GlGraphics)) {
+    if draw_state.blend != Some(SCREEN_BLEND) {
+        f(g);
+        return;
+    }
+    g.draw_end();
+    g.use_draw_state(draw_state);
+    // SAFETY: GL context is current while renderer exists.
+    unsafe {
+        gl::BlendFuncSeparate(gl::ONE, gl::ONE_MINUS_SRC_COLOR, gl::ONE, gl::ONE_MINUS_SRC_ALPHA);
+    }
+    f(g);
+    g.draw_end();
+    // Backend does not know about the replaced blend function, so draw state is bound again.
+    g.clear_draw_state();
+}
+
+/// Textured quads, that share texture and draw state.
+#[derive(Default)]
+struct Batch {
+    /// Index of texture in resource manager textures.
+    texture: usize,
+    /// Draw state of all quads.
+    draw_state: DrawState,
+    /// Custom shader of all quads.
+    shader: Option<ShaderId>,
+    /// Vertex positions in GL coordinates.
+    positions: Vec<[f32; 2]>,
+    /// Vertex texture coordinates.
+    uvs: Vec<[f32; 2]>,
+    /// Vertex colors.
+    colors: Vec<[f32; 4]>,
+}
+
+impl Batch {
+    /// Add quad. `rect` is in local coordinates, `src` is in texture pixels.
+    fn push(
+        &mut self,
+        m: Matrix2d,
+        rect: [f64; 4],
+        src: [f64; 4],
+        size: (u32, u32),
+        color: [f32; 4],
+    ) {
+        let [x, y, w, h] = rect;
+        let [u, v, uw, vh] = src;
+        let (tw, th) = (f64::from(size.0), f64::from(size.1));
+        let corners = [
+            (x, y, u, v),
+            (x + w, y, u + uw, v),
+            (x, y + h, u, v + vh),
+            (x + w, y + h, u + uw, v + vh),
+        ];
+        for i in [0, 1, 2, 1, 3, 2] {
+            let (x, y, u, v) = corners[i];
+            self.positions.push([tx(m, x, y), ty(m, x, y)]);
+            #[allow(clippy::cast_possible_truncation)]
+            self.uvs.push([(u / tw) as f32, (v / th) as f32]);
+            self.colors.push(color);
+        }
+    }
+
+    /// Draw all quads with specified texture and custom shader, then clear batch.
+    fn draw(
+        &mut self,
+        g: &mut GlGraphics,
+        texture: &Texture,
+        shader: Option<&Shader>,
+        stats: &mut Stats,
+    ) {
+        if let Some(shader) = shader {
+            g.draw_end();
+            draw_blended(g, &self.draw_state, |g| {
+                g.use_draw_state(&self.draw_state);
+                let id = texture.get_id();
+                shader.draw(&self.positions, &self.uvs, &self.colors, id, texture.get_size());
+                g.clear_program();
+            });
+        } else {
+            draw_blended(g, &self.draw_state, |g| {
+                g.tri_list_uv_c(&self.draw_state, texture, |f| {
+                    let chunks = self.positions.chunks(BATCH_SIZE).zip(self.uvs.chunks(BATCH_SIZE));
+                    for ((positions, uvs), colors) in chunks.zip(self.colors.chunks(BATCH_SIZE)) {
+                        f(positions, uvs, colors);
+                    }
+                });
+            });
+        }
+        stats.add_draw(self.positions.len(), Some(texture.get_id()));
+        self.positions.clear();
+        self.uvs.clear();
+        self.colors.clear();
+    }
+}
+
+/// Drawing state of frame buffer.
+struct Frame {
+    /// Frame buffer. Zero is window frame buffer.
+    fbo: GLuint,
+    /// Drawing context. Its transform maps window coordinates to GL ones.
+    ctx: Context,
+    /// States stack. The last one is current state.
+    states: Vec<State>,
+    /// Clip rectangles stack in window coordinates. The last one is current clip.
+    clips: Vec<Rectf>,
+}
+
+impl Frame {
+    /// Make frame with default state.
+    fn new(fbo: GLuint, ctx: Context) -> Self {
+        Self { fbo, ctx, states: vec![State::default()], clips: Vec::new() }
+    }
+
+    /// Get viewport of render target with specified size in pixels and in window coordinates.
+    #[allow(clippy::cast_possible_wrap)]
+    const fn target_viewport(size: [u32; 2], window_size: [f64; 2]) -> Viewport {
+        Viewport { rect: [0, 0, size[0] as i32, size[1] as i32], draw_size: size, window_size }
+    }
+
+    /// Make frame of render target, that is drawn with specified viewport.
+    fn target(fbo: GLuint, viewport: Viewport) -> Self {
+        let ctx = Context::new_viewport(viewport);
+        // Frame buffer rows go from bottom to top, but texture rows are drawn from top to bottom.
+        let transform = ctx.transform.trans(0.0, viewport.window_size[1]).flip_v();
+        Self::new(fbo, Context { transform, ..ctx })
+    }
+}
+
+/// Stack of frame buffers. Binding of render target saves the current frame,
+/// unbinding restores it.
+struct Frames {
+    /// Frame, that is drawn to.
+    current: Frame,
+    /// Saved frames. The last one is restored first.
+    saved: Vec<Frame>,
+}
+
+impl Frames {
+    /// Make stack, that draws to window with specified context.
+    fn new(ctx: Context) -> Self {
+        Self { current: Frame::new(0, ctx), saved: Vec::new() }
+    }
+
+    /// Make frame current and save the previous one.
+    fn bind(&mut self, frame: Frame) {
+        self.saved.push(std::mem::replace(&mut self.current, frame));
+    }
+
+    /// Restore the last saved frame. Returns `None` if no render target is bound.
+    fn unbind(&mut self) -> Option<&Frame> {
+        self.current = self.saved.pop()?;
+        Some(&self.current)
+    }
+}
+
+/// Renderer state.
+#[derive(Clone, Copy)]
+struct State {
+    /// Transform from local coordinates to window ones.
+    transform: Transform,
+    /// Multiplier of drawn colors alpha.
+    opacity: f64,
+    /// Blend mode.
+    blend: BlendMode,
+    /// Custom shader of textured draws.
+    shader: Option<ShaderId>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            transform: Transform::IDENTITY,
+            opacity: 1.0,
+            blend: BlendMode::default(),
+            shader: None,
+        }
+    }
+}
+
+/// Glyph metrics, copied from glyph cache.
+#[derive(Clone, Copy, Default)]
+struct Glyph {
+    /// Font, that contains glyph. `None` if glyph can not be drawn.
+    font: Option<FontId>,
+    /// Offset from pen position to the glyph left edge.
+    left: f64,
+    /// Offset from baseline to the glyph top edge.
+    top: f64,
+    /// Horizontal advance.
+    advance_width: f64,
+}
+
+impl<'a> Renderer<'a> {
+    /// Create new renderer. Draw calls are counted in `stats`.
+    pub fn new(
+        ctx: Context,
+        g: &'a mut GlGraphics,
+        res: &'a mut ResMngr,
+        stats: &'a mut Stats,
+    ) -> Self {
+        res.flush_atlas();
+        Self {
+            g,
+            frames: Frames::new(ctx),
+            post_process: Vec::new(),
+            batch: Batch::default(),
+            stats,
+            res,
+        }
+    }
+
+    /// Add textured quad to batch. Batch is drawn before, if it has other texture or draw state.
+    /// `rect` is in local coordinates, `src` is in texture pixels.
+    fn push_quad(&mut self, texture: usize, rect: [f64; 4], src: [f64; 4], color: [f32; 4]) {
+        let draw_state = self.draw_state();
+        let shader = self.state().shader;
+        if self.batch.texture != texture
+            || self.batch.draw_state != draw_state
+            || self.batch.shader != shader
+        {
+            self.flush();
+            self.batch.texture = texture;
+            self.batch.draw_state = draw_state;
+            self.batch.shader = shader;
+        }
+        let m = self.matrix();
+        let size = self.res.textures[texture].get_size();
+        self.batch.push(m, rect, src, size, color);
+    }
+
+    /// Draw batched quads.
+    fn flush(&mut self) {
+        if self.batch.positions.is_empty() {
+            return;
+        }
+        let texture = &self.res.textures[self.batch.texture];
+        let shader = self.batch.shader.map(|s| &self.res.shaders[s.0]);
+        self.batch.draw(self.g, texture, shader, self.stats);
+    }
+
+    /// Redirect drawing into frame buffer with specified size in pixels.
+    /// `window_size` defines drawing coordinates range.
+    fn bind_frame_buffer(&mut self, fbo: GLuint, size: [u32; 2], window_size: [f64; 2]) {
+        let viewport = Frame::target_viewport(size, window_size);
+        self.flush();
+        self.g.draw_end();
+        // SAFETY: frame buffer is owned by resource manager and lives while renderer exists.
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, fbo) };
+        self.g.draw_begin(viewport);
+        self.g.clear_color([0.0; 4]);
+        self.frames.bind(Frame::target(fbo, viewport));
+    }
+
+    /// Draw texture over the whole frame buffer with shader.
+    fn draw_screen(&mut self, texture: TextureId, shader: ShaderId) {
+        self.flush();
+        // Texture rows are drawn from top to bottom, frame buffer rows go from bottom to top.
+        let (top, bottom) = if self.frames.current.fbo == 0 { (1.0, -1.0) } else { (-1.0, 1.0) };
+        let corners = [[-1.0, top], [1.0, top], [-1.0, bottom], [1.0, bottom]];
+        let uv_corners = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0], [1.0, 1.0]];
+        let positions = [0, 1, 2, 1, 3, 2].map(|i| corners[i]);
+        let uvs = [0, 1, 2, 1, 3, 2].map(|i| uv_corners[i]);
+        let colors = [self.color(&Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 }); 6];
+        let draw_state = self.draw_state();
+        self.g.draw_end();
+        let texture = &self.res.textures[self.res.regions[texture.0].texture];
+        let shader = &self.res.shaders[shader.0];
+        draw_blended(self.g, &draw_state, |g| {
+            g.use_draw_state(&draw_state);
+            shader.draw(&positions, &uvs, &colors, texture.get_id(), texture.get_size());
+            g.clear_program();
+        });
+        self.stats.add_draw(positions.len(), Some(texture.get_id()));
+    }
+
+    /// Get current state.
+    fn state(&self) -> &State {
+        self.frames.current.states.last().expect("Renderer states stack is empty")
+    }
+
+    /// Get current state for modification.
+    fn state_mut(&mut self) -> &mut State {
+        self.frames.current.states.last_mut().expect("Renderer states stack is empty")
+    }
+
+    /// Get matrix, that maps local coordinates to GL ones.
+    fn matrix(&self) -> Matrix2d {
+        graphics::math::multiply(self.frames.current.ctx.transform, self.state().transform.into())
+    }
+
+    /// Get color with applied opacity.
+    fn color(&self, color: &Color) -> [f32; 4] {
+        let [r, g, b, a]: [f32; 4] = color.into();
+        #[allow(clippy::cast_possible_truncation)]
+        let opacity = self.state().opacity as f32;
+        [r, g, b, a * opacity]
+    }
+
+    /// Get draw state with current blend mode and scissor set to the current clip rectangle.
+    fn draw_state(&self) -> DrawState {
+        let blend = match self.state().blend {
+            BlendMode::Alpha => Blend::Alpha,
+            BlendMode::Add => Blend::Add,
+            BlendMode::Multiply => Blend::Multiply,
+            BlendMode::Screen => SCREEN_BLEND,
+        };
+        let draw_state = DrawState::default().blend(blend);
+        let Some(clip) = self.frames.current.clips.last() else {
+            return draw_state;
+        };
+        // Scissor is set in frame buffer pixels, that may differ from window coordinates.
+        let (sx, sy, height) = self.frames.current.ctx.viewport.map_or((1.0, 1.0, 0.0), |v| {
+            let [w, h] = v.draw_size.map(f64::from);
+            (w / v.window_size[0], h / v.window_size[1], h)
+        });
+        let x = (clip.x * sx).floor().max(0.0);
+        let mut y = (clip.y * sy).floor().max(0.0);
+        let w = (((clip.x + clip.w) * sx).ceil() - x).max(0.0);
+        let h = (((clip.y + clip.h) * sy).ceil() - y).max(0.0);
+        if self.frames.current.fbo != 0 {
+            // Drawing into render target is flipped vertically, see `bind_target`.
+            y = (height - y - h).max(0.0);
+        }
+        #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+        draw_state.scissor([x as u32, y as u32, w as u32, h as u32])
+    }
+
+    /// Get font metrics.
+    fn metrics(&self, font: FontId, size: f64) -> FontMetrics {
+        match self.res.fonts[font.0].kind {
+            FontKind::Ttf(ref cache) => {
+                let font = &cache.font;
+                #[allow(clippy::cast_possible_truncation)]
+                let scale = f64::from(font.scale_for_pixel_height(size as f32));
+                let vmetric = font.v_metrics_unscaled();
+                FontMetrics {
+                    ascent: f64::from(vmetric.ascent) * scale,
+                    descent: -f64::from(vmetric.descent) * scale,
+                    line_gap: f64::from(vmetric.line_gap) * scale,
+                }
+            }
+            FontKind::Bitmap(ref bm) => {
+                let scale = size / bm.size;
+                FontMetrics {
+                    ascent: bm.base * scale,
+                    descent: (bm.line_height - bm.base) * scale,
+                    line_gap: 0.0,
+                }
+            }
+        }
+    }
+
+    /// Get glyph metrics. Fallback fonts are used for glyphs missed in font.
+    /// Bitmap fonts are scaled to requested size.
+    fn glyph(&mut self, font: FontId, size: f64, ch: char) -> Glyph {
+        let font = self.res.resolve_glyph_font(font, ch);
+        match self.res.fonts[font.0].kind {
+            FontKind::Ttf(ref mut cache) => {
+                #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+                let Ok(c) = cache.character(size as u32, ch) else {
+                    return Glyph::default();
+                };
+                Glyph {
+                    font: Some(font),
+                    left: c.left(),
+                    top: c.top(),
+                    advance_width: c.advance_width(),
+                }
+            }
+            FontKind::Bitmap(ref bm) => {
+                let Some(c) = bm.chars.get(&ch) else {
+                    return Glyph::default();
+                };
+                let scale = size / bm.size;
+                Glyph {
+                    font: Some(font),
+                    left: c.xoffset * scale,
+                    top: (bm.base - c.yoffset) * scale,
+                    advance_width: c.xadvance * scale,
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Renderer<'_> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
 impl renderer::Renderer for Renderer<'_> {
+    fn bind_target(&mut self, target: TextureId) -> Result<(), renderer::Error> {
+        let Some(&RenderTarget { fbo, size: [w, h] }) = self.res.targets.get(&target.0) else {
+            bail!(renderer::Error::msg(format!("Texture {} is not a render target", target.0)));
+        };
+        self.bind_frame_buffer(fbo, [w, h], [f64::from(w), f64::from(h)]);
+        Ok(())
+    }
+
+    fn set_shader(&mut self, shader: Option<ShaderId>) {
+        self.state_mut().shader = shader;
+    }
+
+    fn set_shader_param(&mut self, shader: ShaderId, name: &str, value: &[f32]) {
+        self.flush();
+        self.res.shaders[shader.0].params.insert(name.into(), value.to_vec());
+    }
+
+    fn begin_post_process(&mut self, chain: &[ShaderId]) {
+        // Nested chains are drawn without effects, because post-processing targets are shared.
+        let is_nested = self.post_process.iter().any(|(_, targets)| targets.is_some());
+        let viewport = self.frames.current.ctx.viewport.filter(|_| !chain.is_empty() && !is_nested);
+        let targets = viewport.and_then(|v| {
+            // If targets can not be created, the chain is drawn without effects.
+            let targets = self.res.post_process_targets(v.draw_size).ok()?;
+            let fbo = self.res.targets.get(&targets[0].0)?.fbo;
+            self.bind_frame_buffer(fbo, v.draw_size, v.window_size);
+            Some(targets)
+        });
+        self.post_process.push((chain.to_vec(), targets));
+    }
+
+    fn end_post_process(&mut self) {
+        let Some((chain, Some(targets))) = self.post_process.pop() else {
+            return;
+        };
+        self.unbind_target();
+        let mut source = 0;
+        for (i, shader) in chain.iter().enumerate() {
+            let is_last = i + 1 == chain.len();
+            let viewport = self.frames.current.ctx.viewport.filter(|_| !is_last);
+            let fbo = self.res.targets.get(&targets[1 - source].0).map(|t| t.fbo);
+            if let (Some(v), Some(fbo)) = (viewport, fbo) {
+                self.bind_frame_buffer(fbo, v.draw_size, v.window_size);
+                self.draw_screen(targets[source], *shader);
+                self.unbind_target();
+                source = 1 - source;
+            } else {
+                self.draw_screen(targets[source], *shader);
+            }
+        }
+    }
+
+    fn unbind_target(&mut self) {
+        if self.frames.saved.is_empty() {
+            return;
+        }
+        self.flush();
+        self.g.draw_end();
+        let Some(frame) = self.frames.unbind() else {
+            return;
+        };
+        // SAFETY: frame buffer was bound before the target binding, so it is still alive.
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, frame.fbo) };
+        if let Some(viewport) = frame.ctx.viewport {
+            self.g.draw_begin(viewport);
+        }
+    }
+
     fn draw_rect(&mut self, rect: &Rectf, color: &Color) {
-        Rectangle::new([0.0; 4]).border(Border { color: color.into(), radius: 1.0 }).draw(
-            [rect.x, rect.y, rect.w, rect.h],
-            &DrawState::default(),
-            self.ctx.last().unwrap().transform,
-            self.g,
-        );
+        self.flush();
+        let draw_state = self.draw_state();
+        let (m, color) = (self.matrix(), self.color(color));
+        draw_blended(self.g, &draw_state, |g| {
+            Rectangle::new([0.0; 4]).border(Border { color, radius: 1.0 }).draw(
+                [rect.x, rect.y, rect.w, rect.h],
+                &draw_state,
+                m,
+                g,
+            );
+        });
+        // Border is drawn as four rectangles.
+        self.stats.add_draw(24, None);
+    }
+
+    fn fill_rect(&mut self, rect: &Rectf, color: &Color) {
+        self.flush();
+        let draw_state = self.draw_state();
+        let (m, color) = (self.matrix(), self.color(color));
+        draw_blended(self.g, &draw_state, |g| {
+            Rectangle::new(color).draw([rect.x, rect.y, rect.w, rect.h], &draw_state, m, g);
+        });
+        self.stats.add_draw(6, None);
+    }
+
+    fn fill_triangles(&mut self, triangles: &[Triangle], color: &Color) {
+        self.flush();
+        let m = self.matrix();
+        let vertices: Vec<[f32; 2]> =
+            triangles.iter().flatten().map(|p| [tx(m, p.x, p.y), ty(m, p.x, p.y)]).collect();
+        let draw_state = self.draw_state();
+        let color = self.color(color);
+        draw_blended(self.g, &draw_state, |g| {
+            g.tri_list(&draw_state, &color, |f| {
+                for chunk in vertices.chunks(BATCH_SIZE) {
+                    f(chunk);
+                }
+            });
+        });
+        self.stats.add_draw(vertices.len(), None);
     }
 
     fn push_state(&mut self) {
-        self.ctx.push(*self.ctx.last().unwrap());
+        self.frames.current.states.push(*self.state());
     }
 
     fn pop_state(&mut self) {
-        self.ctx.pop();
+        if self.frames.current.states.len() > 1 {
+            self.frames.current.states.pop();
+        }
     }
 
     fn translate(&mut self, x: f64, y: f64) {
-        let state = self.ctx.pop().unwrap().trans(x, y);
-        self.ctx.push(state);
+        let state = self.state_mut();
+        state.transform = state.transform.translate(x, y);
     }
 
-    fn draw_line(&mut self, points: &[Vec2f], color: &Color) {
-        for (from, to) in points.iter().zip(points.iter().skip(1)) {
-            line(
-                color.into(),
-                1.0,
-                [from.x, from.y, to.x, to.y],
-                self.ctx.last().unwrap().transform,
-                self.g,
-            );
+    fn set_transform(&mut self, transform: &Transform) {
+        self.state_mut().transform = *transform;
+    }
+
+    fn get_transform(&self) -> Transform {
+        self.state().transform
+    }
+
+    fn set_opacity(&mut self, opacity: f64) {
+        self.state_mut().opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    fn get_opacity(&self) -> f64 {
+        self.state().opacity
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.state_mut().blend = mode;
+    }
+
+    fn get_blend_mode(&self) -> BlendMode {
+        self.state().blend
+    }
+
+    fn push_clip(&mut self, rect: &Rectf) {
+        let mut clip = self.state().transform.bounds(rect);
+        if let Some(last) = self.frames.current.clips.last() {
+            clip = clip.intersect(last);
         }
+        self.frames.current.clips.push(clip);
     }
 
-    fn draw_img(&mut self, rect: &Rectf, texture: resources::TextureId, texture_rect: &Rectf) {
-        Image::new()
-            .rect([rect.x, rect.y, rect.w, rect.h])
-            .src_rect(Into::<[f64; 4]>::into([
-                texture_rect.x,
-                texture_rect.y,
-                texture_rect.w,
-                texture_rect.h,
-            ]))
-            .draw(
-                &self.res.textures[texture.0],
-                &DrawState::default(),
-                self.ctx.last().unwrap().transform,
-                self.g,
-            );
+    fn pop_clip(&mut self) {
+        self.frames.current.clips.pop();
+    }
+
+    fn draw_line(&mut self, points: &[Vec2f], color: &Color) {
+        self.flush();
+        let draw_state = self.draw_state();
+        let (m, line) = (self.matrix(), Line::new(self.color(color), 1.0));
+        draw_blended(self.g, &draw_state, |g| {
+            for (from, to) in points.iter().zip(points.iter().skip(1)) {
+                line.draw([from.x, from.y, to.x, to.y], &draw_state, m, g);
+            }
+        });
+        for _ in points.iter().skip(1) {
+            self.stats.add_draw(6, None);
+        }
     }
 
-    fn draw_text(
+    fn draw_img_tinted(
         &mut self,
-        txt: &[char],
-        size: f64,
         rect: &Rectf,
-        font: FontId,
-        color: &Color,
-        mode: TextTruncateMode,
-    ) -> usize {
-        let font = self.res.fonts.get_mut(font.0).unwrap();
-        #[allow(clippy::cast_possible_truncation)]
-        let scale = f64::from(font.font.scale_for_pixel_height(size as f32));
-        let vmetric = font.font.v_metrics_unscaled();
-        let ascent = f64::from(vmetric.ascent) * scale * 1.2;
-        let descent = f64::from(vmetric.line_gap - vmetric.descent) * scale;
-        let line_step = ascent + descent;
-        let y_lim = rect.h - line_step;
-        let transform = self.ctx.last().unwrap().transform.trans(rect.x, rect.y + ascent);
-
-        #[allow(clippy::type_complexity)]
-        let mut iter_over_char =
-            |iter: &mut dyn Iterator<Item = char>,
-             f: &mut dyn FnMut(char, &Character<'_, Texture>, f64, f64)| {
-                let mut x = 0.0;
-                let mut y = 0.0;
-                for ch in iter {
-                    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
-                    let character = font.character(size as u32, ch).unwrap();
-                    let mut ch_x = x + character.left();
-                    let mut ch_y = y - character.top();
-                    if rect.w > 0.0 && (ch_x + character.advance_width() > rect.w || ch == '\n') {
-                        x = 0.0;
-                        y += line_step;
-                        ch_x = x + character.left();
-                        ch_y = y - character.top();
-                        if rect.h > 0.0 && y > y_lim {
-                            break;
-                        }
-                    }
-                    f(ch, &character, ch_x, ch_y);
+        texture: TextureId,
+        texture_rect: &Rectf,
+        tint: &Color,
+    ) {
+        let tint = self.color(tint);
+        let region = self.res.regions[texture.0];
+        let [x, y] = region.offset;
+        self.push_quad(
+            region.texture,
+            [rect.x, rect.y, rect.w, rect.h],
+            [texture_rect.x + x, texture_rect.y + y, texture_rect.w, texture_rect.h],
+            tint,
+        );
+    }
 
-                    if ch != '\n' {
-                        x += character.advance_width();
-                    }
-                    y += character.advance_height();
+    fn draw_layout(&mut self, layout: &TextLayout) {
+        // TrueType glyphs are drawn from glyph cache textures, when all glyphs are cached.
+        let mut ttf_glyphs = Vec::new();
+        for g in &layout.glyphs {
+            let (size, ch) = (g.style.size, g.ch);
+            let glyph = self.glyph(g.style.font, size, ch);
+            let Some(face) = glyph.font else {
+                continue;
+            };
+            let (x, y) = (g.pos.x + glyph.left, g.pos.y - glyph.top);
+            let color = self.color(&g.style.color);
+            let (texture, rect, src) = match self.res.fonts[face.0].kind {
+                FontKind::Ttf(ref mut cache) => {
+                    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+                    let Ok(c) = cache.character(size as u32, ch) else {
+                        continue;
+                    };
+                    let [w, h] = c.atlas_size;
+                    let [u, v] = c.atlas_offset;
+                    let texture = c.texture.get_id();
+                    ttf_glyphs.push((texture, face.0, size, ch, color, [x, y, w, h], [u, v, w, h]));
+                    continue;
+                }
+                FontKind::Bitmap(ref bm) => {
+                    let Some(c) = bm.chars.get(&ch) else {
+                        continue;
+                    };
+                    let scale = size / bm.size;
+                    let rect = [x, y, c.width * scale, c.height * scale];
+                    (bm.pages[c.page], rect, [c.x, c.y, c.width, c.height])
                 }
             };
+            self.push_quad(texture, rect, src, color);
+        }
 
-        let mut start_index = 0;
-        if matches!(mode, TextTruncateMode::Front) {
-            iter_over_char(&mut txt.iter().copied().rev(), &mut |_, _, _, _| start_index += 1);
-            start_index = txt.len() - start_index;
-        }
-
-        let mut image = Image::new_color(Into::<[f32; 4]>::into(color));
-        let mut displayed_chars = 0;
-        iter_over_char(&mut txt[start_index..].iter().copied(), &mut |c, ch, x, y| {
-            displayed_chars += 1;
-            if c != '\n' {
-                let rect =
-                    [ch.atlas_offset[0], ch.atlas_offset[1], ch.atlas_size[0], ch.atlas_size[1]];
-                image = image.src_rect(rect);
-                image.draw(ch.texture, &DrawState::default(), transform.trans(x, y), self.g);
+        if !ttf_glyphs.is_empty() {
+            self.flush();
+        }
+        let m = self.matrix();
+        let shader = self.state().shader;
+        let mut batch = Batch { draw_state: self.draw_state(), shader, ..Batch::default() };
+        // Glyph cache has several atlas pages, so glyphs are grouped by their page texture.
+        // Sorting is stable, so glyphs of the same texture are drawn in layout order.
+        ttf_glyphs.sort_by_key(|g| g.0);
+        for glyphs in ttf_glyphs.chunk_by(|a, b| a.0 == b.0) {
+            let (_, face, size, ch, ..) = glyphs[0];
+            let FontKind::Ttf(ref mut cache) = self.res.fonts[face].kind else {
+                continue;
+            };
+            #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+            let Ok(c) = cache.character(size as u32, ch) else {
+                continue;
+            };
+            let texture_size = c.texture.get_size();
+            for (.., color, rect, src) in glyphs {
+                batch.push(m, *rect, *src, texture_size, *color);
             }
-        });
-        txt.len() - displayed_chars
+            let shader = shader.map(|s| &self.res.shaders[s.0]);
+            batch.draw(self.g, c.texture, shader, self.stats);
+        }
+    }
+
+    fn font_metrics(&mut self, font: FontId, size: f64) -> FontMetrics {
+        self.metrics(font, size)
+    }
+
+    fn glyph_advance(&mut self, font: FontId, size: f64, ch: char) -> f64 {
+        self.glyph(font, size, ch).advance_width
+    }
+
+    fn kerning(&mut self, font: FontId, size: f64, prev: char, ch: char) -> f64 {
+        let face = self.res.resolve_glyph_font(font, ch);
+        if self.res.resolve_glyph_font(font, prev).0 == face.0 {
+            self.res.fonts[face.0].kerning(size, prev, ch)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Frame, Frames};
+    use graphics::{Context, Viewport};
+    use utils::rect::Rectf;
+
+    #[test]
+    fn target_frame() {
+        let viewport = Frame::target_viewport([200, 100], [100.0, 50.0]);
+        assert_eq!(viewport.rect, [0, 0, 200, 100]);
+        assert_eq!(viewport.draw_size, [200, 100]);
+        let frame = Frame::target(7, viewport);
+        assert_eq!(frame.fbo, 7);
+        assert_eq!(frame.states.len(), 1);
+        assert!(frame.clips.is_empty());
+        // Top left corner of target is mapped to the bottom of frame buffer.
+        let m = frame.ctx.transform;
+        assert_eq!([m[0][2], m[1][2]], [-1.0, -1.0]);
+        assert_eq!(m[1][1], 2.0 / 50.0);
+    }
+
+    #[test]
+    fn bind_nested_targets() {
+        let window =
+            Viewport { rect: [0, 0, 320, 240], draw_size: [320, 240], window_size: [320.0, 240.0] };
+        let mut frames = Frames::new(Context::new_viewport(window));
+        frames.current.clips.push(Rectf { x: 0.0, y: 0.0, w: 10.0, h: 10.0 });
+        assert!(frames.unbind().is_none());
+        assert_eq!(frames.current.fbo, 0);
+
+        frames.bind(Frame::target(1, Frame::target_viewport([64, 64], [64.0, 64.0])));
+        assert_eq!(frames.current.fbo, 1);
+        assert!(frames.current.clips.is_empty());
+        frames.current.states.push(frames.current.states[0]);
+        frames.bind(Frame::target(2, Frame::target_viewport([32, 32], [32.0, 32.0])));
+        assert_eq!(frames.current.fbo, 2);
+        assert_eq!(frames.current.states.len(), 1);
+
+        let frame = frames.unbind().unwrap();
+        assert_eq!(frame.fbo, 1);
+        assert_eq!(frame.states.len(), 2);
+        assert_eq!(frame.ctx.viewport.unwrap().draw_size, [64, 64]);
+        let frame = frames.unbind().unwrap();
+        assert_eq!(frame.fbo, 0);
+        assert_eq!(frame.clips.len(), 1);
+        assert_eq!(frame.ctx.viewport.unwrap().draw_size, [320, 240]);
+        assert!(frames.unbind().is_none());
+        assert_eq!(frames.current.fbo, 0);
     }
 }
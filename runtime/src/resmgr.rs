@@ -1,21 +1,115 @@
 //! Simple resource manager implementation.
 
-use error_stack::{bail, Result};
+use super::atlas::{Atlas, Page};
+use super::bmfont::BitmapFont;
+use super::shader::Shader;
+use error_stack::{bail, ensure, Result, ResultExt};
+use gl::types::GLuint;
+use image::RgbaImage;
 use opengl_graphics::{GlyphCache, Texture, TextureSettings};
-use resources::{self, FontId, Manager, TextureId};
+use resources::{
+    self, strings::Strings, Filter, FontId, FontStyle, LoadOptions, Manager, ShaderId, TextureId,
+    Wrap,
+};
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Texture region. Used to find texture packed into atlas.
+#[derive(Clone, Copy)]
+pub struct Region {
+    /// Index of texture in textures vector.
+    pub texture: usize,
+    /// Region offset inside texture.
+    pub offset: [f64; 2],
+}
+
+/// Offscreen render target. Frame buffer is deleted on drop.
+pub struct RenderTarget {
+    /// Frame buffer object, that draws into the target texture.
+    pub fbo: GLuint,
+    /// Target size in pixels.
+    pub size: [u32; 2],
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        // SAFETY: frame buffer was created by `create_render_target` in the current GL context.
+        unsafe { gl::DeleteFramebuffers(1, &self.fbo) };
+    }
+}
+
+/// Font glyphs source.
+pub enum FontKind {
+    /// TrueType font, glyphs are rasterized on demand.
+    Ttf(GlyphCache<'static>),
+    /// Bitmap font, glyphs are taken from page textures.
+    Bitmap(BitmapFont),
+}
+
+/// Loaded font.
+pub struct Font {
+    /// Font glyphs source.
+    pub kind: FontKind,
+    /// Font style in its family.
+    pub style: FontStyle,
+    /// Names of fonts, that used to draw glyphs missed in this font.
+    pub fallback: Vec<String>,
+}
+
+impl Font {
+    /// Check if font contains glyph for specified char.
+    #[must_use]
+    pub fn has_glyph(&self, ch: char) -> bool {
+        match self.kind {
+            FontKind::Ttf(ref cache) => cache.font.glyph(ch).id().0 != 0,
+            FontKind::Bitmap(ref font) => font.chars.contains_key(&ch),
+        }
+    }
+
+    /// Get kerning between two chars for specified font size.
+    #[must_use]
+    pub fn kerning(&self, size: f64, first: char, second: char) -> f64 {
+        match self.kind {
+            FontKind::Ttf(ref cache) => {
+                #[allow(clippy::cast_possible_truncation)]
+                let scale = rusttype::Scale::uniform(size as f32);
+                f64::from(cache.font.pair_kerning(scale, first, second))
+            }
+            FontKind::Bitmap(ref font) => {
+                font.kernings.get(&(first, second)).map_or(0.0, |k| k * size / font.size)
+            }
+        }
+    }
+}
+
+/// Font styles in order of lookup, when font family has no requested style.
+const STYLES: [FontStyle; 4] =
+    [FontStyle::Regular, FontStyle::Bold, FontStyle::Italic, FontStyle::BoldItalic];
+
 /// Simple resource manager implementation.
 pub struct ResMngr {
-    /// All loaded textures.
+    /// All loaded textures (standalone textures and atlas pages).
     pub textures: Vec<Texture>,
-    /// Map to associate texture string name with index in textures vector.
+    /// Texture regions. [`TextureId`] is index in this vector.
+    pub regions: Vec<Region>,
+    /// Map to associate texture string name with texture identifier.
     pub textures_map: HashMap<String, TextureId>,
     /// All loaded fonts.
-    pub fonts: Vec<GlyphCache<'static>>,
-    /// Map to associate font string name with index in fonts vector.
-    pub fonts_map: HashMap<String, FontId>,
+    pub fonts: Vec<Font>,
+    /// Map to associate font family name and style with index in fonts vector.
+    pub fonts_map: HashMap<(String, FontStyle), FontId>,
+    /// Localized string tables.
+    pub strings: Strings,
+    /// Render targets. Key is [`TextureId`] of target texture.
+    pub targets: HashMap<usize, RenderTarget>,
+    /// All loaded shaders. [`ShaderId`] is index in this vector.
+    pub shaders: Vec<Shader>,
+    /// Map to associate shader name with shader identifier.
+    pub shaders_map: HashMap<String, ShaderId>,
+    /// Render targets for post-processing with their size.
+    post_process_targets: Option<([u32; 2], [TextureId; 2])>,
+    /// Texture atlas. If `None` all textures are loaded as standalone.
+    atlas: Option<Atlas>,
 }
 impl ResMngr {
     /// Creates new resource manager.
@@ -23,37 +117,333 @@ impl ResMngr {
     pub fn new() -> Self {
         Self {
             textures: Vec::new(),
+            regions: Vec::new(),
             textures_map: HashMap::new(),
             fonts: Vec::new(),
             fonts_map: HashMap::new(),
+            strings: Strings::default(),
+            targets: HashMap::new(),
+            shaders: Vec::new(),
+            shaders_map: HashMap::new(),
+            post_process_targets: None,
+            atlas: None,
         }
     }
+
+    /// Creates new resource manager, that packs small textures into atlas pages
+    /// with specified size.
+    #[must_use]
+    pub fn with_atlas(page_size: u32) -> Self {
+        Self { atlas: Some(Atlas::new(page_size)), ..Self::new() }
+    }
+
+    /// Add standalone texture.
+    pub fn add_texture(&mut self, name: &str, texture: Texture) -> TextureId {
+        let id = self.push_texture(texture);
+        self.textures_map.insert(name.into(), id);
+        id
+    }
+
+    /// Add standalone texture without name, so it can not be found by [`Manager::get_texture`].
+    fn push_texture(&mut self, texture: Texture) -> TextureId {
+        let id = TextureId(self.regions.len());
+        self.regions.push(Region { texture: self.textures.len(), offset: [0.0; 2] });
+        self.textures.push(texture);
+        id
+    }
+
+    /// Add render target without name. Used for internal targets, that should not replace
+    /// textures loaded by user.
+    fn push_render_target(&mut self, size: [u32; 2]) -> Result<TextureId, resources::Error> {
+        let (texture, target) = new_render_target(size[0], size[1])?;
+        let id = self.push_texture(texture);
+        self.targets.insert(id.0, target);
+        Ok(id)
+    }
+
+    /// Add font to the family with specified name.
+    pub fn add_font(&mut self, name: &str, font: Font) -> FontId {
+        let id = FontId(self.fonts.len());
+        self.fonts_map.insert((name.into(), font.style), id);
+        self.fonts.push(font);
+        id
+    }
+
+    /// Find font of the family with specified style.
+    /// If the family has no such style, regular font is used, then any other loaded style.
+    fn find_font(&self, name: &str, style: FontStyle) -> Option<FontId> {
+        std::iter::once(style)
+            .chain(STYLES)
+            .find_map(|style| self.fonts_map.get(&(name.to_owned(), style)).copied())
+    }
+
+    /// Find font, that contains glyph for specified char.
+    /// The font itself is checked first, then its fallback fonts in order.
+    /// Fallback fonts are resolved in the style of the font.
+    /// Returns specified font if no one contains the glyph.
+    #[must_use]
+    pub fn resolve_glyph_font(&self, font: FontId, ch: char) -> FontId {
+        let has_glyph = |id: FontId| self.fonts[id.0].has_glyph(ch);
+        if ch.is_control() || has_glyph(font) {
+            return font;
+        }
+        let style = self.fonts[font.0].style;
+        self.fonts[font.0]
+            .fallback
+            .iter()
+            .filter_map(|name| self.find_font(name, style))
+            .find(|id| has_glyph(*id))
+            .unwrap_or(font)
+    }
+
+    /// Get two render targets with specified size, that are used for post-processing.
+    /// Targets are created on first use and resized, if size is changed.
+    ///
+    /// # Errors
+    /// Return error if render target can not be created.
+    pub fn post_process_targets(
+        &mut self,
+        size: [u32; 2],
+    ) -> Result<[TextureId; 2], resources::Error> {
+        let ids = match TargetsUpdate::new(self.post_process_targets, size) {
+            TargetsUpdate::Keep(ids) => return Ok(ids),
+            TargetsUpdate::Resize(ids) => {
+                for id in ids {
+                    let (texture, target) = new_render_target(size[0], size[1])?;
+                    self.textures[self.regions[id.0].texture] = texture;
+                    self.targets.insert(id.0, target);
+                }
+                ids
+            }
+            TargetsUpdate::Create => {
+                [self.push_render_target(size)?, self.push_render_target(size)?]
+            }
+        };
+        self.post_process_targets = Some((size, ids));
+        Ok(ids)
+    }
+
+    /// Upload changed atlas pages to GPU.
+    pub fn flush_atlas(&mut self) {
+        let Some(ref mut atlas) = self.atlas else {
+            return;
+        };
+        for page in atlas.pages.iter_mut().filter(|p| p.dirty) {
+            self.textures[page.texture] =
+                Texture::from_image(&page.image, &texture_settings(&page.opts));
+            page.dirty = false;
+        }
+    }
+
+    /// Load texture from file. Small textures are packed into atlas, if it is enabled.
+    fn load_texture(
+        &mut self,
+        name: &str,
+        path: &Path,
+        opts: &LoadOptions,
+    ) -> Result<(), resources::Error> {
+        let mut img = image::open(path)
+            .map_err(|e| resources::Error::msg(format!("Failed to load texture: {e}")))?
+            .to_rgba8();
+        if opts.premultiplied_alpha {
+            unpremultiply(&mut img);
+        }
+        let Some(ref mut atlas) = self.atlas else {
+            self.add_texture(name, Texture::from_image(&img, &texture_settings(opts)));
+            return Ok(());
+        };
+        if !atlas.accepts(img.width(), img.height(), opts) {
+            self.add_texture(name, Texture::from_image(&img, &texture_settings(opts)));
+            return Ok(());
+        }
+
+        let mut packed = atlas
+            .pages
+            .iter_mut()
+            .filter(|p| p.opts == *opts)
+            .find_map(|p| Some((p.texture, p.pack(&img)?)));
+        if packed.is_none() {
+            let mut page = Page::new(atlas.page_size, self.textures.len(), opts.clone());
+            self.textures.push(Texture::from_image(&page.image, &texture_settings(opts)));
+            packed = page.pack(&img).map(|pos| (page.texture, pos));
+            atlas.pages.push(page);
+        }
+        let Some((texture, (x, y))) = packed else {
+            bail!(resources::Error::msg(format!(
+                "Failed to pack texture {name:?} into atlas page"
+            )));
+        };
+
+        let id = TextureId(self.regions.len());
+        self.regions.push(Region { texture, offset: [f64::from(x), f64::from(y)] });
+        self.textures_map.insert(name.into(), id);
+        Ok(())
+    }
+
+    /// Load bitmap font description and its page textures.
+    fn load_bitmap_font(
+        &mut self,
+        name: &str,
+        path: &Path,
+        opts: &LoadOptions,
+    ) -> Result<(), resources::Error> {
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| resources::Error::msg(format!("Failed to read bitmap font: {e}")))?;
+        let mut font = BitmapFont::parse(&src)
+            .change_context(resources::Error::msg("Failed to parse bitmap font"))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+        for file in &font.page_files {
+            let page =
+                Texture::from_path(dir.join(file), &texture_settings(opts)).map_err(|e| {
+                    resources::Error::msg(format!("Failed to load bitmap font page {file:?}: {e}"))
+                })?;
+            font.pages.push(self.textures.len());
+            self.textures.push(page);
+        }
+        let font = Font {
+            kind: FontKind::Bitmap(font),
+            style: opts.style,
+            fallback: opts.fallback.clone(),
+        };
+        self.add_font(name, font);
+        Ok(())
+    }
+}
+
+/// Update of post-processing render targets, that is required to get targets with some size.
+enum TargetsUpdate {
+    /// Targets already have required size.
+    Keep([TextureId; 2]),
+    /// Targets exist, but have other size.
+    Resize([TextureId; 2]),
+    /// Targets are not created yet.
+    Create,
 }
+
+impl TargetsUpdate {
+    /// Get update of `current` targets with their size to make targets with specified size.
+    fn new(current: Option<([u32; 2], [TextureId; 2])>, size: [u32; 2]) -> Self {
+        match current {
+            Some((current_size, ids)) if current_size == size => Self::Keep(ids),
+            Some((_, ids)) => Self::Resize(ids),
+            None => Self::Create,
+        }
+    }
+}
+
+/// Check render target size. Frame buffer without pixels can not be complete.
+///
+/// # Errors
+/// Return error if any side of target is zero.
+fn check_target_size(width: u32, height: u32) -> Result<(), resources::Error> {
+    ensure!(
+        width > 0 && height > 0,
+        resources::Error::msg(format!("Invalid render target size: {width}x{height}"))
+    );
+    Ok(())
+}
+
+/// Create transparent texture with frame buffer, that draws into it.
+fn new_render_target(width: u32, height: u32) -> Result<(Texture, RenderTarget), resources::Error> {
+    check_target_size(width, height)?;
+    let texture = Texture::from_image(&RgbaImage::new(width, height), &TextureSettings::new());
+    let mut target = RenderTarget { fbo: 0, size: [width, height] };
+    // SAFETY: texture is valid and previous frame buffer binding is restored.
+    let status = unsafe {
+        let mut prev = 0;
+        gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut prev);
+        gl::GenFramebuffers(1, &mut target.fbo);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, target.fbo);
+        gl::FramebufferTexture2D(
+            gl::FRAMEBUFFER,
+            gl::COLOR_ATTACHMENT0,
+            gl::TEXTURE_2D,
+            texture.get_id(),
+            0,
+        );
+        let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+        #[allow(clippy::cast_sign_loss)]
+        gl::BindFramebuffer(gl::FRAMEBUFFER, prev as GLuint);
+        status
+    };
+    ensure!(
+        status == gl::FRAMEBUFFER_COMPLETE,
+        resources::Error::msg(format!("Frame buffer is incomplete: status: {status:#x}"))
+    );
+    Ok((texture, target))
+}
+
+/// Make texture settings from resource load options.
+#[must_use]
+pub fn texture_settings(opts: &LoadOptions) -> TextureSettings {
+    let filter = match opts.filter {
+        Filter::Nearest => opengl_graphics::Filter::Nearest,
+        Filter::Linear => opengl_graphics::Filter::Linear,
+    };
+    let wrap = match opts.wrap {
+        Wrap::ClampToEdge => opengl_graphics::Wrap::ClampToEdge,
+        Wrap::Repeat => opengl_graphics::Wrap::Repeat,
+        Wrap::MirroredRepeat => opengl_graphics::Wrap::MirroredRepeat,
+    };
+    let mut settings = TextureSettings::new();
+    settings.set_filter(filter);
+    settings.set_mipmap(filter);
+    settings.set_generate_mipmap(opts.mipmaps);
+    settings.set_wrap_u(wrap);
+    settings.set_wrap_v(wrap);
+    settings.set_convert_gamma(opts.srgb);
+    settings
+}
+
+/// Convert premultiplied colors to straight alpha, that is expected by renderer.
+fn unpremultiply(img: &mut RgbaImage) {
+    for p in img.pixels_mut() {
+        let [r, g, b, a] = p.0;
+        if a == 0 {
+            continue;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let div = |c: u8| (u16::from(c) * 255 / u16::from(a)).min(255) as u8;
+        p.0 = [div(r), div(g), div(b), a];
+    }
+}
+
 impl Manager for ResMngr {
-    fn load(&mut self, kind: &str, name: &str, path: &Path) -> Result<(), resources::Error> {
+    fn load(
+        &mut self,
+        kind: &str,
+        name: &str,
+        path: &Path,
+        opts: &LoadOptions,
+    ) -> Result<(), resources::Error> {
         match kind {
-            "texture" => {
-                let id = TextureId(self.textures.len());
-                let mut settings = TextureSettings::new();
-                settings.set_filter(opengl_graphics::Filter::Nearest);
-                self.textures.push(
-                    Texture::from_path(Path::new(path), &settings).map_err(|e| {
-                        resources::Error::msg(format!("Failed to load texture: {e}"))
-                    })?,
-                );
-                self.textures_map.insert(name.into(), id);
-                Ok(())
-            }
+            "texture" => self.load_texture(name, path, opts),
             "font" => {
-                let id = FontId(self.fonts.len());
-                let mut settings = TextureSettings::new();
-                settings.set_filter(opengl_graphics::Filter::Nearest);
-                let cache = GlyphCache::new(path, (), TextureSettings::new())
+                let cache = GlyphCache::new(path, (), texture_settings(opts))
                     .map_err(|e| resources::Error::msg(format!("Failed to load font: \"{e}\"")))?;
-                self.fonts.push(cache);
-                self.fonts_map.insert(name.into(), id);
+                let font = Font {
+                    kind: FontKind::Ttf(cache),
+                    style: opts.style,
+                    fallback: opts.fallback.clone(),
+                };
+                self.add_font(name, font);
+                Ok(())
+            }
+            "bitmap_font" => self.load_bitmap_font(name, path, opts),
+            "shader" => {
+                let src = std::fs::read_to_string(path)
+                    .map_err(|e| resources::Error::msg(format!("Failed to read shader: {e}")))?;
+                let id = ShaderId(self.shaders.len());
+                self.shaders.push(Shader::new(&src)?);
+                self.shaders_map.insert(name.into(), id);
                 Ok(())
             }
+            "strings" => {
+                let src = std::fs::read_to_string(path).map_err(|e| {
+                    resources::Error::msg(format!("Failed to read string table: {e}"))
+                })?;
+                self.strings.load(name, &src)
+            }
             _ => bail!(resources::Error::msg(format!(
                 "Failed to load recourse: unexpected resource type: {:?}, name: {:?}, path: {:?}",
                 kind,
@@ -63,6 +453,27 @@ impl Manager for ResMngr {
         }
     }
 
+    fn create_render_target(
+        &mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+    ) -> Result<TextureId, resources::Error> {
+        let (texture, target) = new_render_target(width, height).change_context(
+            resources::Error::msg(format!("Failed to create render target {name:?}")),
+        )?;
+        let id = self.add_texture(name, texture);
+        self.targets.insert(id.0, target);
+        Ok(id)
+    }
+
+    fn get_shader(&self, name: &str) -> Result<ShaderId, resources::Error> {
+        Ok(*self
+            .shaders_map
+            .get(name)
+            .ok_or_else(|| resources::Error::msg(format!("Failed to find shader: \"{name}\"")))?)
+    }
+
     fn get_texture(&self, name: &str) -> Result<TextureId, resources::Error> {
         Ok(*self
             .textures_map
@@ -71,9 +482,148 @@ impl Manager for ResMngr {
     }
 
     fn get_font(&self, name: &str) -> Result<resources::FontId, resources::Error> {
-        Ok(*self
-            .fonts_map
-            .get(name)
+        self.get_font_style(name, FontStyle::Regular)
+    }
+
+    fn get_font_style(
+        &self,
+        name: &str,
+        style: FontStyle,
+    ) -> Result<resources::FontId, resources::Error> {
+        Ok(self
+            .find_font(name, style)
             .ok_or_else(|| resources::Error::msg(format!("Failed to find font: \"{name}\"")))?)
     }
+
+    fn set_locale(&mut self, locale: &str) -> Result<(), resources::Error> {
+        self.strings.set_locale(locale)
+    }
+
+    fn get_locale(&self) -> &str {
+        self.strings.locale()
+    }
+
+    fn get_string(&self, key: &str, args: &[(&str, &str)]) -> Result<String, resources::Error> {
+        self.strings.get(key, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        check_target_size, unpremultiply, BitmapFont, Font, FontKind, ResMngr, TargetsUpdate,
+    };
+    use image::{Rgba, RgbaImage};
+    use resources::{FontStyle, Manager, TextureId};
+
+    /// Make bitmap font with specified chars.
+    fn font(style: FontStyle, chars: &str, fallback: &[&str]) -> Font {
+        let pages = "page id=0 file=\"font_0.png\"\n".to_owned();
+        let src: String = std::iter::once(pages)
+            .chain(chars.chars().map(|c| {
+                format!(
+                    "char id={} x=0 y=0 width=1 height=1 xoffset=0 yoffset=0 xadvance=1 page=0\n",
+                    u32::from(c)
+                )
+            }))
+            .collect();
+        Font {
+            kind: FontKind::Bitmap(BitmapFont::parse(&src).unwrap()),
+            style,
+            fallback: fallback.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn font_style_resolution() {
+        let mut res = ResMngr::new();
+        let regular = res.add_font("sans", font(FontStyle::Regular, "a", &[]));
+        let italic = res.add_font("sans", font(FontStyle::Italic, "a", &[]));
+        let bold = res.add_font("serif", font(FontStyle::Bold, "a", &[]));
+
+        assert_eq!(res.get_font("sans").unwrap().0, regular.0);
+        assert_eq!(res.get_font_style("sans", FontStyle::Italic).unwrap().0, italic.0);
+        assert_eq!(res.get_font_style("sans", FontStyle::Bold).unwrap().0, regular.0);
+        assert_eq!(res.get_font("serif").unwrap().0, bold.0);
+        assert_eq!(res.get_font_style("serif", FontStyle::Italic).unwrap().0, bold.0);
+        assert!(res.get_font("mono").is_err());
+    }
+
+    #[test]
+    fn glyph_fallback() {
+        let mut res = ResMngr::new();
+        let main = res.add_font("main", font(FontStyle::Regular, "a", &["missing", "emoji"]));
+        let main_bold = res.add_font("main", font(FontStyle::Bold, "a", &["emoji"]));
+        let emoji = res.add_font("emoji", font(FontStyle::Regular, "bc", &[]));
+        let emoji_bold = res.add_font("emoji", font(FontStyle::Bold, "b", &[]));
+        let other = res.add_font("other", font(FontStyle::Italic, "c", &["emoji"]));
+
+        assert_eq!(res.resolve_glyph_font(main, 'a').0, main.0);
+        assert_eq!(res.resolve_glyph_font(main, 'b').0, emoji.0);
+        assert_eq!(res.resolve_glyph_font(main_bold, 'a').0, main_bold.0);
+        assert_eq!(res.resolve_glyph_font(main_bold, 'b').0, emoji_bold.0);
+        // Fallback font of the requested style has no glyph, other styles are not checked.
+        assert_eq!(res.resolve_glyph_font(main_bold, 'c').0, main_bold.0);
+        // Family has no requested style, regular font is used.
+        assert_eq!(res.resolve_glyph_font(other, 'b').0, emoji.0);
+        assert_eq!(res.resolve_glyph_font(main, 'z').0, main.0);
+        assert_eq!(res.resolve_glyph_font(main, '\n').0, main.0);
+    }
+
+    #[test]
+    fn unpremultiply_edges() {
+        let mut img = RgbaImage::from_pixel(2, 1, Rgba([0, 0, 0, 0]));
+        img.put_pixel(1, 0, Rgba([10, 128, 255, 255]));
+        unpremultiply(&mut img);
+        assert_eq!(img.get_pixel(0, 0).0, [0, 0, 0, 0]);
+        assert_eq!(img.get_pixel(1, 0).0, [10, 128, 255, 255]);
+
+        let mut img = RgbaImage::from_pixel(1, 1, Rgba([64, 32, 0, 128]));
+        unpremultiply(&mut img);
+        assert_eq!(img.get_pixel(0, 0).0, [127, 63, 0, 128]);
+    }
+
+    #[test]
+    fn unpremultiply_round_trip() {
+        for a in 1..=255u8 {
+            #[allow(clippy::cast_possible_truncation)]
+            let mut img = RgbaImage::from_fn(u32::from(a) + 1, 1, |x, _| {
+                let c = x as u8;
+                Rgba([c, c, c, a])
+            });
+            unpremultiply(&mut img);
+            for (x, p) in img.pixels().enumerate() {
+                // Unpremultiplied color is rounded down, so rounding up restores it exactly.
+                let premul = usize::from(u16::from(p.0[0]) * u16::from(a)).div_ceil(255);
+                assert_eq!(premul, x, "alpha: {a}");
+                assert_eq!(p.0[3], a);
+            }
+        }
+    }
+
+    #[test]
+    fn render_target_size() {
+        assert!(check_target_size(1, 1).is_ok());
+        assert!(check_target_size(640, 480).is_ok());
+        assert!(check_target_size(0, 480).is_err());
+        assert!(check_target_size(640, 0).is_err());
+    }
+
+    #[test]
+    fn post_process_targets_update() {
+        let ids = [TextureId(3), TextureId(4)];
+        assert!(matches!(TargetsUpdate::new(None, [640, 480]), TargetsUpdate::Create));
+        assert!(matches!(
+            TargetsUpdate::new(Some(([640, 480], ids)), [640, 480]),
+            TargetsUpdate::Keep([TextureId(3), TextureId(4)])
+        ));
+        assert!(matches!(
+            TargetsUpdate::new(Some(([640, 480], ids)), [800, 480]),
+            TargetsUpdate::Resize([TextureId(3), TextureId(4)])
+        ));
+        assert!(matches!(
+            TargetsUpdate::new(Some(([640, 480], ids)), [640, 600]),
+            TargetsUpdate::Resize([TextureId(3), TextureId(4)])
+        ));
+    }
 }
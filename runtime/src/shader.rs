@@ -0,0 +1,217 @@
+//! Custom shaders.
+//!
+//! Custom shader is a fragment shader, that replaces the default one for textured draws.
+//! Fragment shader gets:
+//! ```glsl
+//! #version 150 core
+//! uniform sampler2D u_texture;  // drawn texture
+//! uniform vec2 u_texture_size;  // texture size in pixels
+//! in vec2 v_uv;                 // texture coordinates
+//! in vec4 v_color;              // tint color with applied opacity
+//! out vec4 o_color;
+//! ```
+//! Shader parameters are passed as float uniforms with the same names.
+
+use error_stack::{bail, Result};
+use gl::types::{GLchar, GLenum, GLint, GLsizei, GLsizeiptr, GLuint};
+use std::{collections::HashMap, ffi::CStr, ffi::CString};
+
+/// Vertex shader, that is shared by all custom shaders. Vertices are already in GL coordinates.
+const VERTEX_SHADER: &str = "#version 150 core
+in vec2 a_pos;
+in vec2 a_uv;
+in vec4 a_color;
+out vec2 v_uv;
+out vec4 v_color;
+void main() {
+    v_uv = a_uv;
+    v_color = a_color;
+    gl_Position = vec4(a_pos, 0.0, 1.0);
+}
+";
+
+/// Vertex attributes names and components count. Attribute location is its index.
+const ATTRIBUTES: [(&CStr, GLint); 3] = [(c"a_pos", 2), (c"a_uv", 2), (c"a_color", 4)];
+
+/// Compiled shader program. GL objects are deleted on drop.
+pub struct Shader {
+    /// Shader program.
+    program: GLuint,
+    /// Vertex array.
+    vao: GLuint,
+    /// Vertex buffers: positions, texture coordinates and colors.
+    vbos: [GLuint; 3],
+    /// Shader parameters.
+    pub params: HashMap<String, Vec<f32>>,
+}
+
+impl Shader {
+    /// Compile shader program with specified fragment shader source.
+    ///
+    /// # Errors
+    /// Return error if shader failed to compile or link.
+    pub fn new(fragment: &str) -> Result<Self, resources::Error> {
+        // SAFETY: GL context is current. Created objects are deleted on error or on drop.
+        unsafe {
+            let vs = compile(gl::VERTEX_SHADER, VERTEX_SHADER)?;
+            let fs = match compile(gl::FRAGMENT_SHADER, fragment) {
+                Ok(fs) => fs,
+                Err(e) => {
+                    gl::DeleteShader(vs);
+                    return Err(e);
+                }
+            };
+            let program = gl::CreateProgram();
+            gl::AttachShader(program, vs);
+            gl::AttachShader(program, fs);
+            for (location, (name, _)) in (0..).zip(ATTRIBUTES) {
+                gl::BindAttribLocation(program, location, name.as_ptr());
+            }
+            gl::BindFragDataLocation(program, 0, c"o_color".as_ptr());
+            gl::LinkProgram(program);
+            gl::DeleteShader(vs);
+            gl::DeleteShader(fs);
+            let mut status = 0;
+            gl::GetProgramiv(program, gl::LINK_STATUS, &mut status);
+            if status == 0 {
+                let log = info_log(program, gl::GetProgramiv, gl::GetProgramInfoLog);
+                gl::DeleteProgram(program);
+                bail!(resources::Error::msg(format!("Failed to link shader: {log}")));
+            }
+
+            let mut prev_vao = 0;
+            gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, &mut prev_vao);
+            let mut vao = 0;
+            gl::GenVertexArrays(1, &mut vao);
+            gl::BindVertexArray(vao);
+            let mut vbos = [0; 3];
+            gl::GenBuffers(3, vbos.as_mut_ptr());
+            for ((location, (_, size)), vbo) in (0..).zip(ATTRIBUTES).zip(vbos) {
+                gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+                gl::EnableVertexAttribArray(location);
+                gl::VertexAttribPointer(location, size, gl::FLOAT, gl::FALSE, 0, std::ptr::null());
+            }
+            #[allow(clippy::cast_sign_loss)]
+            gl::BindVertexArray(prev_vao as GLuint);
+            Ok(Self { program, vao, vbos, params: HashMap::new() })
+        }
+    }
+
+    /// Draw textured triangles. Positions are in GL coordinates.
+    /// Backend must flush its draws and bind draw state before.
+    pub fn draw(
+        &self,
+        positions: &[[f32; 2]],
+        uvs: &[[f32; 2]],
+        colors: &[[f32; 4]],
+        texture: GLuint,
+        texture_size: (u32, u32),
+    ) {
+        // SAFETY: GL context is current and buffers are filled with the same vertex count.
+        // Vertex array and buffer bindings of the backend are restored.
+        unsafe {
+            let (mut prev_vao, mut prev_vbo) = (0, 0);
+            gl::GetIntegerv(gl::VERTEX_ARRAY_BINDING, &mut prev_vao);
+            gl::GetIntegerv(gl::ARRAY_BUFFER_BINDING, &mut prev_vbo);
+            gl::UseProgram(self.program);
+            gl::BindVertexArray(self.vao);
+            upload(self.vbos[0], positions);
+            upload(self.vbos[1], uvs);
+            upload(self.vbos[2], colors);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::Uniform1i(gl::GetUniformLocation(self.program, c"u_texture".as_ptr()), 0);
+            #[allow(clippy::cast_precision_loss)]
+            gl::Uniform2f(
+                gl::GetUniformLocation(self.program, c"u_texture_size".as_ptr()),
+                texture_size.0 as f32,
+                texture_size.1 as f32,
+            );
+            for (name, value) in &self.params {
+                let Ok(name) = CString::new(name.as_str()) else {
+                    continue;
+                };
+                let location = gl::GetUniformLocation(self.program, name.as_ptr());
+                match value.len() {
+                    1 => gl::Uniform1fv(location, 1, value.as_ptr()),
+                    2 => gl::Uniform2fv(location, 1, value.as_ptr()),
+                    3 => gl::Uniform3fv(location, 1, value.as_ptr()),
+                    4 => gl::Uniform4fv(location, 1, value.as_ptr()),
+                    _ => {}
+                }
+            }
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            gl::DrawArrays(gl::TRIANGLES, 0, positions.len() as GLsizei);
+            #[allow(clippy::cast_sign_loss)]
+            {
+                gl::BindVertexArray(prev_vao as GLuint);
+                gl::BindBuffer(gl::ARRAY_BUFFER, prev_vbo as GLuint);
+            }
+        }
+    }
+}
+
+impl Drop for Shader {
+    fn drop(&mut self) {
+        // SAFETY: objects were created by `Shader::new` in the current GL context.
+        unsafe {
+            gl::DeleteBuffers(3, self.vbos.as_ptr());
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteProgram(self.program);
+        }
+    }
+}
+
+/// Compile shader of specified kind.
+///
+/// # Safety
+/// GL context must be current.
+unsafe fn compile(kind: GLenum, src: &str) -> Result<GLuint, resources::Error> {
+    let Ok(src) = CString::new(src) else {
+        bail!(resources::Error::msg("Shader source contains zero byte"));
+    };
+    let shader = gl::CreateShader(kind);
+    gl::ShaderSource(shader, 1, &src.as_ptr(), std::ptr::null());
+    gl::CompileShader(shader);
+    let mut status = 0;
+    gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut status);
+    if status == 0 {
+        let log = info_log(shader, gl::GetShaderiv, gl::GetShaderInfoLog);
+        gl::DeleteShader(shader);
+        bail!(resources::Error::msg(format!("Failed to compile shader: {log}")));
+    }
+    Ok(shader)
+}
+
+/// Get info log of shader or program.
+///
+/// # Safety
+/// GL context must be current and `object` must be valid for specified functions.
+unsafe fn info_log(
+    object: GLuint,
+    get_iv: unsafe fn(GLuint, GLenum, *mut GLint),
+    get_log: unsafe fn(GLuint, GLsizei, *mut GLsizei, *mut GLchar),
+) -> String {
+    let mut len = 0;
+    get_iv(object, gl::INFO_LOG_LENGTH, &mut len);
+    let mut log = vec![0_u8; usize::try_from(len).unwrap_or(0)];
+    let mut written = 0;
+    get_log(object, len, &mut written, log.as_mut_ptr().cast());
+    log.truncate(usize::try_from(written).unwrap_or(0));
+    String::from_utf8_lossy(&log).into_owned()
+}
+
+/// Upload vertex data into buffer.
+///
+/// # Safety
+/// GL context must be current and `vbo` must be valid buffer.
+unsafe fn upload<T>(vbo: GLuint, data: &[T]) {
+    gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+    #[allow(clippy::cast_possible_wrap)]
+    gl::BufferData(
+        gl::ARRAY_BUFFER,
+        std::mem::size_of_val(data) as GLsizeiptr,
+        data.as_ptr().cast(),
+        gl::STREAM_DRAW,
+    );
+}
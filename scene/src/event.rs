@@ -30,6 +30,8 @@ pub enum Event {
     KeyRelease(KeyCode),
     /// Time tick. Used for update time depended object.
     TimeTick(TimeTick),
+    /// Window resize event, args: new window width and height.
+    Resize(f64, f64),
 }
 
 /// Keyboard button codes.
@@ -40,6 +42,10 @@ pub enum KeyCode {
     Backspace,
     Tab,
     F1,
+    F2,
+    F3,
+    F11,
+    F12,
     Enter,
     ArrowUp,
     ArrowDown,
@@ -48,4 +54,6 @@ pub enum KeyCode {
     Home,
     End,
     Delete,
+    Space,
+    Shift,
 }
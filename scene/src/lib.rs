@@ -33,7 +33,7 @@ pub trait State {
     fn load_next_scene(&mut self, cfg: Config) -> Result<(), Error>;
 
     /// Get resource manager.
-    fn get_resources_manager(&mut self) -> &dyn ResManger;
+    fn get_resources_manager(&mut self) -> &mut dyn ResManger;
 }
 
 /// Scene interface.
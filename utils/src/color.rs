@@ -30,7 +30,7 @@ pub const WHITE: Color = Color { r: 1.0, g: 1.0, b: 1.0, a: 0.7 };
 pub const TRANSPARENT: Color = Color { r: 0.0, g: 0.0, b: 0.0, a: 0.0 };
 
 /// Color.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Color {
     /// Red.
     pub r: f32,
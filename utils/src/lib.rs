@@ -2,4 +2,5 @@
 
 pub mod color;
 pub mod rect;
+pub mod transform;
 pub mod vec2;
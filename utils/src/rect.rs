@@ -29,6 +29,18 @@ impl<T: Display + Debug + Copy + Add<Output = T> + PartialOrd<T>> Rect<T> {
     }
 }
 
+impl Rectf {
+    /// Get intersection with other rectangle. Empty intersection has zero size.
+    #[must_use]
+    pub fn intersect(&self, other: &Self) -> Self {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let w = ((self.x + self.w).min(other.x + other.w) - x).max(0.0);
+        let h = ((self.y + self.h).min(other.y + other.h) - y).max(0.0);
+        Self { x, y, w, h }
+    }
+}
+
 impl<T> From<[T; 4]> for Rect<T> {
     fn from(value: [T; 4]) -> Self {
         let [x, y, w, h] = value;
@@ -41,3 +53,23 @@ impl<T: ParseFormValue> ParseFormValue for Rect<T> {
         Ok(<[T; 4]>::parse_val(val)?.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Rectf;
+
+    /// Check that rectangle is equal to expected one with some precision.
+    fn assert_near(r: &Rectf, expected: [f64; 4]) {
+        let actual = [r.x, r.y, r.w, r.h];
+        assert!(actual.iter().zip(expected).all(|(a, b)| (a - b).abs() < 1e-9), "{r:?}");
+    }
+
+    #[test]
+    fn intersect() {
+        let a = Rectf::from([0.0, 0.0, 10.0, 10.0]);
+        let r = a.intersect(&Rectf::from([5.0, -5.0, 10.0, 10.0]));
+        assert_near(&r, [5.0, 0.0, 5.0, 5.0]);
+        let r = a.intersect(&Rectf::from([20.0, 0.0, 10.0, 10.0]));
+        assert_near(&r, [20.0, 0.0, 0.0, 10.0]);
+    }
+}
@@ -0,0 +1,188 @@
+//! 2D affine transform.
+
+use crate::{rect::Rectf, vec2::Vec2f};
+use std::ops::Mul;
+
+/// 2D affine transform matrix.
+///
+/// Point is transformed as:
+/// ```text
+/// x' = m[0][0] * x + m[0][1] * y + m[0][2]
+/// y' = m[1][0] * x + m[1][1] * y + m[1][2]
+/// ```
+/// Angles are in radians, positive angle rotates clockwise on screen (`y` axis points down).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    /// Matrix rows.
+    pub m: [[f64; 3]; 2],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Transform {
+    /// Identity transform.
+    pub const IDENTITY: Self = Self { m: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]] };
+
+    /// Create translation transform.
+    #[must_use]
+    pub const fn translation(x: f64, y: f64) -> Self {
+        Self { m: [[1.0, 0.0, x], [0.0, 1.0, y]] }
+    }
+
+    /// Create rotation transform.
+    #[must_use]
+    pub fn rotation(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self { m: [[cos, -sin, 0.0], [sin, cos, 0.0]] }
+    }
+
+    /// Create scale transform.
+    #[must_use]
+    pub const fn scaling(sx: f64, sy: f64) -> Self {
+        Self { m: [[sx, 0.0, 0.0], [0.0, sy, 0.0]] }
+    }
+
+    /// Combine transforms. `rhs` is applied first.
+    #[must_use]
+    pub fn multiply(&self, rhs: &Self) -> Self {
+        let (a, b) = (&self.m, &rhs.m);
+        let row = |r: usize| {
+            [
+                a[r][0].mul_add(b[0][0], a[r][1] * b[1][0]),
+                a[r][0].mul_add(b[0][1], a[r][1] * b[1][1]),
+                a[r][0].mul_add(b[0][2], a[r][1].mul_add(b[1][2], a[r][2])),
+            ]
+        };
+        Self { m: [row(0), row(1)] }
+    }
+
+    /// Translate local coordinates. Translation is applied before this transform.
+    #[must_use]
+    pub fn translate(&self, x: f64, y: f64) -> Self {
+        self.multiply(&Self::translation(x, y))
+    }
+
+    /// Rotate local coordinates. Rotation is applied before this transform.
+    #[must_use]
+    pub fn rotate(&self, angle: f64) -> Self {
+        self.multiply(&Self::rotation(angle))
+    }
+
+    /// Scale local coordinates. Scale is applied before this transform.
+    #[must_use]
+    pub fn scale(&self, sx: f64, sy: f64) -> Self {
+        self.multiply(&Self::scaling(sx, sy))
+    }
+
+    /// Transform point.
+    #[must_use]
+    pub const fn apply(&self, p: Vec2f) -> Vec2f {
+        let m = &self.m;
+        Vec2f::new(
+            m[0][0].mul_add(p.x, m[0][1].mul_add(p.y, m[0][2])),
+            m[1][0].mul_add(p.x, m[1][1].mul_add(p.y, m[1][2])),
+        )
+    }
+
+    /// Get bounding box of transformed rectangle.
+    #[must_use]
+    pub fn bounds(&self, rect: &Rectf) -> Rectf {
+        let corners = [
+            self.apply(Vec2f::new(rect.x, rect.y)),
+            self.apply(Vec2f::new(rect.x + rect.w, rect.y)),
+            self.apply(Vec2f::new(rect.x, rect.y + rect.h)),
+            self.apply(Vec2f::new(rect.x + rect.w, rect.y + rect.h)),
+        ];
+        let (mut min, mut max) = (corners[0], corners[0]);
+        for p in &corners[1..] {
+            min = Vec2f::new(min.x.min(p.x), min.y.min(p.y));
+            max = Vec2f::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        Rectf::from([min.x, min.y, max.x - min.x, max.y - min.y])
+    }
+
+    /// Get inverse transform. Returns `None` if transform is degenerate.
+    #[must_use]
+    pub fn inverse(&self) -> Option<Self> {
+        let m = &self.m;
+        let det = m[0][0].mul_add(m[1][1], -m[0][1] * m[1][0]);
+        if det.abs() < f64::EPSILON {
+            return None;
+        }
+        let inv = [[m[1][1] / det, -m[0][1] / det], [-m[1][0] / det, m[0][0] / det]];
+        let row = |r: [f64; 2]| [r[0], r[1], -r[0].mul_add(m[0][2], r[1] * m[1][2])];
+        Some(Self { m: [row(inv[0]), row(inv[1])] })
+    }
+}
+
+impl Mul for Transform {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.multiply(&rhs)
+    }
+}
+
+impl From<[[f64; 3]; 2]> for Transform {
+    fn from(m: [[f64; 3]; 2]) -> Self {
+        Self { m }
+    }
+}
+
+impl From<Transform> for [[f64; 3]; 2] {
+    fn from(t: Transform) -> Self {
+        t.m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::{rect::Rectf, vec2::Vec2f};
+    use std::f64::consts::FRAC_PI_2;
+
+    /// Check that points are equal with some precision.
+    fn assert_near(a: Vec2f, b: Vec2f) {
+        assert!((a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn apply() {
+        let p = Vec2f::new(1.0, 2.0);
+        assert_near(Transform::IDENTITY.apply(p), p);
+        assert_near(Transform::translation(3.0, 4.0).apply(p), Vec2f::new(4.0, 6.0));
+        assert_near(Transform::scaling(2.0, -1.0).apply(p), Vec2f::new(2.0, -2.0));
+        // Clockwise on screen: x axis turns into y axis.
+        assert_near(
+            Transform::rotation(FRAC_PI_2).apply(Vec2f::new(1.0, 0.0)),
+            Vec2f::new(0.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn combine() {
+        let t = Transform::translation(10.0, 0.0).scale(2.0, 2.0);
+        assert_near(t.apply(Vec2f::new(1.0, 1.0)), Vec2f::new(12.0, 2.0));
+        let t = Transform::scaling(2.0, 2.0) * Transform::translation(10.0, 0.0);
+        assert_near(t.apply(Vec2f::new(1.0, 1.0)), Vec2f::new(22.0, 2.0));
+
+        let t = Transform::translation(5.0, -3.0).rotate(0.7).scale(2.0, 0.5);
+        let inv = t.inverse().unwrap();
+        let p = Vec2f::new(-4.0, 9.0);
+        assert_near(inv.apply(t.apply(p)), p);
+        assert_near((t * inv).apply(p), p);
+        assert!(Transform::scaling(0.0, 1.0).inverse().is_none());
+    }
+
+    #[test]
+    fn bounds() {
+        let t = Transform::translation(10.0, 0.0).rotate(FRAC_PI_2);
+        let r = t.bounds(&Rectf::from([0.0, 0.0, 4.0, 2.0]));
+        assert_near(Vec2f::new(r.x, r.y), Vec2f::new(8.0, 0.0));
+        assert_near(Vec2f::new(r.w, r.h), Vec2f::new(2.0, 4.0));
+    }
+}